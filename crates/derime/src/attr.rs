@@ -337,14 +337,20 @@ pub fn parse_attrs<Marker, P: AttrsMatch<Marker>>(
     attrs: &[syn::Attribute],
     parser: P,
 ) -> Result<P::Out, CompileError> {
-    let span = match attrs {
+    // Other attributes, such as doc comments, belong to someone else
+    let attrs: Vec<_> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(namespace))
+        .collect();
+
+    let span = match attrs.as_slice() {
         [] => None,
         [attr] => Some(attr.span()),
-        [attr1, .., attr2] => Some(attr1.span().join(attr2.span()).unwrap()),
+        [attr1, .., attr2] => Some(attr1.span().join(attr2.span()).unwrap_or(attr1.span())),
     };
 
     let entries: Vec<_> = attrs
-        .iter()
+        .into_iter()
         .map(|attr| key_value(attr, namespace))
         .filter_map(|r| r.transpose())
         .collect::<Result<_, _>>()?;
@@ -391,6 +397,24 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn skip_doc_comments() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            /// Documented
+            #[test(myprop = true)]
+            struct A {}
+        };
+
+        let result = super::AttributesParser::new("test")
+            .parse(
+                &input.attrs,
+                super::OptionalAttr((super::KeyIdent("myprop"), super::BoolParser)),
+            )
+            .unwrap();
+
+        assert_eq!(result, Some(true));
+    }
+
     #[test]
     fn skip_field_others() {
         let input: syn::DeriveInput = syn::parse_quote! {
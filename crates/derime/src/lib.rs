@@ -19,5 +19,7 @@ macro_rules! span_compile_error(
 );
 
 pub use attr::*;
+pub use serde_attrs::SerdeField;
 
 mod attr;
+mod serde_attrs;
@@ -0,0 +1,51 @@
+use crate::CompileError;
+
+/// `#[serde(...)]` attrs of a field that change how it is serialized
+#[derive(Default)]
+pub struct SerdeField {
+    pub flatten: bool,
+    pub skip: bool,
+    pub rename: Option<String>,
+    pub skip_serializing_if: Option<syn::ExprPath>,
+}
+
+impl SerdeField {
+    pub fn parse(field: &syn::Field) -> Result<Self, CompileError> {
+        let mut parsed = SerdeField::default();
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("serde"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("flatten") {
+                    parsed.flatten = true;
+                } else if meta.path.is_ident("skip") {
+                    parsed.skip = true;
+                } else if meta.path.is_ident("rename") && meta.input.peek(syn::Token![=]) {
+                    let name: syn::LitStr = meta.value()?.parse()?;
+                    parsed.rename = Some(name.value());
+                } else if meta.path.is_ident("skip_serializing_if") {
+                    let path: syn::LitStr = meta.value()?.parse()?;
+                    parsed.skip_serializing_if = Some(path.parse()?);
+                } else if meta.input.peek(syn::Token![=]) {
+                    // Other attrs do not change the stored field, such as `default = "..."`
+                    meta.value()?.parse::<syn::Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    meta.input.parse::<proc_macro2::Group>()?;
+                }
+
+                Ok(())
+            })
+            .map_err(CompileError)?;
+        }
+
+        Ok(parsed)
+    }
+
+    /// Serialized name of the field
+    pub fn name(&self, field: &syn::Ident) -> String {
+        self.rename.clone().unwrap_or_else(|| field.to_string())
+    }
+}
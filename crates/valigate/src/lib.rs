@@ -1,7 +1,10 @@
+use schema::Schema;
 use std::{borrow::Cow, collections::HashMap, error::Error as StdError};
 pub use valigate_derive::Valid;
 use valigate_derive::impl_gates_tup;
 
+pub mod schema;
+
 pub enum GateResult<T, E> {
     Ok(T),
     ErrCut(E),
@@ -13,6 +16,9 @@ pub trait Gate<I> {
     type Err;
 
     fn parse(self, input: I) -> GateResult<Self::Out, Self::Err>;
+
+    /// Adds the constraints of this gate to the schema of its input
+    fn describe(&self, _schema: &mut Schema) {}
 }
 
 impl_gates_tup!();
@@ -90,6 +96,11 @@ collect_errs!(E0, E1, E2, E3, E4, E5, E6, E7, E8, E9);
 pub trait Valid: Sized {
     type In;
     fn parse(input: Self::In) -> Result<Self, Error>;
+
+    /// Schema of `Self::In`, including the constraints of its gates
+    fn schema() -> Schema {
+        Schema::any()
+    }
 }
 
 pub trait GatedField: Valid {
@@ -200,6 +211,53 @@ impl std::fmt::Display for ErrorKey {
     }
 }
 
+/// Error message of a field, located by the JSON pointer of its input
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct PointedError {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl Error {
+    /// Flattens the tree into one error per message, pointed with RFC 6901 JSON pointers
+    /// sorted by pointer. Errors of the whole input have an empty pointer
+    pub fn flatten(&self) -> Vec<PointedError> {
+        fn collect(error: &Error, pointer: &mut String, out: &mut Vec<PointedError>) {
+            match error {
+                Error::Field(errors) => {
+                    out.extend(errors.0.iter().map(|err| PointedError {
+                        pointer: pointer.clone(),
+                        message: err.0.to_string(),
+                    }));
+                }
+
+                Error::Map(errors) => {
+                    for (key, err) in &errors.0 {
+                        let len = pointer.len();
+                        pointer.push('/');
+                        match key {
+                            ErrorKey::Index(i) => pointer.push_str(&i.to_string()),
+                            ErrorKey::Field(field) => {
+                                pointer.push_str(&field.replace('~', "~0").replace('/', "~1"))
+                            }
+                        }
+
+                        collect(err, pointer, out);
+                        pointer.truncate(len);
+                    }
+                }
+            }
+        }
+
+        let mut out = vec![];
+        collect(self, &mut String::new(), &mut out);
+        // map errors are unordered
+        out.sort_by(|a, b| a.pointer.cmp(&b.pointer));
+
+        out
+    }
+}
+
 pub fn field_pipe<In, G>(input: In, gate: G) -> Result<G::Out, Error>
 where
     G: Gate<In, Err: CollectsErrors>,
@@ -281,6 +339,14 @@ pub mod gates {
 
             crate::GateResult::Ok(input)
         }
+
+        fn describe(&self, schema: &mut crate::schema::Schema) {
+            if schema.is_array() {
+                schema.min_items = Some(self.0);
+            } else {
+                schema.min_length = Some(self.0);
+            }
+        }
     }
 
     pub struct MaxLen(pub usize);
@@ -311,6 +377,14 @@ pub mod gates {
 
             crate::GateResult::Ok(input)
         }
+
+        fn describe(&self, schema: &mut crate::schema::Schema) {
+            if schema.is_array() {
+                schema.max_items = Some(self.0);
+            } else {
+                schema.max_length = Some(self.0);
+            }
+        }
     }
 
     pub struct LessThan(pub usize);
@@ -332,58 +406,73 @@ pub mod gates {
 
             crate::GateResult::Ok(input)
         }
+
+        fn describe(&self, schema: &mut crate::schema::Schema) {
+            schema.exclusive_maximum = Some(self.0 as f64);
+        }
     }
 }
 
 mod valids {
     use std::collections::HashMap;
 
-    use crate::{Error, ErrorKey, Valid};
+    use crate::{
+        Error, ErrorKey, Valid,
+        schema::{Schema, SchemaType},
+    };
 
     macro_rules! impl_valid {
-        (no: where $($tp: ident : $pt: path),*: for $ty:ty) => {
+        (no: where $($tp: ident : $pt: path),*: for $ty:ty => $schema:expr) => {
             impl< $($tp: $pt),* > Valid for $ty {
                 type In = $ty;
                 fn parse(input: Self::In) -> Result<Self, crate::Error> {
                     Ok(input)
                 }
+
+                fn schema() -> Schema {
+                    $schema
+                }
             }
         };
 
-        (no: $ty:ty) => {
+        (no: $ty:ty => $schema:expr) => {
             impl Valid for $ty {
                 type In = $ty;
                 fn parse(input: Self::In) -> Result<Self, crate::Error> {
                     Ok(input)
                 }
+
+                fn schema() -> Schema {
+                    $schema
+                }
             }
         };
     }
 
-    impl_valid!(no: usize);
-    impl_valid!(no: u8);
-    impl_valid!(no: u16);
-    impl_valid!(no: u32);
-    impl_valid!(no: u64);
-    impl_valid!(no: u128);
+    impl_valid!(no: usize => Schema::new(SchemaType::Integer).with_format("uint"));
+    impl_valid!(no: u8 => Schema::new(SchemaType::Integer).with_format("uint8"));
+    impl_valid!(no: u16 => Schema::new(SchemaType::Integer).with_format("uint16"));
+    impl_valid!(no: u32 => Schema::new(SchemaType::Integer).with_format("uint32"));
+    impl_valid!(no: u64 => Schema::new(SchemaType::Integer).with_format("uint64"));
+    impl_valid!(no: u128 => Schema::new(SchemaType::Integer).with_format("uint128"));
 
-    impl_valid!(no: isize);
-    impl_valid!(no: i8);
-    impl_valid!(no: i16);
-    impl_valid!(no: i32);
-    impl_valid!(no: i64);
-    impl_valid!(no: i128);
+    impl_valid!(no: isize => Schema::new(SchemaType::Integer).with_format("int"));
+    impl_valid!(no: i8 => Schema::new(SchemaType::Integer).with_format("int8"));
+    impl_valid!(no: i16 => Schema::new(SchemaType::Integer).with_format("int16"));
+    impl_valid!(no: i32 => Schema::new(SchemaType::Integer).with_format("int32"));
+    impl_valid!(no: i64 => Schema::new(SchemaType::Integer).with_format("int64"));
+    impl_valid!(no: i128 => Schema::new(SchemaType::Integer).with_format("int128"));
 
-    impl_valid!(no: String);
-    impl_valid!(no: Box<str>);
+    impl_valid!(no: String => Schema::new(SchemaType::String));
+    impl_valid!(no: Box<str> => Schema::new(SchemaType::String));
 
-    impl_valid!(no: std::path::PathBuf);
+    impl_valid!(no: std::path::PathBuf => Schema::new(SchemaType::String));
 
-    impl_valid!(no: std::time::Duration);
-    impl_valid!(no: std::time::Instant);
-    impl_valid!(no: std::time::SystemTime);
+    impl_valid!(no: std::time::Duration => Schema::any());
+    impl_valid!(no: std::time::Instant => Schema::any());
+    impl_valid!(no: std::time::SystemTime => Schema::any());
 
-    impl_valid!(no: std::net::SocketAddr);
+    impl_valid!(no: std::net::SocketAddr => Schema::new(SchemaType::String));
 
     impl<V: Valid> Valid for Vec<V> {
         type In = Vec<V::In>;
@@ -416,19 +505,62 @@ mod valids {
 
             Err(Error::Map(crate::MapErrors(errors)))
         }
+
+        fn schema() -> Schema {
+            Schema::array(V::schema())
+        }
     }
 
     #[cfg(feature = "chrono")]
     mod chrono_impl {
         use super::Valid;
+        use crate::schema::{Schema, SchemaType};
+
+        impl_valid!(no: where Tz: chrono::TimeZone: for chrono::DateTime<Tz> =>
+            Schema::new(SchemaType::String).with_format("date-time"));
+        impl_valid!(no: chrono::NaiveDate => Schema::new(SchemaType::String).with_format("date"));
+        impl_valid!(no: chrono::Weekday => Schema::new(SchemaType::String));
+        impl_valid!(no: chrono::TimeDelta => Schema::any());
+        impl_valid!(no: chrono::Months => Schema::any());
+        impl_valid!(no: chrono::Days => Schema::any());
+        impl_valid!(no: chrono::NaiveWeek => Schema::any());
+        impl_valid!(no: chrono::Month => Schema::new(SchemaType::String));
+    }
+}
+
+#[doc(hidden)]
+pub mod __private {
+    //! Used by the derive to describe field types that may not implement `Valid`
+    use std::marker::PhantomData;
 
-        impl_valid!(no: where Tz: chrono::TimeZone: for chrono::DateTime<Tz>);
-        impl_valid!(no: chrono::NaiveDate);
-        impl_valid!(no: chrono::Weekday);
-        impl_valid!(no: chrono::TimeDelta);
-        impl_valid!(no: chrono::Months);
-        impl_valid!(no: chrono::Days);
-        impl_valid!(no: chrono::NaiveWeek);
-        impl_valid!(no: chrono::Month);
+    use crate::{Valid, schema::Schema};
+
+    pub struct SchemaProbe<T>(PhantomData<fn() -> T>);
+
+    impl<T> SchemaProbe<T> {
+        #[allow(clippy::new_without_default)]
+        pub const fn new() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    pub trait ValidSchema {
+        fn schema(&self) -> Schema;
+    }
+
+    impl<T: Valid> ValidSchema for SchemaProbe<T> {
+        fn schema(&self) -> Schema {
+            T::schema()
+        }
+    }
+
+    pub trait AnySchema {
+        fn schema(&self) -> Schema;
+    }
+
+    impl<T> AnySchema for &SchemaProbe<T> {
+        fn schema(&self) -> Schema {
+            Schema::any()
+        }
     }
 }
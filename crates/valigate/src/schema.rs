@@ -0,0 +1,93 @@
+//! JSON Schema description of `Valid` inputs, only the subset needed to document gates
+
+use std::{borrow::Cow, collections::BTreeMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaType {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+/// An empty schema accepts any value
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schema {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub ty: Option<SchemaType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<Cow<'static, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<Cow<'static, str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_items: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_items: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclusive_maximum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<Schema>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<Cow<'static, str>, Schema>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<Cow<'static, str>>,
+}
+
+impl Schema {
+    pub fn new(ty: SchemaType) -> Self {
+        Self {
+            ty: Some(ty),
+            ..Default::default()
+        }
+    }
+
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    pub fn array(items: Schema) -> Self {
+        Self {
+            items: Some(Box::new(items)),
+            ..Self::new(SchemaType::Array)
+        }
+    }
+
+    pub fn object() -> Self {
+        Self::new(SchemaType::Object)
+    }
+
+    pub fn with_format(mut self, format: impl Into<Cow<'static, str>>) -> Self {
+        self.format = Some(format.into());
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<Cow<'static, str>>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn property(&mut self, name: impl Into<Cow<'static, str>>, schema: Schema, required: bool) {
+        let name = name.into();
+        if required {
+            self.required.push(name.clone());
+        }
+
+        self.properties.insert(name, schema);
+    }
+
+    /// Whether the value is described as an array, used by length gates
+    pub fn is_array(&self) -> bool {
+        self.ty == Some(SchemaType::Array)
+    }
+}
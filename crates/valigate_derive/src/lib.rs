@@ -78,6 +78,13 @@ fn do_impl_gates_tup(limit: usize) -> proc_macro2::TokenStream {
                     errs => GateResult::ErrPass(out, GateErrors(errs)),
                 }
             }
+
+            fn describe(&self, schema: &mut crate::schema::Schema) {
+                let (g0, #(#gates_lets),*) = self;
+
+                g0.describe(schema);
+                #(#gates_lets.describe(schema);)*
+            }
         }
     };
 
@@ -96,7 +103,7 @@ pub fn derive_valid(tokens: TokenStream) -> TokenStream {
 mod derive_valid {
     use std::str::FromStr;
 
-    use derime::CompileError;
+    use derime::{CompileError, SerdeField};
     use quote::quote;
     use syn::{FieldsUnnamed, spanned::Spanned};
 
@@ -214,6 +221,8 @@ mod derive_valid {
         input: syn::Type,
     ) -> proc_macro2::TokenStream {
         let gate = gate.unwrap_or_else(|| syn::parse_quote!( #path::gates::NoopField ));
+        let input_schema = schema_probe(&path, &input);
+
         quote! {
             impl #path::Valid for #ident {
                 type In = #input;
@@ -221,6 +230,24 @@ mod derive_valid {
                 fn parse(input: Self::In) -> Result<Self, #path::Error> {
                     #path::field_pipe(input, #gate).map(#ident)
                 }
+
+                fn schema() -> #path::schema::Schema {
+                    let mut schema = #input_schema;
+                    <_ as #path::Gate<#input>>::describe(&(#gate), &mut schema);
+
+                    schema
+                }
+            }
+        }
+    }
+
+    /// Schema of a type that may not implement `Valid`, falls back to any
+    fn schema_probe(path: &proc_macro2::TokenStream, ty: &syn::Type) -> proc_macro2::TokenStream {
+        quote! {
+            {
+                #[allow(unused_imports)]
+                use #path::__private::{AnySchema as _, ValidSchema as _};
+                (&#path::__private::SchemaProbe::<#ty>::new()).schema()
             }
         }
     }
@@ -238,6 +265,12 @@ mod derive_valid {
         let custom_gate_ident = quote::format_ident!("{}Gate", &ident);
         let custom_err_ident = quote::format_ident!("{}Error", &ident);
 
+        let describe_gate = gate.as_ref().map(|gate| {
+            quote! {
+                <_ as #path::Gate<#ident>>::describe(&(#gate), &mut schema);
+            }
+        });
+
         let gate: syn::Expr = match gate {
             Some(gate) => syn::parse_quote!((#custom_gate_ident, #gate)),
             None => syn::parse_quote!(#custom_gate_ident),
@@ -401,6 +434,31 @@ mod derive_valid {
             .cloned()
             .collect::<Vec<_>>();
 
+        // Properties and errors are keyed like the input is deserialized
+        let serde_names = fields
+            .iter()
+            .map(|field| {
+                SerdeField::parse(field).map(|serde| serde.name(field.ident.as_ref().unwrap()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let properties = fields_meta.iter().zip(&serde_names).map(|(meta, name)| {
+            let (ty, required) = match &meta.ty {
+                FieldValidType::Required(ty) => (ty, true),
+                FieldValidType::Optional { inner } => (inner, false),
+            };
+
+            let field_schema = if meta.skip {
+                schema_probe(&path, ty)
+            } else {
+                quote! { <#ty as #path::Valid>::schema() }
+            };
+
+            quote! {
+                schema.property(#name, #field_schema, #required);
+            }
+        });
+
         let derive_serde = if serde_top_attrs.is_some() {
             quote! { #[derive(serde::Deserialize)] }
         } else {
@@ -431,11 +489,11 @@ mod derive_valid {
                     #(
                         match self.#names {
                             #path::MaybeFieldError::Invalid(e) => {
-                                map.insert_field(stringify!(#names), e);
+                                map.insert_field(#serde_names, e);
                             }
                             #path::MaybeFieldError::Missing => {
-                                let error = #path::FieldErrors::from_one(#path::MissingField(stringify!(#names)));
-                                map.insert_field(stringify!(#names), error.into());
+                                let error = #path::FieldErrors::from_one(#path::MissingField(#serde_names));
+                                map.insert_field(#serde_names, error.into());
                             }
                             _ => {}
                         }
@@ -465,6 +523,14 @@ mod derive_valid {
                 fn parse(input: Self::In) -> Result<Self, #path::Error> {
                     #path::field_pipe(input, #gate)
                 }
+
+                fn schema() -> #path::schema::Schema {
+                    let mut schema = #path::schema::Schema::object();
+                    #(#properties)*
+                    #describe_gate
+
+                    schema
+                }
             }
 
         };
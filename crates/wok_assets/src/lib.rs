@@ -184,7 +184,7 @@ where
                     .map_err(|err| Error(origin.clone(), valigate::ErrorDisplay(err)))?;
 
                 init.insert_all(a);
-                Ok(())
+                Ok::<_, WokUnknownError>(())
             },
         );
     }
@@ -0,0 +1,399 @@
+//! Authentication through a user defined verification system
+//! ```rust
+//! use wok::prelude::*;
+//! use wok_axum::{AxumPlugin, ConfigureRoute, Route, get, auth::{AuthPlugin, Credentials, HasRoles, Principal}};
+//!
+//! #[derive(Clone)]
+//! struct User {
+//!     admin: bool,
+//! }
+//!
+//! impl HasRoles for User {
+//!     fn has_role(&self, role: &str) -> bool {
+//!         role == "admin" && self.admin
+//!     }
+//! }
+//!
+//! #[derive(Resource)]
+//! struct KeyStore(Vec<String>);
+//!
+//! async fn verify(In(credentials): In<Credentials>, keys: Res<'_, KeyStore>) -> Option<User> {
+//!     let admin = keys.0.iter().any(|key| key == credentials.token());
+//!     Some(User { admin })
+//! }
+//!
+//! async fn me(In(Principal(user)): In<Principal<User>>) -> &'static str {
+//!     if user.admin { "admin" } else { "user" }
+//! }
+//!
+//! App::default()
+//!     .add_plugin(AxumPlugin)
+//!     .add_systems(Route("/me"), get(me))
+//!     .add_systems(Route("/admin"), get(me).require("admin"))
+//!     .add_plugin(AuthPlugin::new(verify).cookie("session"));
+//! ```
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{HeaderMap, header, request::Parts},
+    middleware::Next,
+    response::IntoResponse,
+};
+use wok::{
+    plugin::Plugin,
+    prelude::{
+        BorrowMutParam, ConfigureWorld, In, IntoSystem, ProtoTaskSystem, System, WokUnknownError,
+    },
+    remote_gateway::RemoteWorldPorts,
+};
+use wok_core::world::{World, WorldCenter};
+
+use crate::{
+    ConfigureRoute, Layer, MethodRouterMut,
+    openapi::{OperationInput, PathItem, Response},
+    response::Permitted,
+};
+
+/// Credentials found in the request
+#[derive(Debug, Clone)]
+pub enum Credentials {
+    /// `Authorization: Bearer <token>`, the scheme is case insensitive
+    Bearer(String),
+    /// Value of the configured session cookie
+    Cookie(String),
+}
+
+impl Credentials {
+    pub fn token(&self) -> &str {
+        match self {
+            Credentials::Bearer(token) | Credentials::Cookie(token) => token,
+        }
+    }
+
+    /// Prefers the bearer token over the session cookie
+    pub fn from_headers(headers: &HeaderMap, cookie: Option<&str>) -> Option<Self> {
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split_once(' '))
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("Bearer"))
+            .map(|(_, token)| Credentials::Bearer(token.trim().to_owned()));
+
+        if bearer.is_some() {
+            return bearer;
+        }
+
+        let cookie = cookie?;
+        headers
+            .get_all(header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(name, _)| *name == cookie)
+            .map(|(_, value)| Credentials::Cookie(value.to_owned()))
+    }
+}
+
+/// Roles checked by `require(role)`
+pub trait HasRoles {
+    fn has_role(&self, role: &str) -> bool;
+}
+
+/// Output of a verification system, `None` means the credentials are not valid
+pub trait VerifyOutput<P>: Send + 'static {
+    fn into_principal(self) -> Result<Option<P>, WokUnknownError>;
+}
+
+impl<P: Send + 'static> VerifyOutput<P> for Option<P> {
+    fn into_principal(self) -> Result<Option<P>, WokUnknownError> {
+        Ok(self)
+    }
+}
+
+impl<P: Send + 'static, E: Into<WokUnknownError> + Send + 'static> VerifyOutput<P>
+    for Result<Option<P>, E>
+{
+    #[track_caller]
+    fn into_principal(self) -> Result<Option<P>, WokUnknownError> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(WokUnknownError::convert(err)),
+        }
+    }
+}
+
+/// The verified principal, rejects with `401 Unauthorized` when the request has none
+#[derive(Debug, Clone)]
+pub struct Principal<P>(pub P);
+
+impl<S: Send + Sync, P: Clone + Send + Sync + 'static> FromRequestParts<S> for Principal<P> {
+    type Rejection = axum::response::Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Principal<P>>()
+            .cloned()
+            .ok_or_else(|| Permitted::<()>::Unauthorized.into_response())
+    }
+}
+
+impl<P> OperationInput for Principal<P> {
+    fn operation_input(operation: &mut crate::openapi::Operation) {
+        operation.response("401", Response::new("Unauthorized"));
+    }
+}
+
+/// Type erased roles of the principal, read by `require(role)`
+#[derive(Clone)]
+struct AuthRoles(Arc<dyn HasRoles + Send + Sync>);
+
+/// Verifies the credentials of every request with a system, which can borrow world resources
+/// The system params are only reserved while verifying, not for the whole request
+pub struct AuthPlugin<S, P> {
+    system: S,
+    cookie: Option<&'static str>,
+    _marker: std::marker::PhantomData<fn() -> P>,
+}
+
+impl<S, P> AuthPlugin<S, P> {
+    pub fn new<Sys, Marker>(system: Sys) -> Self
+    where
+        Sys: IntoSystem<Marker, System = S>,
+        S: System<In = In<Credentials>, Out: VerifyOutput<P>>,
+    {
+        Self {
+            system: system.into_system(),
+            cookie: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Reads credentials from this cookie when there is no bearer token
+    pub fn cookie(mut self, name: &'static str) -> Self {
+        self.cookie = Some(name);
+        self
+    }
+}
+
+impl<S, P> Plugin for AuthPlugin<S, P>
+where
+    S: ProtoTaskSystem<Param: BorrowMutParam>,
+    S: System<In = In<Credentials>, Out: VerifyOutput<P>>,
+    P: HasRoles + Clone + Send + Sync + 'static,
+{
+    fn setup(self, app: &mut wok::prelude::App) {
+        let system = app.world_mut().center.register_system(self.system);
+        let cookie = self.cookie;
+
+        // Not a `MiddlPartsIn` system: that one keeps its params reserved while `next` runs,
+        // which would lock the verifier's resources for the whole request
+        let handler = move |world: axum::extract::Extension<RemoteWorldPorts>,
+                            mut req: Request,
+                            next: Next| {
+            let system = system.clone();
+            async move {
+                let Some(credentials) = Credentials::from_headers(req.headers(), cookie) else {
+                    return next.run(req).await;
+                };
+
+                let out = world
+                    .reserver()
+                    .reserve(system.entry_ref())
+                    .await
+                    .task()
+                    .run(credentials.clone())
+                    .await;
+
+                match out.into_principal() {
+                    Ok(Some(principal)) => {
+                        let roles = AuthRoles(Arc::new(principal.clone()));
+                        req.extensions_mut().insert(roles);
+                        req.extensions_mut().insert(Principal(principal));
+                        // verified, read by `limit::RateScope::Principal`
+                        req.extensions_mut().insert(credentials);
+                    }
+                    // Routes reject anonymous requests themselves
+                    Ok(None) => {}
+                    Err(err) => {
+                        tracing::error!(%err);
+                        return axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                    }
+                }
+
+                next.run(req).await
+            }
+        };
+
+        app.add_objs(Layer, axum::middleware::from_fn(handler));
+    }
+}
+
+/// Route that only runs when the principal has the role, see `ConfigureRoute::require`
+pub struct Require<R> {
+    pub(crate) route: R,
+    pub(crate) role: &'static str,
+}
+
+impl<R: ConfigureRoute> ConfigureRoute for Require<R> {
+    fn cfg(self, router: &mut MethodRouterMut<'_>, world: &mut WorldCenter) {
+        let role = self.role;
+        let guarded = self
+            .route
+            .into_route(world)
+            .layer(axum::middleware::from_fn(
+                move |req: Request, next: Next| async move {
+                    match req.extensions().get::<AuthRoles>() {
+                        None => Permitted::<()>::Unauthorized.into_response(),
+                        Some(roles) if !roles.0.has_role(role) => {
+                            Permitted::<()>::Forbidden.into_response()
+                        }
+                        Some(_) => next.run(req).await,
+                    }
+                },
+            ));
+
+        router.mutate(move |router| router.merge(guarded));
+    }
+
+    fn setup(&self, world: &mut World) {
+        self.route.setup(world);
+    }
+
+    fn docs(&self, item: &mut PathItem) {
+        let mut guarded = PathItem::default();
+        self.route.docs(&mut guarded);
+
+        for operation in guarded.operations_mut() {
+            operation.response("401", Response::new("Unauthorized"));
+            operation.response("403", Response::new("Forbidden"));
+        }
+
+        item.merge(guarded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn prefers_bearer_over_cookie() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; session=abc"),
+        );
+
+        let credentials = Credentials::from_headers(&headers, Some("session"));
+        assert!(matches!(credentials, Some(Credentials::Cookie(token)) if token == "abc"));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer xyz"),
+        );
+        let credentials = Credentials::from_headers(&headers, Some("session"));
+        assert!(matches!(credentials, Some(Credentials::Bearer(token)) if token == "xyz"));
+    }
+
+    #[test]
+    fn matches_the_bearer_scheme_in_any_case() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("bEARER xyz"),
+        );
+        let credentials = Credentials::from_headers(&headers, None);
+        assert!(matches!(credentials, Some(Credentials::Bearer(token)) if token == "xyz"));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Basic eHl6"),
+        );
+        assert!(Credentials::from_headers(&headers, None).is_none());
+    }
+
+    #[test]
+    fn ignores_cookies_unless_configured() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_static("session=abc"));
+
+        assert!(Credentials::from_headers(&headers, None).is_none());
+    }
+
+    #[cfg(feature = "testing")]
+    mod app {
+        use axum::{body::Body, http::Request};
+        use wok::prelude::*;
+
+        use crate::{AxumPlugin, ConfigureRoute, Route, auth::*, get, testing::TestApp};
+
+        #[derive(Clone)]
+        struct User {
+            admin: bool,
+        }
+
+        impl HasRoles for User {
+            fn has_role(&self, role: &str) -> bool {
+                role == "admin" && self.admin
+            }
+        }
+
+        async fn verify(In(credentials): In<Credentials>) -> Option<User> {
+            match credentials.token() {
+                "root" => Some(User { admin: true }),
+                "guest" => Some(User { admin: false }),
+                _ => None,
+            }
+        }
+
+        async fn me(In(Principal(user)): In<Principal<User>>) -> &'static str {
+            if user.admin { "admin" } else { "user" }
+        }
+
+        fn get_as(uri: &str, authorization: Option<&str>) -> Request<Body> {
+            let mut request = Request::get(uri);
+            if let Some(authorization) = authorization {
+                request = request.header(header::AUTHORIZATION, authorization);
+            }
+
+            request.body(Body::empty()).unwrap()
+        }
+
+        #[tokio::test]
+        async fn guards_routes_by_principal_and_role() {
+            let app = App::default()
+                .add_plugin(AxumPlugin)
+                .add_systems(Route("/me"), get(me))
+                .add_systems(Route("/admin"), get(me).require("admin"))
+                .add_plugin(AuthPlugin::new(verify));
+
+            TestApp::new(app)
+                .run(|client| async move {
+                    assert_eq!(client.get("/me").await.status(), 401);
+                    assert_eq!(client.get("/admin").await.status(), 401);
+
+                    let res = client
+                        .request(get_as("/admin", Some("Bearer nobody")))
+                        .await;
+                    assert_eq!(res.status(), 401);
+
+                    let res = client.request(get_as("/admin", Some("Bearer guest"))).await;
+                    assert_eq!(res.status(), 403);
+                    let res = client.request(get_as("/me", Some("Bearer guest"))).await;
+                    assert_eq!(res.text(), "user");
+
+                    let res = client.request(get_as("/admin", Some("bearer root"))).await;
+                    assert_eq!(res.status(), 200);
+                    assert_eq!(res.text(), "admin");
+                })
+                .await
+                .unwrap();
+        }
+    }
+}
@@ -1,17 +1,31 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 
-use axum::Json;
+use axum::{Json, extract::Query, http::StatusCode};
+use valigate::schema::{Schema, SchemaType};
 use wok::{
     plugin::Plugin,
-    prelude::{ConfigureWorld, In, Res, Resource, WokUnknownError},
+    prelude::{ConfigureWorld, In, IntoBlockingSystem, Res, Resource, WokUnknownError},
 };
 use wok_db::{
     Record,
-    db::{DbCreate, DbDelete, DbList, DbSelectSingle, DbQuery},
+    db::{
+        DbCreate, DbDelete, DbListPage, DbMerge, DbQuery, DbSelectSingle, DbUpdate, DbUpdateError,
+        FilterType, ListParams,
+    },
     id_strategy::IdStrategy,
+    query::Condition,
 };
 
-use crate::{Route, delete, get, post};
+use crate::{
+    Route, delete,
+    extract::{JsonG, PathG},
+    get,
+    openapi::{Operation, OperationInput, OperationOutput, Parameter, ParameterIn, Response},
+    patch, post,
+    problem::Problem,
+    put,
+    response::{Deleted, Maybe},
+};
 
 pub struct CRUDCfgBuilder<Db = (), IdStrategy = ()>(std::marker::PhantomData<(Db, IdStrategy)>);
 
@@ -75,11 +89,41 @@ impl<R: Record, Config: CrudConfig> RoutePluginBuilder<R, Config> {
         }
     }
 
-    pub const fn list_all<D: serde::Serialize>(&self) -> ListAllPlugin<'_, R, D, Config>
+    /// Lists records with `limit`, `offset` and `after` (cursor) query params, any other param
+    /// is an equality filter on a field allowed with `.filterable(...)`
+    pub const fn list_all<D>(&self) -> ListAllPlugin<'_, R, D, Config>
     where
-        Config::Db: DbList<wok_db::RecordEntry<R, D>>,
+        D: serde::Serialize + valigate::Valid,
+        Config::Db: DbListPage<R, wok_db::RecordEntry<R, D>>,
     {
         ListAllPlugin {
+            builder: self,
+            filterable: &[],
+            max_limit: None,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Replaces a record with a PUT request
+    pub const fn update_one<D: valigate::Valid>(&self) -> UpdateOnePlugin<'_, R, D, Config>
+    where
+        Config::Db: DbUpdate<R, D>,
+        D::In: serde::de::DeserializeOwned,
+    {
+        UpdateOnePlugin {
+            builder: self,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Merges a partial record with a PATCH request, `P` is usually a `Valid` struct with
+    /// optional fields which skip serializing when absent
+    pub const fn patch_one<P: valigate::Valid>(&self) -> PatchOnePlugin<'_, R, P, Config>
+    where
+        Config::Db: DbMerge<R, P>,
+        P::In: serde::de::DeserializeOwned,
+    {
+        PatchOnePlugin {
             builder: self,
             _marker: std::marker::PhantomData,
         }
@@ -108,7 +152,7 @@ impl<R: Record, Config: CrudConfig> RoutePluginBuilder<R, Config> {
 
     pub const fn get_one<D>(&self) -> GetOnePlugin<'_, R, D, Config>
     where
-        D: serde::Serialize,
+        D: serde::Serialize + valigate::Valid,
         Config::Db: DbSelectSingle<R, wok_db::RecordEntry<R, D>>,
     {
         GetOnePlugin {
@@ -120,28 +164,236 @@ impl<R: Record, Config: CrudConfig> RoutePluginBuilder<R, Config> {
 
 pub struct ListAllPlugin<'b, R: Record, D: serde::Serialize, Config: CrudConfig>
 where
-    Config::Db: DbList<wok_db::RecordEntry<R, D>>,
+    Config::Db: DbListPage<R, wok_db::RecordEntry<R, D>>,
 {
     builder: &'b RoutePluginBuilder<R, Config>,
+    filterable: &'static [(&'static str, FilterType)],
+    max_limit: Option<usize>,
     _marker: std::marker::PhantomData<fn(D)>,
 }
 
+impl<'b, R: Record, D: serde::Serialize, Config: CrudConfig> ListAllPlugin<'b, R, D, Config>
+where
+    Config::Db: DbListPage<R, wok_db::RecordEntry<R, D>>,
+{
+    /// Fields that can be filtered by equality through query params, values are parsed by the
+    /// declared type of their field
+    pub const fn filterable(mut self, fields: &'static [(&'static str, FilterType)]) -> Self {
+        self.filterable = fields;
+        self
+    }
+
+    /// Caps the `limit` query param, also used when it is missing
+    pub const fn max_limit(mut self, max_limit: usize) -> Self {
+        self.max_limit = Some(max_limit);
+        self
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ListQueryError {
+    #[error("expected a non negative integer")]
+    NotInteger,
+    #[error("invalid record id")]
+    InvalidId,
+    #[error("cannot filter by this field")]
+    NotFilterable,
+    #[error("value does not match the field type")]
+    InvalidFilter,
+}
+
+fn deserialize_id<R: serde::de::DeserializeOwned>(
+    id: String,
+) -> Result<R, serde::de::value::Error> {
+    R::deserialize(IdDeserializer(id))
+}
+
+/// Deserializes a raw id like `axum::extract::Path` does, newtype ids are unwrapped and
+/// numbers or booleans are parsed from the text
+struct IdDeserializer(String);
+
+macro_rules! parse_id {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: serde::de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Str(&self.0),
+                        &visitor,
+                    )),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for IdDeserializer {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_option<V: serde::de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: serde::de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: serde::de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        use serde::de::IntoDeserializer;
+
+        let deserializer: serde::de::value::StringDeserializer<Self::Error> =
+            self.0.into_deserializer();
+        deserializer.deserialize_enum(name, variants, visitor)
+    }
+
+    parse_id! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+/// Collects every invalid param, keyed by its name
+fn list_params<R: serde::de::DeserializeOwned>(
+    query: HashMap<String, String>,
+    filterable: &[(&'static str, FilterType)],
+    max_limit: Option<usize>,
+) -> Result<ListParams<R>, valigate::Error> {
+    let mut params = ListParams::default();
+    let mut errors = valigate::MapErrors::default();
+    let mut invalid = false;
+
+    let mut reject = |key: String, err: ListQueryError| {
+        errors.insert_field(key, valigate::FieldErrors::from_one(err).into());
+        invalid = true;
+    };
+
+    for (key, value) in query {
+        match key.as_str() {
+            "limit" => match value.parse() {
+                Ok(limit) => params.limit = Some(limit),
+                Err(_) => reject(key, ListQueryError::NotInteger),
+            },
+            "offset" => match value.parse() {
+                Ok(offset) => params.start = Some(offset),
+                Err(_) => reject(key, ListQueryError::NotInteger),
+            },
+            "after" => match deserialize_id(value) {
+                Ok(after) => params.after = Some(after),
+                Err(_) => reject(key, ListQueryError::InvalidId),
+            },
+            field => match filterable.iter().find(|(allowed, _)| *allowed == field) {
+                Some((field, filter_type)) => match filter_type.parse(&value) {
+                    Some(value) => params = params.filter(Condition::field_eq(*field, value)),
+                    None => reject(key, ListQueryError::InvalidFilter),
+                },
+                None => reject(key, ListQueryError::NotFilterable),
+            },
+        }
+    }
+
+    if invalid {
+        return Err(errors.into());
+    }
+
+    if let Some(max_limit) = max_limit {
+        params.limit = Some(params.limit.map_or(max_limit, |limit| limit.min(max_limit)));
+    }
+
+    Ok(params)
+}
+
+/// Record ID of the `{id}` path param, rejected with a `/id` pointer when it does not parse
+pub struct RecordId<R>(pub R);
+
+impl<R: serde::de::DeserializeOwned> valigate::Valid for RecordId<R> {
+    type In = String;
+
+    fn parse(input: Self::In) -> Result<Self, valigate::Error> {
+        deserialize_id(input).map(RecordId).map_err(|_| {
+            let mut errors = valigate::MapErrors::default();
+            errors.insert_field(
+                "id",
+                valigate::FieldErrors::from_one(ListQueryError::InvalidId).into(),
+            );
+            errors.into()
+        })
+    }
+
+    fn schema() -> valigate::schema::Schema {
+        valigate::schema::Schema::new(valigate::schema::SchemaType::String)
+    }
+}
+
 impl<'b, R: Record, D: serde::Serialize + Send + Sync + 'static, Config: CrudConfig> Plugin
     for ListAllPlugin<'b, R, D, Config>
 where
-    R: serde::Serialize,
-    Config::Db: DbList<wok_db::RecordEntry<R, D>>,
+    D: valigate::Valid,
+    R: serde::Serialize + serde::de::DeserializeOwned,
+    Config::Db: DbListPage<R, wok_db::RecordEntry<R, D>>,
 {
     fn setup(self, app: &mut wok::prelude::App) {
-        let system = async move |db: Res<'_, Config::Db>| -> Result<
-            axum::Json<Vec<wok_db::RecordEntry<R, D>>>,
-            WokUnknownError,
-        > {
-            let data = db.list(R::TABLE).execute().await?;
-            Ok(axum::Json(data))
+        let filterable = self.filterable;
+        let max_limit = self.max_limit;
+
+        let parse = move |In(Query(query)): In<Query<HashMap<String, String>>>| {
+            list_params::<R>(query, filterable, max_limit)
         };
 
-        app.add_systems(Route(&self.builder.path), get(system));
+        let list = async |In(params): In<Result<ListParams<R>, valigate::Error>>,
+                          db: Res<'_, Config::Db>| {
+            let params = match params {
+                Ok(params) => params,
+                Err(err) => return Ok(Err(Problem::validation(&err))),
+            };
+
+            let data = db.list_page(R::TABLE, params).execute().await?;
+            Ok(Ok(Json(data))) as Result<_, WokUnknownError>
+        };
+
+        let system = parse.pipe_then(list);
+        let operation = list_operation::<D>(filterable);
+
+        app.add_systems(
+            Route(&self.builder.path),
+            get(system).documented_as(operation),
+        );
     }
 }
 
@@ -166,7 +418,7 @@ where
     R: serde::de::DeserializeOwned + serde::Serialize,
 {
     fn setup(self, app: &mut wok::prelude::App) {
-        use crate::{extract::JsonG, response::Created};
+        use crate::response::Created;
 
         let system = async move |In(JsonG(data)): In<JsonG<D>>, db: Res<'_, Config::Db>| {
             let data = Config::IdStrategy::wrap(data);
@@ -175,7 +427,156 @@ where
             Ok(Created::Created(Json(id))) as Result<_, WokUnknownError>
         };
 
-        app.add_systems(Route(&self.builder.path), post(system));
+        app.add_systems(Route(&self.builder.path), post(system).documented());
+    }
+}
+
+pub struct UpdateOnePlugin<'b, R: Record, D: valigate::Valid, Config: CrudConfig> {
+    builder: &'b RoutePluginBuilder<R, Config>,
+    _marker: std::marker::PhantomData<fn(R, D)>,
+}
+
+impl<'b, R: Record, D, Config: CrudConfig> Plugin for UpdateOnePlugin<'b, R, D, Config>
+where
+    D: valigate::Valid + Send + Sync + 'static,
+    D::In: serde::de::DeserializeOwned + Send + Sync + 'static,
+    Config::Db: DbUpdate<R, D>,
+    R: serde::de::DeserializeOwned,
+{
+    fn setup(self, app: &mut wok::prelude::App) {
+        let system = async move |In((PathG(RecordId(id)), JsonG(data))): In<(
+            PathG<RecordId<R>>,
+            JsonG<D>,
+        )>,
+                                 db: Res<'_, Config::Db>| {
+            let result = db.update(R::TABLE, id, data).execute().await?;
+            Ok(updated_status(result)) as Result<_, WokUnknownError>
+        };
+
+        app.add_systems(
+            Route(&format!("{}/{{id}}", self.builder.path)),
+            put(system).documented_as(update_operation::<R, D>()),
+        );
+    }
+}
+
+pub struct PatchOnePlugin<'b, R: Record, P: valigate::Valid, Config: CrudConfig> {
+    builder: &'b RoutePluginBuilder<R, Config>,
+    _marker: std::marker::PhantomData<fn(R, P)>,
+}
+
+impl<'b, R: Record, P, Config: CrudConfig> Plugin for PatchOnePlugin<'b, R, P, Config>
+where
+    P: valigate::Valid + Send + Sync + 'static,
+    P::In: serde::de::DeserializeOwned + Send + Sync + 'static,
+    Config::Db: DbMerge<R, P>,
+    R: serde::de::DeserializeOwned,
+{
+    fn setup(self, app: &mut wok::prelude::App) {
+        let system = async move |In((PathG(RecordId(id)), JsonG(data))): In<(
+            PathG<RecordId<R>>,
+            JsonG<P>,
+        )>,
+                                 db: Res<'_, Config::Db>| {
+            let result = db.merge(R::TABLE, id, data).execute().await?;
+            Ok(updated_status(result)) as Result<_, WokUnknownError>
+        };
+
+        app.add_systems(
+            Route(&format!("{}/{{id}}", self.builder.path)),
+            patch(system).documented_as(update_operation::<R, P>()),
+        );
+    }
+}
+
+/// Schema of a `RecordEntry`, the fields of `D` next to the record `id`
+fn entry_schema<D: valigate::Valid>() -> Schema {
+    let mut schema = D::schema();
+    schema.property("id", Schema::any(), true);
+    schema
+}
+
+fn filter_schema(filter_type: FilterType) -> Schema {
+    match filter_type {
+        FilterType::Bool => Schema::new(SchemaType::Boolean),
+        FilterType::Int => Schema::new(SchemaType::Integer),
+        FilterType::Float => Schema::new(SchemaType::Number),
+        FilterType::String => Schema::new(SchemaType::String),
+    }
+}
+
+fn list_operation<D: valigate::Valid>(filterable: &[(&'static str, FilterType)]) -> Operation {
+    let query_param = |name: &'static str, schema| Parameter {
+        name: name.into(),
+        location: ParameterIn::Query,
+        required: false,
+        schema,
+    };
+
+    let mut operation = Operation::default();
+    operation.parameters.extend([
+        query_param("limit", Schema::new(SchemaType::Integer)),
+        query_param("offset", Schema::new(SchemaType::Integer)),
+        query_param("after", Schema::any()),
+    ]);
+    operation.parameters.extend(
+        filterable
+            .iter()
+            .map(|(field, filter_type)| query_param(field, filter_schema(*filter_type))),
+    );
+
+    operation.response(
+        "200",
+        Response::new("OK").content("application/json", Schema::array(entry_schema::<D>())),
+    );
+    operation.response(
+        "400",
+        Response::new("Invalid query").content("application/problem+json", Problem::schema()),
+    );
+    WokUnknownError::operation_output(&mut operation);
+
+    operation
+}
+
+fn get_operation<R, D>() -> Operation
+where
+    R: serde::de::DeserializeOwned,
+    D: valigate::Valid,
+{
+    let mut operation = Operation::default();
+    PathG::<RecordId<R>>::operation_input(&mut operation);
+
+    operation.response(
+        "200",
+        Response::new("OK").content("application/json", entry_schema::<D>()),
+    );
+    operation.response("404", Response::new("Not found"));
+    WokUnknownError::operation_output(&mut operation);
+
+    operation
+}
+
+fn update_operation<R, D>() -> Operation
+where
+    R: serde::de::DeserializeOwned,
+    D: valigate::Valid,
+{
+    let mut operation = Operation::default();
+    <(PathG<RecordId<R>>, JsonG<D>)>::operation_input(&mut operation);
+
+    operation.response("204", Response::new("Updated"));
+    operation.response("404", Response::new("Not found"));
+    operation.response("409", Response::new("Conflict"));
+    WokUnknownError::operation_output(&mut operation);
+
+    operation
+}
+
+fn updated_status(result: Result<(), DbUpdateError>) -> StatusCode {
+    match result {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(DbUpdateError::NotFound) => StatusCode::NOT_FOUND,
+        Err(DbUpdateError::Conflict) => StatusCode::CONFLICT,
     }
 }
 
@@ -188,20 +589,20 @@ impl<'b, R: Record, D: serde::Serialize, Config: CrudConfig> Plugin
     for GetOnePlugin<'b, R, D, Config>
 where
     R: serde::de::DeserializeOwned + serde::Serialize,
-    D: Send + Sync + 'static,
+    D: valigate::Valid + Send + Sync + 'static,
     Config::Db: DbSelectSingle<R, wok_db::RecordEntry<R, D>>,
 {
     fn setup(self, app: &mut wok::prelude::App) {
-        let system = async move |In(axum::extract::Path(id)): In<axum::extract::Path<R>>,
+        let system = async move |In(PathG(RecordId(id))): In<PathG<RecordId<R>>>,
                                  db: Res<'_, Config::Db>| {
             let data = db.select(R::TABLE, id).execute().await?;
-            (match data {
-                Some(data) => Ok(Ok(axum::Json(data))),
-                None => Ok(Err(axum::http::StatusCode::NOT_FOUND)),
-            }) as Result<_, WokUnknownError>
+            Ok(Maybe(data.map(Json))) as Result<_, WokUnknownError>
         };
 
-        app.add_systems(Route(&format!("{}/{{id}}", self.builder.path)), get(system));
+        app.add_systems(
+            Route(&format!("{}/{{id}}", self.builder.path)),
+            get(system).documented_as(get_operation::<R, D>()),
+        );
     }
 }
 
@@ -216,18 +617,371 @@ where
     Config::Db: DbDelete<R>,
 {
     fn setup(self, app: &mut wok::prelude::App) {
-        let system = async move |In(axum::extract::Path(id)): In<axum::extract::Path<R>>,
+        let system = async move |In(PathG(RecordId(id))): In<PathG<RecordId<R>>>,
                                  db: Res<'_, Config::Db>| {
             let result = db.delete(R::TABLE, id).execute().await?;
-            (match result {
-                Ok(()) => Ok(axum::http::StatusCode::NO_CONTENT),
-                Err(_) => Ok(axum::http::StatusCode::NOT_FOUND),
-            }) as Result<_, WokUnknownError>
+            Ok(Maybe(result.ok().map(|()| Deleted))) as Result<_, WokUnknownError>
         };
 
         app.add_systems(
             Route(&format!("{}/{{id}}", self.builder.path)),
-            delete(system),
+            delete(system).documented(),
+        );
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use wok::prelude::*;
+    use wok_db::{
+        Record, RecordEntry, RecordGenerate,
+        db::{FilterType, FilterValue},
+        id_strategy::GenerateId,
+        memory::MemoryDb,
+        query::Condition,
+    };
+
+    use super::{CRUDCfgBuilder, CrudConfig, deserialize_id, list_params};
+    use crate::{
+        AxumPlugin, NestRoutes, Route, get, openapi::OpenApiPlugin, problem::Problem, routes,
+        testing::TestApp,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct NoteId(uuid::Uuid);
+
+    impl Record for NoteId {
+        const TABLE: &'static str = "note";
+    }
+
+    impl RecordGenerate for NoteId {
+        fn generate() -> Self {
+            NoteId(uuid::Uuid::new_v4())
+        }
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize, valigate::Valid)]
+    #[gate(serde = true)]
+    struct Note {
+        text: String,
+        #[gate(skip = true)]
+        pinned: bool,
+    }
+
+    fn note(text: &str, pinned: bool) -> Note {
+        Note {
+            text: text.to_owned(),
+            pinned,
+        }
+    }
+
+    // Absent fields are left untouched on PATCH
+    #[derive(serde::Serialize, valigate::Valid)]
+    #[gate(serde = true)]
+    struct NotePatch {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        text: Option<String>,
+        #[gate(skip = true)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pinned: Option<bool>,
+    }
+
+    fn note_app() -> App {
+        let factory = CRUDCfgBuilder::default()
+            .db::<MemoryDb>()
+            .id::<GenerateId>()
+            .for_record::<NoteId>();
+
+        App::default()
+            .add_plugin(AxumPlugin)
+            .insert_resource(MemoryDb::default())
+            .add_plugin(factory.create_one::<Note>())
+            .add_plugin(factory.get_one::<Note>())
+            .add_plugin(factory.list_all::<Note>().filterable(FILTERABLE))
+            .add_plugin(factory.update_one::<Note>())
+            .add_plugin(factory.patch_one::<NotePatch>())
+            .add_plugin(factory.delete_one())
+    }
+
+    #[tokio::test]
+    async fn serves_records_from_a_memory_db() {
+        TestApp::new(note_app())
+            .run(|client| async move {
+                let note = note("hello", false);
+                let res = client.post_json("/note", &note).await;
+                assert_eq!(res.status(), 201);
+                let id: NoteId = res.json();
+
+                let res = client.get(&format!("/note/{}", id.0)).await;
+                assert_eq!(res.status(), 200);
+                let found: RecordEntry<NoteId, Note> = res.json();
+                assert_eq!((found.id, found.data), (id, note));
+
+                let notes: Vec<RecordEntry<NoteId, Note>> = client.get("/note").await.json();
+                assert_eq!(notes.len(), 1);
+
+                let res = client.delete(&format!("/note/{}", id.0)).await;
+                assert_eq!(res.status(), 204);
+                let res = client.get(&format!("/note/{}", id.0)).await;
+                assert_eq!(res.status(), 404);
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn updates_and_patches_records() {
+        TestApp::new(note_app())
+            .run(|client| async move {
+                let id: NoteId = client
+                    .post_json("/note", &note("draft", false))
+                    .await
+                    .json();
+                let path = format!("/note/{}", id.0);
+                let missing = format!("/note/{}", uuid::Uuid::new_v4());
+
+                let res = client.put_json(&path, &note("final", false)).await;
+                assert_eq!(res.status(), 204);
+                let res = client.put_json(&missing, &note("final", false)).await;
+                assert_eq!(res.status(), 404);
+
+                let pin = NotePatch {
+                    text: None,
+                    pinned: Some(true),
+                };
+                let res = client.patch_json(&path, &pin).await;
+                assert_eq!(res.status(), 204);
+                let res = client.patch_json(&missing, &pin).await;
+                assert_eq!(res.status(), 404);
+
+                let found: RecordEntry<NoteId, Note> = client.get(&path).await.json();
+                assert_eq!(found.data, note("final", true));
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn lists_pages_and_filters_records() {
+        TestApp::new(note_app())
+            .run(|client| async move {
+                for (text, pinned) in [("a", true), ("b", false), ("c", true)] {
+                    let res = client.post_json("/note", &note(text, pinned)).await;
+                    assert_eq!(res.status(), 201);
+                }
+
+                let list = |uri: String| {
+                    let client = &client;
+                    async move {
+                        let res = client.get(&uri).await;
+                        assert_eq!(res.status(), 200);
+                        res.json::<Vec<RecordEntry<NoteId, Note>>>()
+                    }
+                };
+                let ids = |notes: &[RecordEntry<NoteId, Note>]| {
+                    notes.iter().map(|entry| entry.id).collect::<Vec<_>>()
+                };
+
+                let all = list("/note".to_owned()).await;
+                assert_eq!(all.len(), 3);
+                let page = list("/note?limit=2".to_owned()).await;
+                assert_eq!(ids(&page), ids(&all[..2]));
+                let page = list(format!("/note?after={}", all[0].id.0)).await;
+                assert_eq!(ids(&page), ids(&all[1..]));
+
+                let pinned = list("/note?pinned=true".to_owned()).await;
+                assert_eq!(pinned.len(), 2);
+                assert!(pinned.iter().all(|entry| entry.data.pinned));
+
+                // Filters and the cursor combine
+                let page = list(format!("/note?pinned=true&after={}", all[0].id.0)).await;
+                let expected: Vec<_> = all[1..]
+                    .iter()
+                    .filter(|entry| entry.data.pinned)
+                    .map(|entry| entry.id)
+                    .collect();
+                assert_eq!(ids(&page), expected);
+
+                let res = client.get("/note?pinned=maybe").await;
+                assert_eq!(res.status(), 400);
+                let problem: serde_json::Value = res.json();
+                assert_eq!(problem["errors"][0]["pointer"], "/pinned");
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn documents_crud_routes_next_to_other_routes() {
+        async fn ping() -> &'static str {
+            "pong"
+        }
+
+        let factory = CRUDCfgBuilder::default()
+            .db::<MemoryDb>()
+            .id::<GenerateId>()
+            .for_record::<NoteId>();
+
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .add_plugin(OpenApiPlugin::new("/openapi.json"))
+            .insert_resource(MemoryDb::default())
+            .add_systems(Route("/health"), get(ping).documented())
+            .add_systems(NestRoutes("/api"), routes("/ping", get(ping).documented()))
+            .add_plugin(factory.list_all::<Note>().filterable(FILTERABLE))
+            .add_plugin(factory.create_one::<Note>())
+            .add_plugin(factory.get_one::<Note>())
+            .add_plugin(factory.update_one::<Note>())
+            .add_plugin(factory.patch_one::<Note>())
+            .add_plugin(factory.delete_one());
+
+        TestApp::new(app)
+            .run(|client| async move {
+                let res = client.get("/openapi.json").await;
+                assert_eq!(res.status(), 200);
+                let document: serde_json::Value = res.json();
+                let paths = &document["paths"];
+
+                assert_eq!(
+                    paths["/health"]["get"]["responses"]["200"]["description"],
+                    "OK"
+                );
+                assert_eq!(
+                    paths["/api/ping"]["get"]["responses"]["200"]["description"],
+                    "OK"
+                );
+
+                let list = &paths["/note"]["get"];
+                let params: Vec<_> = list["parameters"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|param| param["name"].as_str().unwrap())
+                    .collect();
+                assert_eq!(params, ["limit", "offset", "after", "text", "pinned"]);
+                let entries = &list["responses"]["200"]["content"]["application/json"]["schema"];
+                assert_eq!(
+                    entries["items"]["required"],
+                    serde_json::json!(["text", "pinned", "id"])
+                );
+                assert!(list["responses"]["400"].is_object());
+
+                let create = &paths["/note"]["post"];
+                let body = &create["requestBody"]["content"]["application/json"]["schema"];
+                assert_eq!(body["properties"]["text"]["type"], "string");
+                assert!(create["responses"]["201"].is_object());
+
+                let item = &paths["/note/{id}"];
+                assert_eq!(item["parameters"][0]["name"], "id");
+                assert_eq!(item["parameters"][0]["in"], "path");
+                let entry = &item["get"]["responses"]["200"]["content"]["application/json"];
+                assert_eq!(
+                    entry["schema"]["required"],
+                    serde_json::json!(["text", "pinned", "id"])
+                );
+                for method in ["put", "patch"] {
+                    let body = &item[method]["requestBody"]["content"]["application/json"];
+                    assert_eq!(body["schema"]["properties"]["text"]["type"], "string");
+                    assert!(item[method]["responses"]["204"].is_object());
+                    assert!(item[method]["responses"]["404"].is_object());
+                }
+                assert!(item["delete"]["responses"]["204"].is_object());
+                assert!(!document.to_string().contains("Undocumented"));
+            })
+            .await
+            .unwrap();
+    }
+
+    const FILTERABLE: &[(&str, FilterType)] =
+        &[("text", FilterType::String), ("pinned", FilterType::Bool)];
+
+    fn query(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_list_params() {
+        let after = uuid::Uuid::new_v4();
+        let params = list_params::<NoteId>(
+            query(&[
+                ("limit", "10"),
+                ("offset", "5"),
+                ("after", &after.to_string()),
+                ("pinned", "true"),
+            ]),
+            FILTERABLE,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(params.limit, Some(10));
+        assert_eq!(params.start, Some(5));
+        assert_eq!(params.after, Some(NoteId(after)));
+        assert_eq!(
+            params.filters,
+            [Condition::field_eq("pinned", FilterValue::Bool(true))]
+        );
+    }
+
+    #[test]
+    fn filters_parse_by_their_declared_type() {
+        let params = list_params::<NoteId>(query(&[("text", "42")]), FILTERABLE, None).unwrap();
+
+        assert_eq!(
+            params.filters,
+            [Condition::field_eq(
+                "text",
+                FilterValue::String("42".to_owned())
+            )]
+        );
+    }
+
+    #[test]
+    fn max_limit_caps_and_defaults_the_limit() {
+        for (limit, expected) in [(None, 20), (Some("50"), 20), (Some("5"), 5)] {
+            let pairs: Vec<_> = limit.map(|limit| ("limit", limit)).into_iter().collect();
+            let params = list_params::<NoteId>(query(&pairs), FILTERABLE, Some(20)).unwrap();
+
+            assert_eq!(params.limit, Some(expected));
+        }
+    }
+
+    #[test]
+    fn rejects_every_invalid_param() {
+        let err = list_params::<NoteId>(
+            query(&[
+                ("limit", "-1"),
+                ("after", "not-an-id"),
+                ("pinned", "maybe"),
+                ("secret", "1"),
+            ]),
+            FILTERABLE,
+            None,
+        )
+        .unwrap_err();
+
+        let problem = Problem::validation(&err);
+        let mut pointers: Vec<_> = problem.errors.iter().map(|e| e.pointer.as_str()).collect();
+        pointers.sort();
+
+        assert_eq!(pointers, ["/after", "/limit", "/pinned", "/secret"]);
+    }
+
+    #[test]
+    fn parses_scalar_and_newtype_ids() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Counter(u32);
+
+        assert_eq!(deserialize_id::<i64>("-42".to_owned()).unwrap(), -42);
+        assert_eq!(
+            deserialize_id::<Counter>("7".to_owned()).unwrap(),
+            Counter(7)
         );
+        assert!(deserialize_id::<u32>("seven".to_owned()).is_err());
     }
 }
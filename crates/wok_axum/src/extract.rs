@@ -1,36 +1,308 @@
-pub enum JsonGatedRejection {
-    Json(axum::extract::rejection::JsonRejection),
+//! Extractors that validate their input with `valigate::Valid::parse`, rejecting with a
+//! `Problem` on failure
+
+use axum::{
+    extract::{
+        FromRequest, FromRequestParts, Request,
+        rejection::{FormRejection, JsonRejection, PathRejection, QueryRejection},
+    },
+    http::{StatusCode, request::Parts},
+};
+
+use crate::problem::Problem;
+
+/// Rejection of every gated extractor
+pub enum GatedRejection {
+    Json(JsonRejection),
+    Query(QueryRejection),
+    Path(PathRejection),
+    Form(FormRejection),
+    /// Headers that do not deserialize as the input of `HeaderG`
+    Header(serde_urlencoded::de::Error),
+    Multipart(axum::extract::multipart::MultipartRejection),
+    MultipartField(axum::extract::multipart::MultipartError),
+    /// Text parts that do not deserialize as the fields of `MultipartG`
+    MultipartText(serde_urlencoded::de::Error),
     Valigate(valigate::Error),
 }
 
-impl axum::response::IntoResponse for JsonGatedRejection {
+pub type JsonGatedRejection = GatedRejection;
+
+impl axum::response::IntoResponse for GatedRejection {
     fn into_response(self) -> axum::response::Response {
-        match self {
-            JsonGatedRejection::Json(e) => e.into_response(),
-            JsonGatedRejection::Valigate(e) => {
-                (axum::http::StatusCode::BAD_REQUEST, axum::extract::Json(e)).into_response()
+        let problem = match self {
+            GatedRejection::Json(e) => Problem::new(e.status()).with_detail(e.body_text()),
+            GatedRejection::Query(e) => Problem::new(e.status()).with_detail(e.body_text()),
+            GatedRejection::Path(e) => Problem::new(e.status()).with_detail(e.body_text()),
+            GatedRejection::Form(e) => Problem::new(e.status()).with_detail(e.body_text()),
+            GatedRejection::Header(e) => Problem::new(StatusCode::BAD_REQUEST)
+                .with_detail(format!("Failed to deserialize headers: {e}")),
+            GatedRejection::Multipart(e) => Problem::new(e.status()).with_detail(e.body_text()),
+            GatedRejection::MultipartField(e) => {
+                Problem::new(e.status()).with_detail(e.body_text())
+            }
+            GatedRejection::MultipartText(e) => Problem::new(StatusCode::BAD_REQUEST)
+                .with_detail(format!("Failed to deserialize multipart fields: {e}")),
+            GatedRejection::Valigate(e) => Problem::validation(&e),
+        };
+
+        problem.into_response()
+    }
+}
+
+fn parse<T: valigate::Valid>(input: T::In) -> Result<T, GatedRejection> {
+    T::parse(input).map_err(GatedRejection::Valigate)
+}
+
+/// Deserializes string pairs the way query strings are, so numbers and booleans parse
+fn deserialize_pairs<'p, T: serde::de::DeserializeOwned>(
+    pairs: impl Iterator<Item = (&'p str, &'p str)>,
+) -> Result<T, serde_urlencoded::de::Error> {
+    fn encode(out: &mut String, value: &str) {
+        for byte in value.bytes() {
+            match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    out.push(byte as char)
+                }
+                _ => out.push_str(&format!("%{byte:02X}")),
             }
         }
     }
+
+    let mut encoded = String::new();
+    for (i, (key, value)) in pairs.enumerate() {
+        if i > 0 {
+            encoded.push('&');
+        }
+        encode(&mut encoded, key);
+        encoded.push('=');
+        encode(&mut encoded, value);
+    }
+
+    serde_urlencoded::from_str(&encoded)
 }
 
 pub struct JsonG<T>(pub T);
 
-impl<S: Send + Sync + 'static, T> axum::extract::FromRequest<S> for JsonG<T>
+impl<S: Send + Sync + 'static, T> FromRequest<S> for JsonG<T>
 where
     T: valigate::Valid,
     T::In: serde::de::DeserializeOwned,
 {
-    type Rejection = JsonGatedRejection;
+    type Rejection = GatedRejection;
 
-    async fn from_request(req: axum::extract::Request, state: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
         let input = axum::Json::<T::In>::from_request(req, state)
             .await
-            .map_err(JsonGatedRejection::Json)?;
+            .map_err(GatedRejection::Json)?;
+
+        parse(input.0).map(JsonG)
+    }
+}
+
+pub struct QueryG<T>(pub T);
+
+impl<S: Send + Sync, T> FromRequestParts<S> for QueryG<T>
+where
+    T: valigate::Valid,
+    T::In: serde::de::DeserializeOwned,
+{
+    type Rejection = GatedRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let input = axum::extract::Query::<T::In>::from_request_parts(parts, state)
+            .await
+            .map_err(GatedRejection::Query)?;
+
+        parse(input.0).map(QueryG)
+    }
+}
+
+pub struct PathG<T>(pub T);
+
+impl<S: Send + Sync, T> FromRequestParts<S> for PathG<T>
+where
+    T: valigate::Valid,
+    T::In: serde::de::DeserializeOwned + Send,
+{
+    type Rejection = GatedRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let input = axum::extract::Path::<T::In>::from_request_parts(parts, state)
+            .await
+            .map_err(GatedRejection::Path)?;
+
+        parse(input.0).map(PathG)
+    }
+}
+
+pub struct FormG<T>(pub T);
+
+impl<S: Send + Sync, T> FromRequest<S> for FormG<T>
+where
+    T: valigate::Valid,
+    T::In: serde::de::DeserializeOwned,
+{
+    type Rejection = GatedRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let input = axum::Form::<T::In>::from_request(req, state)
+            .await
+            .map_err(GatedRejection::Form)?;
+
+        parse(input.0).map(FormG)
+    }
+}
+
+/// Headers deserialized by their lowercase names, with the first value of repeated ones.
+/// Use `#[serde(rename_all = "kebab-case")]` for headers such as `x-api-key`
+pub struct HeaderG<T>(pub T);
+
+impl<S: Send + Sync, T> FromRequestParts<S> for HeaderG<T>
+where
+    T: valigate::Valid,
+    T::In: serde::de::DeserializeOwned,
+{
+    type Rejection = GatedRejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let pairs = parts.headers.keys().filter_map(|name| {
+            let value = parts.headers.get(name)?.to_str().ok()?;
+            Some((name.as_str(), value))
+        });
+
+        let input = deserialize_pairs::<T::In>(pairs).map_err(GatedRejection::Header)?;
+        parse(input).map(HeaderG)
+    }
+}
+
+/// File part of a multipart request
+#[derive(Debug, Clone)]
+pub struct MultipartFile {
+    pub name: String,
+    pub file_name: String,
+    pub content_type: Option<String>,
+    pub bytes: axum::body::Bytes,
+}
+
+/// Multipart form, text parts are validated as `T` and parts with a file name are kept as
+/// `MultipartFile`s. The whole body is buffered
+pub struct MultipartG<T> {
+    pub fields: T,
+    pub files: Vec<MultipartFile>,
+}
+
+impl<S: Send + Sync, T> FromRequest<S> for MultipartG<T>
+where
+    T: valigate::Valid,
+    T::In: serde::de::DeserializeOwned,
+{
+    type Rejection = GatedRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let mut multipart = axum::extract::Multipart::from_request(req, state)
+            .await
+            .map_err(GatedRejection::Multipart)?;
+
+        let mut texts = vec![];
+        let mut files = vec![];
+
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(GatedRejection::MultipartField)?
+        {
+            let name = field.name().unwrap_or_default().to_owned();
+
+            match field.file_name().map(str::to_owned) {
+                Some(file_name) => {
+                    let content_type = field.content_type().map(str::to_owned);
+                    let bytes = field
+                        .bytes()
+                        .await
+                        .map_err(GatedRejection::MultipartField)?;
+
+                    files.push(MultipartFile {
+                        name,
+                        file_name,
+                        content_type,
+                        bytes,
+                    });
+                }
+                None => {
+                    let text = field.text().await.map_err(GatedRejection::MultipartField)?;
+                    texts.push((name, text));
+                }
+            }
+        }
+
+        let pairs = texts
+            .iter()
+            .map(|(name, text)| (name.as_str(), text.as_str()));
+        let input = deserialize_pairs::<T::In>(pairs).map_err(GatedRejection::MultipartText)?;
+
+        Ok(MultipartG {
+            fields: parse(input)?,
+            files,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{http::Request, response::IntoResponse};
+
+    use super::*;
 
-        match T::parse(input.0) {
-            Ok(value) => Ok(JsonG(value)),
-            Err(e) => Err(JsonGatedRejection::Valigate(e)),
+    #[derive(serde::Deserialize)]
+    #[serde(rename_all = "kebab-case")]
+    struct RetriesIn {
+        x_retries: u8,
+    }
+
+    struct Retries(u8);
+
+    impl valigate::Valid for Retries {
+        type In = RetriesIn;
+
+        fn parse(input: Self::In) -> Result<Self, valigate::Error> {
+            Ok(Retries(input.x_retries))
         }
     }
+
+    async fn headers(retries: &str) -> Result<HeaderG<Retries>, GatedRejection> {
+        let (mut parts, _) = Request::builder()
+            .header("x-retries", retries)
+            .body(())
+            .unwrap()
+            .into_parts();
+
+        HeaderG::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn parses_headers() {
+        let Ok(HeaderG(retries)) = headers("3").await else {
+            panic!("headers to parse");
+        };
+        assert_eq!(retries.0, 3);
+    }
+
+    #[tokio::test]
+    async fn rejects_headers_with_their_own_detail() {
+        let Err(rejection) = headers("many").await else {
+            panic!("headers to be rejected");
+        };
+        assert!(matches!(rejection, GatedRejection::Header(_)));
+
+        let response = rejection.into_response();
+        let problem = response.extensions().get::<Problem>().unwrap();
+        assert_eq!(problem.status, StatusCode::BAD_REQUEST);
+        assert!(
+            problem
+                .detail
+                .as_ref()
+                .is_some_and(|detail| detail.starts_with("Failed to deserialize headers"))
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use axum::{
     extract::{FromRequest, FromRequestParts},
     response::IntoResponse,
@@ -8,6 +10,11 @@ use wok::{
 };
 use wok_core::world::gateway::SystemEntry;
 
+use crate::{
+    observe::{LockWait, SystemFailed},
+    problem::Problem,
+};
+
 #[derive(Clone)]
 pub(crate) struct AxumRouteSystem<S>(pub(crate) SystemEntry<S>);
 
@@ -30,14 +37,14 @@ where
                 Err(rejection) => return rejection.into_response(),
             };
 
-            state
-                .reserver()
-                .reserve(self.0.entry_ref())
-                .await
-                .task()
-                .run(input)
-                .await
-                .wok_into_response()
+            let waiting = Instant::now();
+            let permit = state.reserver().reserve(self.0.entry_ref()).await;
+            let lock_wait = LockWait(waiting.elapsed());
+
+            let mut response = permit.task().run(input).await.wok_into_response();
+            response.extensions_mut().insert(lock_wait);
+
+            response
         })
     }
 }
@@ -61,14 +68,14 @@ where
                 Err(rejection) => return rejection.into_response(),
             };
 
-            state
-                .reserver()
-                .reserve(self.0.entry_ref())
-                .await
-                .task()
-                .run(input)
-                .await
-                .wok_into_response()
+            let waiting = Instant::now();
+            let permit = state.reserver().reserve(self.0.entry_ref()).await;
+            let lock_wait = LockWait(waiting.elapsed());
+
+            let mut response = permit.task().run(input).await.wok_into_response();
+            response.extensions_mut().insert(lock_wait);
+
+            response
         })
     }
 }
@@ -86,14 +93,14 @@ where
 
     fn call(self, _req: axum::extract::Request, state: RemoteWorldPorts) -> Self::Future {
         Box::pin(async move {
-            state
-                .reserver()
-                .reserve(self.0.entry_ref())
-                .await
-                .task()
-                .run(())
-                .await
-                .wok_into_response()
+            let waiting = Instant::now();
+            let permit = state.reserver().reserve(self.0.entry_ref()).await;
+            let lock_wait = LockWait(waiting.elapsed());
+
+            let mut response = permit.task().run(()).await.wok_into_response();
+            response.extensions_mut().insert(lock_wait);
+
+            response
         })
     }
 }
@@ -112,13 +119,31 @@ impl<T: IntoResponse> WokIntoResponse<IsAxumResponse> for T {
 }
 
 pub struct IsWokResult;
+/// Responds with a `Problem` of the status hint of the error, defaults to
+/// `500 Internal Server Error`
 impl<T: IntoResponse> WokIntoResponse<IsWokResult> for Result<T, WokUnknownError> {
     fn wok_into_response(self) -> axum::response::Response {
         match self {
             Ok(value) => value.into_response(),
             Err(err) => {
-                tracing::error!(%err);
-                axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                let status = err
+                    .status()
+                    .and_then(|status| axum::http::StatusCode::from_u16(status).ok())
+                    .unwrap_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+
+                // Only client errors are meant to be read by the client
+                let problem = if status.is_server_error() {
+                    tracing::error!(%err);
+                    Problem::new(status)
+                } else {
+                    tracing::debug!(%err);
+                    Problem::new(status).with_detail(err.to_string())
+                };
+
+                let mut response = problem.into_response();
+                response.extensions_mut().insert(SystemFailed);
+
+                response
             }
         }
     }
@@ -1,5 +1,7 @@
 use std::convert::Infallible;
 
+use futures::future::BoxFuture;
+
 use axum::{
     RequestExt,
     extract::{FromRequestParts, Request},
@@ -9,65 +11,224 @@ use axum::{
 use tower_service::Service;
 use wok::{
     prelude::{
-        BorrowMutParam, ConfigureWorld, IntoSystem, ProtoSystem, ResMut, System, SystemInput,
+        BorrowMutParam, IntoSystem, ProtoSystem, ProtoTaskSystem, ResMut, Resource, System,
+        SystemInput,
     },
     remote_gateway::RemoteWorldPorts,
 };
-use wok_core::schedule::{ConfigureObjects, ScheduleConfigure, ScheduleLabel};
+use wok_core::{
+    schedule::{ConfigureObjects, ScheduleConfigure, ScheduleLabel},
+    world::WorldCenter,
+};
 
-use crate::{RouterRoot, handler::WokIntoResponse};
+use crate::{Router, handler::WokIntoResponse};
 
+/// Wraps every request of the current scope, including unmatched ones
+/// Layers are not applied when added: they are kept until the scope router is built (in
+/// `serve`, or when an `AxumNestPlugin` scope ends), so they wrap every route of the scope no
+/// matter the order plugins were added in. See `Ordered` for the application order
+#[derive(Clone, Copy)]
 pub struct Layer;
 
+/// Wraps only the matched routes of the current scope, unmatched requests skip it
+#[derive(Clone, Copy)]
+pub struct RouteLayer;
+
+/// Layer label with an explicit order
+/// Layers are applied in ascending order, equal orders in insertion order; the last one
+/// applied is the outermost, so higher orders see the request first. `Layer` and
+/// `RouteLayer` have order `0`
+/// ```rust
+/// use wok::prelude::*;
+/// use wok_axum::{AxumPlugin, Layer, LayerLabel};
+///
+/// App::default()
+///     .add_plugin(AxumPlugin)
+///     // seen before every other layer of the scope
+///     .add_objs(Layer.order(100), axum::Extension("outermost"));
+/// ```
+#[derive(Clone, Copy)]
+pub struct Ordered<L> {
+    label: L,
+    order: i32,
+}
+
+#[derive(Clone, Copy)]
+pub enum LayerKind {
+    Layer,
+    RouteLayer,
+}
+
+pub trait LayerLabel: Copy + Send + Sync + 'static {
+    fn kind(&self) -> LayerKind;
+
+    fn layer_order(&self) -> i32 {
+        0
+    }
+
+    fn order(self, order: i32) -> Ordered<Self> {
+        Ordered { label: self, order }
+    }
+}
+
+impl LayerLabel for Layer {
+    fn kind(&self) -> LayerKind {
+        LayerKind::Layer
+    }
+}
+
+impl LayerLabel for RouteLayer {
+    fn kind(&self) -> LayerKind {
+        LayerKind::RouteLayer
+    }
+}
+
+impl<L: LayerLabel> LayerLabel for Ordered<L> {
+    fn kind(&self) -> LayerKind {
+        self.label.kind()
+    }
+
+    fn layer_order(&self) -> i32 {
+        self.order
+    }
+}
+
+type ApplyLayer = Box<dyn FnOnce(Router) -> Router + Send + Sync>;
+
+/// Layers of the current scope, waiting for its router to be built
+#[derive(Resource, Default)]
+#[resource(mutable = true)]
+pub struct RouterLayers(Vec<(i32, ApplyLayer)>);
+
+impl RouterLayers {
+    pub fn push<L>(&mut self, label: impl LayerLabel, layer: L)
+    where
+        L: tower_layer::Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        let apply: ApplyLayer = match label.kind() {
+            LayerKind::Layer => Box::new(move |router: Router| router.layer(layer)),
+            // axum panics on route layers without routes
+            LayerKind::RouteLayer => Box::new(move |router: Router| {
+                if router.has_routes() {
+                    router.route_layer(layer)
+                } else {
+                    router
+                }
+            }),
+        };
+
+        self.0.push((label.layer_order(), apply));
+    }
+
+    /// Applies the layers from the lowest order to the highest
+    pub fn apply(self, router: Router) -> Router {
+        let mut layers = self.0;
+        // stable, keeps insertion order between equal orders
+        layers.sort_by_key(|(order, _)| *order);
+
+        layers
+            .into_iter()
+            .fold(router, |router, (_, apply)| apply(router))
+    }
+}
+
 impl ScheduleLabel for Layer {}
+impl ScheduleLabel for RouteLayer {}
+impl<L: LayerLabel> ScheduleLabel for Ordered<L> {}
 
-impl<L> ConfigureObjects<L, ()> for Layer
-where
-    L: tower_layer::Layer<Route> + Clone + Send + Sync + 'static,
-    L::Service: Service<Request> + Clone + Send + Sync + 'static,
-    <L::Service as Service<Request>>::Response: IntoResponse + 'static,
-    <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
-    <L::Service as Service<Request>>::Future: Send + 'static,
+macro_rules! impl_layer_label {
+    ($label:ty $(, $gen:ident)?) => {
+        impl<$($gen: LayerLabel,)? L> ConfigureObjects<L, ()> for $label
+        where
+            L: tower_layer::Layer<Route> + Clone + Send + Sync + 'static,
+            L::Service: Service<Request> + Clone + Send + Sync + 'static,
+            <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+            <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+            <L::Service as Service<Request>>::Future: Send + 'static,
+        {
+            fn add_objs(self, world: &mut wok_core::world::World, layer: L) {
+                world.get::<ResMut<'_, RouterLayers>>().push(self, layer);
+            }
+        }
+
+        impl<$($gen: LayerLabel,)? S, P, Marker, WMarker> ScheduleConfigure<S, (Marker, WMarker, P)>
+            for $label
+        where
+            P: FromRequestParts<()> + Send + 'static,
+            S: IntoSystem<Marker>,
+            S::System: System<In = MiddlPartsIn<P>, Out: WokIntoResponse<WMarker>>
+                + ProtoSystem<Param: BorrowMutParam>,
+        {
+            fn add(self, world: &mut wok_core::world::World, system: S) {
+                add_layer_system(self, world, system.into_system());
+            }
+        }
+    };
+}
+
+impl_layer_label!(Layer);
+impl_layer_label!(RouteLayer);
+impl_layer_label!(Ordered<Lb>, Lb);
+
+fn add_layer_system<P, S, WMarker>(
+    label: impl LayerLabel,
+    world: &mut wok_core::world::World,
+    system: S,
+) where
+    P: FromRequestParts<()> + Send + 'static,
+    S: System<In = MiddlPartsIn<P>, Out: WokIntoResponse<WMarker>>
+        + ProtoTaskSystem<Param: BorrowMutParam>,
 {
-    fn add_objs(self, world: &mut wok_core::world::World, layer: L) {
-        let mut router = world.get::<ResMut<'_, RouterRoot>>();
-        let router = router.0.as_mut().expect("router");
-        take_mut::take(router, move |r| r.layer(layer));
-    }
+    let middleware = system_middleware(&mut world.center, system);
+
+    world
+        .get::<ResMut<'_, RouterLayers>>()
+        .push(label, axum::middleware::from_fn(middleware));
 }
 
-impl<S, P, Marker, WMarker> ScheduleConfigure<S, (Marker, WMarker, P)> for Layer
+/// Middleware running the system on every request, for `axum::middleware::from_fn`
+/// The params of the system stay reserved until it returns, `next` included
+pub(crate) fn system_middleware<P, S, WMarker>(
+    world: &mut WorldCenter,
+    system: S,
+) -> impl Fn(
+    axum::extract::Extension<RemoteWorldPorts>,
+    axum::extract::Request,
+    axum::middleware::Next,
+) -> BoxFuture<'static, axum::response::Response>
++ Clone
++ Send
++ Sync
++ 'static
 where
     P: FromRequestParts<()> + Send + 'static,
-    S: IntoSystem<Marker>,
-    S::System: System<In = MiddlPartsIn<P>, Out: WokIntoResponse<WMarker>>
-        + ProtoSystem<Param: BorrowMutParam>,
+    S: System<In = MiddlPartsIn<P>, Out: WokIntoResponse<WMarker>>
+        + ProtoTaskSystem<Param: BorrowMutParam>,
 {
-    fn add(self, world: &mut wok_core::world::World, system: S) {
-        let system = system.into_system();
-        let system = world.register_system(system);
-
-        let handler = move |world: axum::extract::Extension<RemoteWorldPorts>,
-                            mut req: axum::extract::Request,
-                            next: axum::middleware::Next| {
-            let system = system.clone();
-            async move {
-                let parts = match req.extract_parts::<P>().await {
-                    Ok(parts) => parts,
-                    Err(err) => {
-                        return err.into_response();
-                    }
-                };
-
-                let permit = world.reserver().reserve(system.entry_ref()).await;
-
-                let out = permit.task().run(MiddlPartsIn(parts, req, next)).await;
-
-                out.wok_into_response()
-            }
-        };
+    let system = world.register_system(system);
+
+    move |world: axum::extract::Extension<RemoteWorldPorts>,
+          mut req: axum::extract::Request,
+          next: axum::middleware::Next| {
+        let system = system.clone();
+        Box::pin(async move {
+            let parts = match req.extract_parts::<P>().await {
+                Ok(parts) => parts,
+                Err(err) => {
+                    return err.into_response();
+                }
+            };
+
+            let permit = world.reserver().reserve(system.entry_ref()).await;
+
+            let out = permit.task().run(MiddlPartsIn(parts, req, next)).await;
 
-        world.add_objs(Layer, axum::middleware::from_fn(handler));
+            out.wok_into_response()
+        })
     }
 }
 
@@ -85,3 +246,65 @@ impl<P: FromRequestParts<()> + Send> SystemInput for MiddlPartsIn<P> {
         this
     }
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use axum::{extract::Request, middleware::Next};
+    use wok::prelude::*;
+
+    use crate::{
+        AxumNestPlugin, AxumPlugin, ConfigureRoute, Layer, LayerLabel, Route, RouteLayer, get,
+        testing::TestApp,
+    };
+
+    /// Names of the layers a request went through, outermost first
+    #[derive(Clone, Default)]
+    struct Trace(Vec<&'static str>);
+
+    macro_rules! tag {
+        ($name:literal) => {
+            axum::middleware::from_fn(|mut req: Request, next: Next| async move {
+                let mut trace = req.extensions_mut().remove::<Trace>().unwrap_or_default();
+                trace.0.push($name);
+                req.extensions_mut().insert(trace);
+
+                next.run(req).await
+            })
+        };
+    }
+
+    async fn trace(In(axum::Extension(trace)): In<axum::Extension<Trace>>) -> String {
+        trace.0.join(",")
+    }
+
+    struct Nested;
+    impl Plugin for Nested {
+        fn setup(self, app: &mut App) {
+            app.add_systems(Route("/trace"), get(trace))
+                .add_objs(Layer, tag!("nested"));
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_layers_by_order_then_insertion() {
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .add_systems(Route("/trace"), get(trace).route_layer(tag!("method")))
+            .add_objs(Layer, tag!("scope"))
+            .add_objs(RouteLayer, tag!("route"))
+            .add_objs(Layer.order(10), tag!("outer"))
+            .add_objs(RouteLayer.order(-5), tag!("inner"))
+            .add_plugin(AxumNestPlugin("/nested", Nested));
+
+        TestApp::new(app)
+            .run(|client| async move {
+                let res = client.get("/trace").await;
+                assert_eq!(res.text(), "outer,route,scope,inner,method");
+
+                let res = client.get("/nested/trace").await;
+                assert_eq!(res.text(), "outer,route,scope,inner,nested");
+            })
+            .await
+            .unwrap();
+    }
+}
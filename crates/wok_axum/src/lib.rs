@@ -1,18 +1,25 @@
+pub mod auth;
 pub mod crud;
 pub mod response;
 pub mod extract;
+pub mod limit;
+pub mod observe;
+pub mod openapi;
+pub mod problem;
+pub mod stream;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod ws;
 
 mod handler;
 mod layer;
+mod serve;
 
-use wok::{
-    plugin::Plugin,
-    prelude::*,
-    remote_gateway::{RemoteWorldPorts, RemoteWorldRef},
-};
+use wok::{plugin::Plugin, prelude::*, remote_gateway::RemoteWorldPorts};
 
 pub use layer::*;
 pub use nest_route::*;
+pub use serve::*;
 pub use single_route::*;
 
 type Router = axum::Router<RemoteWorldPorts>;
@@ -44,7 +51,14 @@ pub struct AxumPlugin;
 
 impl Plugin for AxumPlugin {
     fn setup(self, app: &mut wok::prelude::App) {
-        app.init_resource::<RouterRoot>();
+        app.init_resource::<RouterRoot>()
+            .init_resource::<RouterLayers>()
+            .init_resource::<ListenerRouters>()
+            .init_resource::<problem::ProblemFormat>()
+            .init_resource::<limit::RateCounters>()
+            .init_resource::<limit::RatePolicies>()
+            .init_resource::<observe::Readiness>()
+            .init_resource::<openapi::ApiDocs>();
     }
 }
 
@@ -67,8 +81,32 @@ impl Plugin for AxumPlugin {
 /// }
 ///
 /// async fn my_handler() {}
+/// ```
+/// `Layer`s added inside the scope only wrap its routes, `.route_layer` adds one from outside
 pub struct AxumNestPlugin<P: Plugin>(pub &'static str, pub P);
 
+impl<P: Plugin> AxumNestPlugin<P> {
+    /// Wraps the matched routes of this scope with the layer
+    pub fn route_layer<L>(self, layer: L) -> AxumNestPlugin<ScopeLayer<P, L>>
+    where
+        RouteLayer: wok_core::schedule::ConfigureObjects<L, ()>,
+    {
+        AxumNestPlugin(self.0, ScopeLayer(self.1, layer))
+    }
+}
+
+/// Plugin with a `RouteLayer` over its routes, see `AxumNestPlugin::route_layer`
+pub struct ScopeLayer<P, L>(P, L);
+
+impl<P: Plugin, L> Plugin for ScopeLayer<P, L>
+where
+    RouteLayer: wok_core::schedule::ConfigureObjects<L, ()>,
+{
+    fn setup(self, app: &mut wok::prelude::App) {
+        app.add_plugin(self.0).add_objs(RouteLayer, self.1);
+    }
+}
+
 impl<P: Plugin> Plugin for AxumNestPlugin<P> {
     fn setup(self, app: &mut wok::prelude::App) {
         let parent = app
@@ -76,38 +114,63 @@ impl<P: Plugin> Plugin for AxumNestPlugin<P> {
             .state
             .take_resource::<RouterRoot>()
             .expect("missing previous `AxumPlugin`");
+        let parent_layers = app
+            .world_mut()
+            .state
+            .take_resource::<RouterLayers>()
+            .expect("missing previous `AxumPlugin`");
+
+        app.init_resource::<RouterRoot>()
+            .init_resource::<RouterLayers>();
 
-        app.init_resource::<RouterRoot>();
+        app.world_mut()
+            .get::<ResMut<'_, openapi::ApiDocs>>()
+            .push_prefix(self.0);
         app.add_plugin(self.1);
+        app.world_mut()
+            .get::<ResMut<'_, openapi::ApiDocs>>()
+            .pop_prefix();
 
         let child = app
             .world_mut()
             .state
             .take_resource::<RouterRoot>()
             .expect("Unexpected state, not recovering `RouterRoot` is not allowed");
+        let child_layers = app
+            .world_mut()
+            .state
+            .take_resource::<RouterLayers>()
+            .expect("Unexpected state, not recovering `RouterLayers` is not allowed");
 
         let (Some(parent_router), Some(child_router)) = (parent.0, child.0) else {
             panic!("Unexpected state, `RouterRoot` is not defined");
         };
 
-        let new_router = parent_router.nest(self.0, child_router);
+        let new_router = parent_router.nest(self.0, child_layers.apply(child_router));
 
-        app.insert_resource(RouterRoot(Some(new_router)));
+        app.insert_resource(RouterRoot(Some(new_router)))
+            .insert_resource(parent_layers);
     }
 }
 
 mod single_route {
-    use axum::routing::{MethodFilter, MethodRouter};
+    use std::convert::Infallible;
+
+    use axum::extract::Request;
+    use axum::response::IntoResponse;
+    use axum::routing::{MethodFilter, MethodRouter, Route as AxumRoute};
+    use tower_service::Service;
     use wok::prelude::ResMut;
     use wok::{
         prelude::{IntoSystem, System},
         remote_gateway::RemoteWorldPorts,
     };
     use wok_core::schedule::{ScheduleConfigure, ScheduleLabel};
-    use wok_core::world::WorldCenter;
+    use wok_core::world::{World, WorldCenter};
 
     use crate::RouterRoot;
     use crate::handler::AxumRouteSystem;
+    use crate::openapi::{ApiDocs, Operation, OperationInput, OperationOutput, PathItem};
 
     pub struct MethodRouterMut<'m>(&'m mut MethodRouter<RemoteWorldPorts>);
 
@@ -130,40 +193,44 @@ mod single_route {
 
     macro_rules! method_filter_fn {
         (self, $name:ident : $method:ident) => {
-            fn $name<S, SMarker, HMarker, OMarker>(self, system: S) -> impl ConfigureRoute
+            fn $name<S, SMarker, HMarker, OMarker>(
+                self,
+                system: S,
+            ) -> (Self, OnRoute<S::System, HMarker>)
             where
                 OnRoute<S::System, HMarker>: ConfigureRoute,
                 S: IntoSystem<SMarker>,
                 S::System: System<Out: crate::handler::WokIntoResponse<OMarker>>,
             {
-                let route = OnRoute {
-                    system: system.into_system(),
-                    _marker: std::marker::PhantomData,
-                    method: MethodFilter::$method,
-                };
-
-                (self, route)
+                (
+                    self,
+                    OnRoute::new(system.into_system(), MethodFilter::$method),
+                )
             }
         };
 
         ($name:ident : $method:ident) => {
-            pub fn $name<S, SMarker, HMarker, OMarker>(system: S) -> impl ConfigureRoute
+            pub fn $name<S, SMarker, HMarker, OMarker>(system: S) -> OnRoute<S::System, HMarker>
             where
                 OnRoute<S::System, HMarker>: ConfigureRoute,
                 S: IntoSystem<SMarker>,
                 S::System: System<Out: crate::handler::WokIntoResponse<OMarker>>,
             {
-                OnRoute {
-                    system: system.into_system(),
-                    _marker: std::marker::PhantomData,
-                    method: MethodFilter::$method,
-                }
+                OnRoute::new(system.into_system(), MethodFilter::$method)
             }
         };
     }
 
     pub trait ConfigureRoute: Sized {
         fn cfg(self, router: &mut MethodRouterMut<'_>, world: &mut WorldCenter);
+
+        /// Prepares the world before the route is configured, such as the resources read by
+        /// its middlewares
+        fn setup(&self, _world: &mut World) {}
+
+        /// Documents the operations of this route
+        fn docs(&self, _item: &mut PathItem) {}
+
         fn into_route(self, world: &mut WorldCenter) -> MethodRouter<RemoteWorldPorts> {
             let mut router = MethodRouter::new();
             self.cfg(&mut MethodRouterMut(&mut router), world);
@@ -171,6 +238,43 @@ mod single_route {
             router
         }
 
+        /// Only runs the route when the `auth::AuthPlugin` principal has the role,
+        /// answers `401 Unauthorized` without a principal and `403 Forbidden` without the role
+        fn require(self, role: &'static str) -> crate::auth::Require<Self> {
+            crate::auth::Require { route: self, role }
+        }
+
+        /// Limits the route with a policy of the `limit::RateLimits` resource, answering
+        /// `429 Too Many Requests` with a `Retry-After` header once exhausted. Serving fails
+        /// when the policy is missing
+        fn rate_limit(self, policy: &'static str) -> crate::limit::RateLimited<Self> {
+            crate::limit::RateLimited {
+                route: self,
+                policy,
+            }
+        }
+
+        /// Runs at most `max` requests of this route at once, answering
+        /// `503 Service Unavailable` to the others
+        fn concurrency_limit(self, max: usize) -> crate::limit::ConcurrencyLimited<Self> {
+            crate::limit::ConcurrencyLimited {
+                route: self,
+                max,
+                status: axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            }
+        }
+
+        /// Wraps the methods of this route with the layer, unmatched methods skip it
+        fn route_layer<L>(self, layer: L) -> RouteLayered<Self, L>
+        where
+            L: tower_layer::Layer<AxumRoute> + Clone + Send + Sync + 'static,
+            L::Service: Service<Request, Error = Infallible> + Clone + Send + Sync + 'static,
+            <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+            <L::Service as Service<Request>>::Future: Send + 'static,
+        {
+            RouteLayered { route: self, layer }
+        }
+
         method_filter_fn!(self, get: GET);
         method_filter_fn!(self, post: POST);
         method_filter_fn!(self, put: PUT);
@@ -181,10 +285,66 @@ mod single_route {
         method_filter_fn!(self, trace: TRACE);
     }
 
+    pub struct RouteLayered<R, L> {
+        route: R,
+        layer: L,
+    }
+
+    impl<R: ConfigureRoute, L> ConfigureRoute for RouteLayered<R, L>
+    where
+        L: tower_layer::Layer<AxumRoute> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request, Error = Infallible> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        fn cfg(self, router: &mut MethodRouterMut<'_>, world: &mut WorldCenter) {
+            let route = self.route.into_route(world).route_layer(self.layer);
+            router.mutate(move |router| router.merge(route));
+        }
+
+        fn setup(&self, world: &mut World) {
+            self.route.setup(world);
+        }
+
+        fn docs(&self, item: &mut PathItem) {
+            self.route.docs(item);
+        }
+    }
+
     pub struct OnRoute<S, Marker> {
         system: S,
         _marker: std::marker::PhantomData<fn(Marker)>,
         method: MethodFilter,
+        operation: Option<Operation>,
+    }
+
+    impl<S: System, Marker> OnRoute<S, Marker> {
+        fn new(system: S, method: MethodFilter) -> Self {
+            OnRoute {
+                system,
+                _marker: std::marker::PhantomData,
+                method,
+                operation: None,
+            }
+        }
+
+        /// Documents the operation from the extractors and response of the system, otherwise
+        /// the route is listed without them
+        pub fn documented(mut self) -> Self
+        where
+            S::In: OperationInput,
+            S::Out: OperationOutput,
+        {
+            self.operation = Some(Operation::new::<S::In, S::Out>());
+            self
+        }
+
+        /// Documents the operation as described, for systems whose extractors or response are
+        /// only known at runtime
+        pub fn documented_as(mut self, operation: Operation) -> Self {
+            self.operation = Some(operation);
+            self
+        }
     }
 
     impl<Marker: 'static, S> ConfigureRoute for OnRoute<S, Marker>
@@ -198,6 +358,14 @@ mod single_route {
 
             router.on(self.method, handler);
         }
+
+        fn docs(&self, item: &mut PathItem) {
+            let operation = self
+                .operation
+                .clone()
+                .unwrap_or_else(Operation::undocumented);
+            item.set(self.method, operation);
+        }
     }
 
     macro_rules! impl_configure_route {
@@ -210,6 +378,22 @@ mod single_route {
                         $name.cfg(router, world);
                     )*
                 }
+
+                fn setup(&self, world: &mut World) {
+                    #[allow(non_snake_case)]
+                    let ($($name,)*) = self;
+                    $(
+                        $name.setup(world);
+                    )*
+                }
+
+                fn docs(&self, item: &mut PathItem) {
+                    #[allow(non_snake_case)]
+                    let ($($name,)*) = self;
+                    $(
+                        $name.docs(item);
+                    )*
+                }
             }
         };
     }
@@ -236,7 +420,13 @@ mod single_route {
     impl<'r> ScheduleLabel for Route<'r> {}
 
     impl<'r, R: ConfigureRoute> ScheduleConfigure<R, ()> for Route<'r> {
-        fn add(self, world: &mut wok_core::world::World, thing: R) {
+        fn add(self, world: &mut World, thing: R) {
+            thing.setup(world);
+
+            let mut item = PathItem::default();
+            thing.docs(&mut item);
+            world.get::<ResMut<'_, ApiDocs>>().add(self.0, item);
+
             let (mut router, center) = world.get_and_center::<ResMut<'_, RouterRoot>>();
             let router = router.0.as_mut().expect("router");
 
@@ -247,10 +437,21 @@ mod single_route {
 }
 
 mod nest_route {
+    use std::convert::Infallible;
+
+    use axum::{extract::Request, response::IntoResponse, routing::Route as AxumRoute};
+    use tower_service::Service;
     use wok::prelude::ResMut;
-    use wok_core::{schedule::ScheduleConfigure, world::WorldCenter};
+    use wok_core::{
+        schedule::{ScheduleConfigure, ScheduleLabel},
+        world::{World, WorldCenter},
+    };
 
-    use crate::{RouterRoot, single_route::ConfigureRoute};
+    use crate::{
+        RouterRoot,
+        openapi::{ApiDocs, PathItem},
+        single_route::ConfigureRoute,
+    };
 
     pub trait ConfigureRoutesSet: Sized {
         fn route(self, path: &'static str, routes: impl ConfigureRoute) -> impl ConfigureRoutesSet {
@@ -261,7 +462,25 @@ mod nest_route {
             }
         }
 
+        /// Wraps the routes of this set with the layer, routes chained after it are not wrapped
+        fn route_layer<L>(self, layer: L) -> impl ConfigureRoutesSet
+        where
+            L: tower_layer::Layer<AxumRoute> + Clone + Send + Sync + 'static,
+            L::Service: Service<Request> + Clone + Send + Sync + 'static,
+            <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+            <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+            <L::Service as Service<Request>>::Future: Send + 'static,
+        {
+            SetLayer { set: self, layer }
+        }
+
         fn cfg(self, router: super::Router, world: &mut WorldCenter) -> super::Router;
+
+        /// Prepares the world before the routes are configured, see `ConfigureRoute::setup`
+        fn setup(&self, _world: &mut World) {}
+
+        /// Documents the routes of this set
+        fn docs(&self, _docs: &mut ApiDocs) {}
     }
 
     pub fn routes(path: &'static str, routes: impl ConfigureRoute) -> impl ConfigureRoutesSet {
@@ -292,85 +511,77 @@ mod nest_route {
 
             router.route(self.path, route)
         }
-    }
 
-    pub struct NestRoutes(pub &'static str);
+        fn setup(&self, world: &mut World) {
+            self.prev_layer.setup(world);
+            self.into_route.setup(world);
+        }
 
-    impl<L: ConfigureRoutesSet> ScheduleConfigure<L, ()> for NestRoutes {
-        fn add(self, world: &mut wok_core::world::World, thing: L) {
-            let (mut router, center) = world.get_and_center::<ResMut<'_, RouterRoot>>();
-            let router = router.0.as_mut().expect("router");
+        fn docs(&self, docs: &mut ApiDocs) {
+            self.prev_layer.docs(docs);
 
-            let axum_router = thing.cfg(axum::Router::new(), center);
-            take_mut::take(router, move |r| r.nest(self.0, axum_router));
+            let mut item = PathItem::default();
+            self.into_route.docs(&mut item);
+            docs.add(self.path, item);
         }
     }
-}
 
-#[derive(Debug, serde::Deserialize, Clone)]
-#[serde(untagged)]
-pub enum Addr {
-    // try to deserialize plain socket addr first
-    Plain(std::net::SocketAddr),
-    // then allow custom hosts
-    Unresolved(String),
-    HostPort(String, u16),
-}
+    struct SetLayer<S, L> {
+        set: S,
+        layer: L,
+    }
 
-#[derive(wok::prelude::Resource, serde::Deserialize, Debug, Clone)]
-#[serde(untagged)]
-pub enum SocketAddrs {
-    Single(Addr),
-    Multiple(Vec<Addr>),
-}
+    impl<S: ConfigureRoutesSet, L> ConfigureRoutesSet for SetLayer<S, L>
+    where
+        L: tower_layer::Layer<AxumRoute> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        fn cfg(self, router: crate::Router, world: &mut WorldCenter) -> crate::Router {
+            let router = self.set.cfg(router, world);
 
-/// Main runtime for wok_axum
-/// Requires tthe he `AxumPlugin` & a `SocketAddrs` resource
-pub async fn serve(
-    world: RemoteWorldRef<'_>,
-    addrs: Res<'_, SocketAddrs>,
-    mut router: ResMut<'_, RouterRoot>,
-) -> Result<(), WokUnknownError> {
-    let world = world.upgrade().expect("the app to be active");
-
-    let router = router
-        .0
-        .take()
-        .expect("to have `AxumPlugin`")
-        .layer(axum::Extension(world.clone()))
-        .with_state(world);
-
-    async fn lookup_addr(addr: &Addr) -> std::io::Result<Vec<std::net::SocketAddr>> {
-        match addr {
-            Addr::Plain(addr) => Ok(vec![*addr]),
-            Addr::Unresolved(host) => tokio::net::lookup_host(host)
-                .await
-                .map(|addrs| addrs.collect()),
-            Addr::HostPort(host, port) => tokio::net::lookup_host((host.as_str(), *port))
-                .await
-                .map(|addrs| addrs.collect()),
-        }
-    }
-
-    let addrs = match addrs.as_ref() {
-        SocketAddrs::Single(addr) => lookup_addr(addr).await?,
-        SocketAddrs::Multiple(addrs) => {
-            let mut res = vec![];
-            let set = addrs.iter().map(lookup_addr);
-            for addrs in futures::future::join_all(set).await {
-                res.extend(addrs?);
+            // axum panics on route layers without routes
+            if router.has_routes() {
+                router.route_layer(self.layer)
+            } else {
+                router
             }
+        }
 
-            res
+        fn setup(&self, world: &mut World) {
+            self.set.setup(world);
         }
-    };
 
-    println!("listening on: {addrs:?}");
+        fn docs(&self, docs: &mut ApiDocs) {
+            self.set.docs(docs);
+        }
+    }
+
+    /// Nests a set of routes under a path
+    pub struct NestRoutes(pub &'static str);
+
+    impl ScheduleLabel for NestRoutes {}
+
+    impl<L: ConfigureRoutesSet> ScheduleConfigure<L, ()> for NestRoutes {
+        fn add(self, world: &mut World, thing: L) {
+            thing.setup(world);
+
+            {
+                let mut docs = world.get::<ResMut<'_, ApiDocs>>();
+                docs.push_prefix(self.0);
+                thing.docs(&mut docs);
+                docs.pop_prefix();
+            }
 
-    let listener = tokio::net::TcpListener::bind(addrs.as_slice()).await?;
-    axum::serve(listener, router).await?;
+            let (mut router, center) = world.get_and_center::<ResMut<'_, RouterRoot>>();
+            let router = router.0.as_mut().expect("router");
 
-    Ok(())
+            let axum_router = thing.cfg(axum::Router::new(), center);
+            take_mut::take(router, move |r| r.nest(self.0, axum_router));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -386,10 +597,100 @@ mod tests {
         fn setup(self, app: &mut wok::prelude::App) {
             app.add_systems(Route("/hello"), get(simple_route).post(parse_req))
                 .add_systems(Route("/hello/{data}"), get(parse_req_part))
-                .add_systems(Route("/error"), get(input_less_err));
+                .add_systems(Route("/error"), get(input_less_err))
+                .add_systems(Route("/live"), ws::ws(live_message))
+                .add_systems(Route("/live/all"), ws::ws(live_session).per_connection())
+                .add_systems(Route("/events"), get(events))
+                .add_systems(Route("/download"), get(download))
+                .add_systems(Route("/me"), get(me))
+                .add_systems(Route("/search"), get(search).post(upload))
+                .add_systems(Route("/search/{q}"), get(search_path).post(search_form))
+                .add_systems(Route("/limited"), get(simple_route).rate_limit("test"))
+                .add_systems(
+                    Route("/busy"),
+                    get(simple_route).concurrency_limit(1).too_many_requests(),
+                )
+                .add_systems(Route("/admin"), get(me).require("admin"))
+                .add_systems(
+                    Route("/tagged"),
+                    get(simple_route).route_layer(axum::Extension("tagged")),
+                )
+                .add_systems(
+                    NestRoutes("/nested"),
+                    routes("/", get(simple_route)).route_layer(axum::Extension("nested")),
+                )
+                .add_plugin(auth::AuthPlugin::new(verify).cookie("session"))
+                .add_plugin(observe::ObservabilityPlugin)
+                .add_plugin(observe::ReadinessCheck::new(is_ready))
+                .add_objs(Layer.order(10), axum::Extension("outermost"))
+                .add_objs(RouteLayer, axum::Extension("matched"))
+                .add_plugin(AxumListenerPlugin("admin", AdminPlugin));
         }
     }
 
+    struct AdminPlugin;
+    impl Plugin for AdminPlugin {
+        fn setup(self, app: &mut wok::prelude::App) {
+            app.add_systems(Route("/stats"), get(simple_route))
+                .add_objs(Layer, axum::Extension("admin"));
+        }
+    }
+
+    #[derive(Clone)]
+    struct User(bool);
+
+    impl auth::HasRoles for User {
+        fn has_role(&self, role: &str) -> bool {
+            role == "admin" && self.0
+        }
+    }
+
+    async fn verify(
+        In(credentials): In<auth::Credentials>,
+        _: wok::prelude::Res<'_, MyRes>,
+    ) -> Result<Option<User>, WokUnknownError> {
+        Ok(Some(User(credentials.token() == "root")))
+    }
+
+    async fn me(In(auth::Principal(user)): In<auth::Principal<User>>) -> &'static str {
+        if user.0 { "admin" } else { "user" }
+    }
+
+    #[derive(serde::Deserialize)]
+    struct SearchIn {
+        q: String,
+    }
+
+    struct Search(String);
+
+    impl valigate::Valid for Search {
+        type In = SearchIn;
+
+        fn parse(input: Self::In) -> Result<Self, valigate::Error> {
+            Ok(Search(input.q))
+        }
+    }
+
+    async fn search(
+        In((extract::QueryG(_), extract::HeaderG(_))): In<(
+            extract::QueryG<Search>,
+            extract::HeaderG<Search>,
+        )>,
+    ) {
+    }
+
+    async fn search_path(In(extract::PathG(_)): In<extract::PathG<Search>>) {}
+
+    async fn search_form(In(extract::FormG(_)): In<extract::FormG<Search>>) {}
+
+    async fn upload(In(form): In<extract::MultipartG<Search>>) -> String {
+        format!("{} files", form.files.len())
+    }
+
+    async fn is_ready(_: wok::prelude::Res<'_, MyRes>) -> bool {
+        true
+    }
+
     async fn simple_route() -> &'static str {
         "hello"
     }
@@ -401,4 +702,33 @@ mod tests {
     async fn parse_req(_: In<String>, _: wok::prelude::Res<'_, MyRes>) {}
 
     async fn parse_req_part(_: In<axum::extract::Path<String>>) {}
+
+    async fn live_message(
+        In(mut session): In<ws::WebSocketSession>,
+        _: wok::prelude::Res<'_, MyRes>,
+    ) -> Result<(), WokUnknownError> {
+        while let Some(message) = session.recv().await {
+            session.send(message?).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn events() -> stream::Sse<
+        impl futures::Stream<Item = Result<stream::Event, std::convert::Infallible>> + Send + 'static,
+    > {
+        stream::Sse::new(futures::stream::iter([Ok(
+            stream::Event::default().data("hello")
+        )]))
+    }
+
+    async fn download() -> stream::Chunked<
+        impl futures::Stream<Item = Result<&'static str, WokUnknownError>> + Send + 'static,
+    > {
+        stream::Chunked(futures::stream::iter([Ok("hello"), Ok("world")]))
+    }
+
+    async fn live_session(In(session): In<ws::WebSocketSession>) {
+        let _ = session.send("hello").await;
+    }
 }
@@ -0,0 +1,647 @@
+//! Rate limiting and concurrency caps of routes
+//! Rate limits are named policies of the `RateLimits` resource, usually loaded with
+//! `wok_assets`. Their counters are the `RateCounters` resource, so other systems can inspect
+//! and reset them. Serving fails when a route names a policy missing from `RateLimits`
+//! ```toml
+//! [rate_limits.search]
+//! mode = "token_bucket"
+//! capacity = 20
+//! refill_per_second = 2.0
+//! per = "principal"
+//!
+//! [rate_limits.login]
+//! mode = "sliding_window"
+//! limit = 5
+//! window_secs = 60
+//! ```
+//! ```rust
+//! use wok::prelude::*;
+//! use wok_axum::{AxumPlugin, ConfigureRoute, Route, get};
+//!
+//! App::default()
+//!     .add_plugin(AxumPlugin)
+//!     .add_systems(Route("/search"), get(search).rate_limit("search"))
+//!     .add_systems(Route("/report"), get(search).concurrency_limit(4));
+//!
+//! async fn search() {}
+//! ```
+
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    convert::Infallible,
+    hash::{Hash, Hasher},
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Extension,
+    extract::{FromRequestParts, MatchedPath, Request},
+    http::{HeaderValue, StatusCode, header, request::Parts},
+    middleware::Next,
+    response::IntoResponse,
+};
+use wok::{
+    prelude::{IntoSystem, Res, ResMut, Resource, WokUnknownError},
+    remote_gateway::{RemoteParams, RemoteWorldPorts},
+};
+use wok_core::world::{World, WorldCenter};
+
+use crate::{
+    ConfigureRoute, MethodRouterMut, MiddlPartsIn,
+    auth::Credentials,
+    layer::system_middleware,
+    openapi::{PathItem, Response},
+    problem::Problem,
+};
+
+/// Whose requests share a counter
+#[derive(serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RateScope {
+    /// Every request of the route
+    #[default]
+    Route,
+    /// Requests with the same `auth::AuthPlugin` credentials, anonymous requests share one
+    Principal,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum RateMode {
+    /// Bursts up to `capacity` requests, refilled continuously
+    TokenBucket {
+        capacity: u32,
+        refill_per_second: f64,
+    },
+    /// At most `limit` requests within the last `window_secs`
+    SlidingWindow { limit: u32, window_secs: u64 },
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    #[serde(flatten)]
+    pub mode: RateMode,
+    #[serde(default)]
+    pub per: RateScope,
+}
+
+/// Rate limit policies by name, see `ConfigureRoute::rate_limit`
+#[derive(Resource, serde::Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct RateLimits(pub HashMap<String, RateLimit>);
+
+/// Counter of a policy, route and principal
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RateKey {
+    pub policy: Arc<str>,
+    pub route: Arc<str>,
+    /// Hash of the principal credentials, `None` for route scoped and anonymous requests
+    pub principal: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum RateCounter {
+    TokenBucket { tokens: f64, updated: Instant },
+    SlidingWindow { hits: VecDeque<Instant> },
+}
+
+impl RateCounter {
+    fn new(mode: &RateMode, now: Instant) -> Self {
+        match mode {
+            RateMode::TokenBucket { capacity, .. } => RateCounter::TokenBucket {
+                tokens: *capacity as f64,
+                updated: now,
+            },
+            RateMode::SlidingWindow { .. } => RateCounter::SlidingWindow {
+                hits: VecDeque::new(),
+            },
+        }
+    }
+
+    /// Takes one request, or returns how long to wait for the next one
+    fn hit(&mut self, mode: &RateMode, now: Instant) -> Result<(), Duration> {
+        match (self, mode) {
+            (
+                RateCounter::TokenBucket { tokens, updated },
+                RateMode::TokenBucket {
+                    capacity,
+                    refill_per_second,
+                },
+            ) => {
+                let elapsed = now.saturating_duration_since(*updated).as_secs_f64();
+                *tokens = (*tokens + elapsed * refill_per_second).min(*capacity as f64);
+                *updated = now;
+
+                if *tokens >= 1. {
+                    *tokens -= 1.;
+                    return Ok(());
+                }
+
+                let wait = (1. - *tokens) / refill_per_second;
+                Err(Duration::try_from_secs_f64(wait).unwrap_or(Duration::MAX))
+            }
+            (
+                RateCounter::SlidingWindow { hits },
+                RateMode::SlidingWindow { limit, window_secs },
+            ) => {
+                let window = Duration::from_secs(*window_secs);
+                while hits
+                    .front()
+                    .is_some_and(|hit| now.saturating_duration_since(*hit) >= window)
+                {
+                    hits.pop_front();
+                }
+
+                if hits.len() < *limit as usize {
+                    hits.push_back(now);
+                    return Ok(());
+                }
+
+                let oldest = hits.front().copied().unwrap_or(now);
+                Err(window.saturating_sub(now.saturating_duration_since(oldest)))
+            }
+            // the policy changed mode, start over
+            (counter, mode) => {
+                *counter = RateCounter::new(mode, now);
+                counter.hit(mode, now)
+            }
+        }
+    }
+
+    /// Whether the counter is back to its initial state
+    fn is_idle(&self, mode: &RateMode, now: Instant) -> bool {
+        match (self, mode) {
+            (
+                RateCounter::TokenBucket { tokens, updated },
+                RateMode::TokenBucket {
+                    capacity,
+                    refill_per_second,
+                },
+            ) => {
+                let elapsed = now.saturating_duration_since(*updated).as_secs_f64();
+                tokens + elapsed * refill_per_second >= *capacity as f64
+            }
+            (RateCounter::SlidingWindow { hits }, RateMode::SlidingWindow { window_secs, .. }) => {
+                hits.back().is_none_or(|hit| {
+                    now.saturating_duration_since(*hit) >= Duration::from_secs(*window_secs)
+                })
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Counters of every rate limited route
+#[derive(Resource, Debug, Default)]
+#[resource(mutable = true)]
+pub struct RateCounters {
+    counters: HashMap<RateKey, RateCounter>,
+    hits: u64,
+}
+
+impl RateCounters {
+    pub fn get(&self, key: &RateKey) -> Option<&RateCounter> {
+        self.counters.get(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&RateKey, &RateCounter)> {
+        self.counters.iter()
+    }
+
+    pub fn reset(&mut self, key: &RateKey) {
+        self.counters.remove(key);
+    }
+
+    /// Resets every counter of the policy
+    pub fn reset_policy(&mut self, policy: &str) {
+        self.counters.retain(|key, _| &*key.policy != policy);
+    }
+
+    pub fn clear(&mut self) {
+        self.counters.clear();
+    }
+
+    pub fn hit(&mut self, key: RateKey, limit: &RateLimit, now: Instant) -> Result<(), Duration> {
+        self.hits += 1;
+        // principal scoped policies leave a counter per client behind
+        if self.hits.is_multiple_of(1024) {
+            self.prune(limit, &key.policy, now);
+        }
+
+        self.counters
+            .entry(key)
+            .or_insert_with(|| RateCounter::new(&limit.mode, now))
+            .hit(&limit.mode, now)
+    }
+
+    fn prune(&mut self, limit: &RateLimit, policy: &str, now: Instant) {
+        self.counters
+            .retain(|key, counter| &*key.policy != policy || !counter.is_idle(&limit.mode, now));
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("missing rate limit policy `{0}`")]
+pub struct MissingRateLimit(pub Arc<str>);
+
+/// Policies named by the rate limited routes, checked against `RateLimits` once the routers
+/// are finished
+#[derive(Resource, Debug, Default)]
+#[resource(mutable = true)]
+pub struct RatePolicies(BTreeSet<&'static str>);
+
+impl RatePolicies {
+    /// The first policy missing from the limits
+    pub fn check(&self, limits: Option<&RateLimits>) -> Result<(), MissingRateLimit> {
+        match self
+            .0
+            .iter()
+            .find(|policy| limits.is_none_or(|limits| !limits.0.contains_key(**policy)))
+        {
+            Some(policy) => Err(MissingRateLimit((*policy).into())),
+            None => Ok(()),
+        }
+    }
+}
+
+type RateParams = (
+    Option<Res<'static, RateLimits>>,
+    ResMut<'static, RateCounters>,
+);
+
+/// Policy of the route, inserted before `check_rate` runs
+#[derive(Clone)]
+struct RatePolicy(Arc<str>);
+
+/// Request counted by `check_rate`
+struct RateRequest {
+    world: RemoteWorldPorts,
+    policy: Arc<str>,
+    route: Arc<str>,
+    principal: Option<u64>,
+}
+
+impl FromRequestParts<()> for RateRequest {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &()) -> Result<Self, Self::Rejection> {
+        let world = parts.extensions.get::<RemoteWorldPorts>().cloned();
+        let policy = parts.extensions.get::<RatePolicy>().cloned();
+        let (Some(world), Some(RatePolicy(policy))) = (world, policy) else {
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        };
+
+        let route = parts
+            .extensions
+            .get::<MatchedPath>()
+            .map_or_else(|| parts.uri.path().into(), |path| path.as_str().into());
+
+        Ok(RateRequest {
+            world,
+            policy,
+            route,
+            principal: credentials_hash(parts),
+        })
+    }
+}
+
+/// Counters are only reserved while checking, not for the whole request
+async fn check_rate(
+    MiddlPartsIn(request, req, next): MiddlPartsIn<RateRequest>,
+    params: Res<'_, RemoteParams<RateParams>>,
+) -> Result<axum::response::Response, WokUnknownError> {
+    let checked = {
+        let mut permit = request.world.reserve_params(*params).await;
+        let (limits, mut counters) = permit.get();
+
+        let limit = limits
+            .as_ref()
+            .and_then(|limits| limits.0.get(&*request.policy))
+            .ok_or_else(|| MissingRateLimit(request.policy.clone()))?;
+
+        let principal = match limit.per {
+            RateScope::Route => None,
+            RateScope::Principal => request.principal,
+        };
+        let key = RateKey {
+            policy: request.policy,
+            route: request.route,
+            principal,
+        };
+
+        counters.hit(key, limit, Instant::now())
+    };
+
+    match checked {
+        Ok(()) => Ok(next.run(req).await),
+        Err(retry_after) => Ok(too_many_requests(retry_after)),
+    }
+}
+
+fn credentials_hash(parts: &Parts) -> Option<u64> {
+    let credentials = parts.extensions.get::<Credentials>()?;
+    let mut hasher = std::hash::DefaultHasher::new();
+    credentials.token().hash(&mut hasher);
+
+    Some(hasher.finish())
+}
+
+fn too_many_requests(retry_after: Duration) -> axum::response::Response {
+    let mut response = Problem::new(StatusCode::TOO_MANY_REQUESTS).into_response();
+    // whole seconds, rounded up
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+
+    response
+}
+
+/// Route limited by a `RateLimits` policy, see `ConfigureRoute::rate_limit`
+pub struct RateLimited<R> {
+    pub(crate) route: R,
+    pub(crate) policy: &'static str,
+}
+
+impl<R: ConfigureRoute> ConfigureRoute for RateLimited<R> {
+    fn cfg(self, router: &mut MethodRouterMut<'_>, world: &mut WorldCenter) {
+        let middleware = system_middleware(world, check_rate.into_system());
+
+        let limited = self
+            .route
+            .into_route(world)
+            .layer::<_, Infallible>(axum::middleware::from_fn(middleware))
+            .layer(Extension(RatePolicy(self.policy.into())));
+
+        router.mutate(move |router| router.merge(limited));
+    }
+
+    fn setup(&self, world: &mut World) {
+        RemoteParams::<RateParams>::init(world);
+        world
+            .get::<ResMut<'_, RatePolicies>>()
+            .0
+            .insert(self.policy);
+
+        self.route.setup(world);
+    }
+
+    fn docs(&self, item: &mut PathItem) {
+        let mut limited = PathItem::default();
+        self.route.docs(&mut limited);
+
+        for operation in limited.operations_mut() {
+            operation.response(
+                "429",
+                Response::new("Too many requests")
+                    .content("application/problem+json", Problem::schema()),
+            );
+        }
+
+        item.merge(limited);
+    }
+}
+
+/// Route running at most `max` requests at once, see `ConfigureRoute::concurrency_limit`
+pub struct ConcurrencyLimited<R> {
+    pub(crate) route: R,
+    pub(crate) max: usize,
+    pub(crate) status: StatusCode,
+}
+
+impl<R> ConcurrencyLimited<R> {
+    /// Rejects with `429 Too Many Requests` instead of `503 Service Unavailable`
+    pub fn too_many_requests(mut self) -> Self {
+        self.status = StatusCode::TOO_MANY_REQUESTS;
+        self
+    }
+}
+
+/// Releases the slot once the response is ready
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn acquire(running: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        running
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Slot(running.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<R: ConfigureRoute> ConfigureRoute for ConcurrencyLimited<R> {
+    fn cfg(self, router: &mut MethodRouterMut<'_>, world: &mut WorldCenter) {
+        let running = Arc::new(AtomicUsize::new(0));
+        let (max, status) = (self.max, self.status);
+
+        let limited = self
+            .route
+            .into_route(world)
+            .layer(axum::middleware::from_fn(
+                move |req: Request, next: Next| {
+                    let slot = Slot::acquire(&running, max);
+                    async move {
+                        let Some(_slot) = slot else {
+                            return Problem::new(status).into_response();
+                        };
+
+                        next.run(req).await
+                    }
+                },
+            ));
+
+        router.mutate(move |router| router.merge(limited));
+    }
+
+    fn setup(&self, world: &mut World) {
+        self.route.setup(world);
+    }
+
+    fn docs(&self, item: &mut PathItem) {
+        let mut limited = PathItem::default();
+        self.route.docs(&mut limited);
+
+        for operation in limited.operations_mut() {
+            operation.response(
+                self.status.as_str().to_owned(),
+                Response::new(self.status.canonical_reason().unwrap_or("Overloaded"))
+                    .content("application/problem+json", Problem::schema()),
+            );
+        }
+
+        item.merge(limited);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(principal: Option<u64>) -> RateKey {
+        RateKey {
+            policy: "test".into(),
+            route: "/".into(),
+            principal,
+        }
+    }
+
+    #[test]
+    fn token_bucket_refills() {
+        let limit = RateLimit {
+            mode: RateMode::TokenBucket {
+                capacity: 2,
+                refill_per_second: 1.,
+            },
+            per: RateScope::Route,
+        };
+        let mut counters = RateCounters::default();
+        let now = Instant::now();
+
+        assert!(counters.hit(key(None), &limit, now).is_ok());
+        assert!(counters.hit(key(None), &limit, now).is_ok());
+        assert_eq!(
+            counters.hit(key(None), &limit, now),
+            Err(Duration::from_secs(1))
+        );
+
+        let later = now + Duration::from_millis(1500);
+        assert!(counters.hit(key(None), &limit, later).is_ok());
+        assert!(counters.hit(key(None), &limit, later).is_err());
+    }
+
+    #[test]
+    fn sliding_window_forgets_old_hits() {
+        let limit = RateLimit {
+            mode: RateMode::SlidingWindow {
+                limit: 1,
+                window_secs: 10,
+            },
+            per: RateScope::Principal,
+        };
+        let mut counters = RateCounters::default();
+        let now = Instant::now();
+
+        assert!(counters.hit(key(Some(1)), &limit, now).is_ok());
+        // another principal has its own counter
+        assert!(counters.hit(key(Some(2)), &limit, now).is_ok());
+
+        let later = now + Duration::from_secs(4);
+        assert_eq!(
+            counters.hit(key(Some(1)), &limit, later),
+            Err(Duration::from_secs(6))
+        );
+        assert!(
+            counters
+                .hit(key(Some(1)), &limit, now + Duration::from_secs(10))
+                .is_ok()
+        );
+
+        counters.reset_policy("test");
+        assert!(counters.iter().next().is_none());
+    }
+
+    #[cfg(feature = "testing")]
+    async fn count_limited(counters: Res<'_, RateCounters>) -> String {
+        counters.iter().count().to_string()
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn limits_routes_without_holding_the_counters() {
+        use wok::prelude::*;
+
+        use crate::{AxumPlugin, Route, get, testing::TestApp};
+
+        let limits = RateLimits(HashMap::from([(
+            "test".to_owned(),
+            RateLimit {
+                mode: RateMode::SlidingWindow {
+                    limit: 1,
+                    window_secs: 60,
+                },
+                per: RateScope::Route,
+            },
+        )]));
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .insert_resource(limits)
+            .add_systems(Route("/limited"), get(count_limited).rate_limit("test"));
+
+        TestApp::new(app)
+            .params::<ResMut<'static, RateCounters>>()
+            .run(|client| async move {
+                // the route reads the counters while the request runs
+                let res = client.get("/limited").await;
+                assert_eq!(res.status(), 200);
+                assert_eq!(res.text(), "1");
+
+                let res = client.get("/limited").await;
+                assert_eq!(res.status(), 429);
+                assert_eq!(res.headers()[header::RETRY_AFTER], "60");
+
+                client
+                    .params::<ResMut<'static, RateCounters>>()
+                    .await
+                    .get()
+                    .reset_policy("test");
+                assert_eq!(client.get("/limited").await.status(), 200);
+            })
+            .await
+            .unwrap();
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn fails_to_serve_without_the_policy() {
+        use wok::prelude::*;
+
+        use crate::{AxumPlugin, Route, get, testing::TestApp};
+
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .add_systems(Route("/limited"), get(count_limited).rate_limit("missing"));
+
+        let err = TestApp::new(app).run(|_| async {}).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("missing rate limit policy `missing`")
+        );
+    }
+
+    #[test]
+    fn deserializes_policies() {
+        let limits: RateLimits = toml::from_str(
+            r#"
+            [search]
+            mode = "token_bucket"
+            capacity = 20
+            refill_per_second = 2.0
+            per = "principal"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            limits.0["search"],
+            RateLimit {
+                mode: RateMode::TokenBucket {
+                    capacity: 20,
+                    refill_per_second: 2.
+                },
+                per: RateScope::Principal,
+            }
+        );
+    }
+}
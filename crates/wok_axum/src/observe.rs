@@ -0,0 +1,391 @@
+//! Health, readiness and Prometheus metrics routes
+//! `/health` always answers once served, `/ready` once `Startup` completed and every
+//! `ReadinessCheck` passes, and `/metrics` renders the `Metrics` of the routes
+//! ```rust
+//! use wok::prelude::*;
+//! use wok_axum::{AxumPlugin, observe::{ObservabilityPlugin, ReadinessCheck}};
+//!
+//! #[derive(Resource)]
+//! struct Db;
+//!
+//! async fn ping_db(_: Res<'_, Db>) -> Result<(), WokUnknownError> {
+//!     Ok(())
+//! }
+//!
+//! App::default()
+//!     .add_plugin(AxumPlugin)
+//!     .insert_resource(Db)
+//!     .add_plugin(ObservabilityPlugin)
+//!     .add_plugin(ReadinessCheck::new(ping_db));
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::IntoResponse,
+};
+use futures::future::BoxFuture;
+use wok::{
+    plugin::Plugin,
+    prelude::{
+        BorrowMutParam, ConfigureWorld, IntoSystem, ProtoTaskSystem, Res, ResMut, Resource, System,
+        WokUnknownError,
+    },
+    remote_gateway::{RemoteWorldPorts, RemoteWorldRef},
+};
+
+use crate::{Layer, LayerLabel, Route, get};
+
+/// Time a route system waited for its params, set by the route handlers
+#[derive(Clone, Copy)]
+pub(crate) struct LockWait(pub(crate) Duration);
+
+/// Marks responses of route systems that returned an error
+#[derive(Clone, Copy)]
+pub(crate) struct SystemFailed;
+
+/// Flipped once the app is served, which happens after `Startup`
+#[derive(Resource, Clone, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+    pub fn is_started(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn start(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+/// Output of a readiness check system, `false` or an error means not ready
+pub trait CheckOutput: Send + 'static {
+    fn into_check(self) -> Result<(), WokUnknownError>;
+}
+
+impl CheckOutput for bool {
+    fn into_check(self) -> Result<(), WokUnknownError> {
+        match self {
+            true => Ok(()),
+            false => Err(WokUnknownError::from_message("not ready")),
+        }
+    }
+}
+
+impl<E: Into<WokUnknownError> + Send + 'static> CheckOutput for Result<(), E> {
+    #[track_caller]
+    fn into_check(self) -> Result<(), WokUnknownError> {
+        match self {
+            Ok(value) => Ok(value),
+            Err(err) => Err(WokUnknownError::convert(err)),
+        }
+    }
+}
+
+type RunCheck =
+    Arc<dyn Fn(RemoteWorldPorts) -> BoxFuture<'static, Result<(), WokUnknownError>> + Send + Sync>;
+
+#[derive(Resource, Default, Clone)]
+#[resource(mutable = true)]
+pub struct ReadinessChecks(Vec<(&'static str, RunCheck)>);
+
+/// Adds a system to the checks of `/ready`, its params are only reserved while checking
+pub struct ReadinessCheck<S> {
+    system: S,
+}
+
+impl<S> ReadinessCheck<S> {
+    pub fn new<Sys, Marker>(system: Sys) -> Self
+    where
+        Sys: IntoSystem<Marker, System = S>,
+        S: System<In = (), Out: CheckOutput>,
+    {
+        Self {
+            system: system.into_system(),
+        }
+    }
+}
+
+impl<S> Plugin for ReadinessCheck<S>
+where
+    S: ProtoTaskSystem<Param: BorrowMutParam> + System<In = (), Out: CheckOutput>,
+{
+    fn setup(self, app: &mut wok::prelude::App) {
+        let name = std::any::type_name::<S>();
+        let system = app.world_mut().center.register_system(self.system);
+
+        let check: RunCheck = Arc::new(move |world: RemoteWorldPorts| {
+            let system = system.clone();
+            Box::pin(async move {
+                world
+                    .reserver()
+                    .reserve(system.entry_ref())
+                    .await
+                    .task()
+                    .run(())
+                    .await
+                    .into_check()
+            })
+        });
+
+        app.world_mut()
+            .get::<ResMut<'_, ReadinessChecks>>()
+            .0
+            .push((name, check));
+    }
+}
+
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10.];
+
+#[derive(Default, Clone)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: Duration) {
+        let secs = value.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter_mut().zip(BUCKETS) {
+            if secs <= bound {
+                *bucket += 1;
+            }
+        }
+
+        self.count += 1;
+        self.sum += secs;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        for (count, bound) in self.buckets.iter().zip(BUCKETS) {
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {count}");
+        }
+        let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", self.count);
+        let _ = writeln!(out, "{name}_sum{{{labels}}} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{{{labels}}} {}", self.count);
+    }
+}
+
+#[derive(Default)]
+struct MetricsState {
+    requests: HashMap<(String, String, u16), u64>,
+    latency: HashMap<(String, String), Histogram>,
+    lock_wait: HashMap<String, Histogram>,
+    errors: HashMap<String, u64>,
+}
+
+/// Request metrics of the routes, shared with the metrics layer
+#[derive(Resource, Clone, Default)]
+pub struct Metrics(Arc<Mutex<MetricsState>>);
+
+/// Label of requests without a matched route, keeps the label set bounded
+const UNMATCHED: &str = "<unmatched>";
+
+impl Metrics {
+    fn record(
+        &self,
+        method: &str,
+        route: &str,
+        response: &axum::response::Response,
+        took: Duration,
+    ) {
+        let mut state = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let status = response.status().as_u16();
+
+        *state
+            .requests
+            .entry((method.to_owned(), route.to_owned(), status))
+            .or_default() += 1;
+        state
+            .latency
+            .entry((method.to_owned(), route.to_owned()))
+            .or_default()
+            .observe(took);
+
+        if let Some(LockWait(wait)) = response.extensions().get::<LockWait>() {
+            state
+                .lock_wait
+                .entry(route.to_owned())
+                .or_default()
+                .observe(*wait);
+        }
+
+        if response.extensions().get::<SystemFailed>().is_some() {
+            *state.errors.entry(route.to_owned()).or_default() += 1;
+        }
+    }
+
+    /// Prometheus text format
+    pub fn render(&self) -> String {
+        let state = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let mut out = String::new();
+
+        out.push_str("# HELP http_requests_total Requests by route and status.\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for ((method, route, status), count) in sorted(&state.requests) {
+            let _ = writeln!(
+                out,
+                "http_requests_total{{method=\"{}\",route=\"{}\",status=\"{status}\"}} {count}",
+                escape(method),
+                escape(route),
+            );
+        }
+
+        out.push_str("# HELP http_request_duration_seconds Latency of the routes.\n");
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for ((method, route), histogram) in sorted(&state.latency) {
+            let labels = format!("method=\"{}\",route=\"{}\"", escape(method), escape(route));
+            histogram.render(&mut out, "http_request_duration_seconds", &labels);
+        }
+
+        out.push_str(
+            "# HELP wok_gateway_lock_wait_seconds Time route systems waited for their params.\n",
+        );
+        out.push_str("# TYPE wok_gateway_lock_wait_seconds histogram\n");
+        for (route, histogram) in sorted(&state.lock_wait) {
+            let labels = format!("route=\"{}\"", escape(route));
+            histogram.render(&mut out, "wok_gateway_lock_wait_seconds", &labels);
+        }
+
+        out.push_str("# HELP wok_system_errors_total Errors returned by route systems.\n");
+        out.push_str("# TYPE wok_system_errors_total counter\n");
+        for (route, count) in sorted(&state.errors) {
+            let _ = writeln!(
+                out,
+                "wok_system_errors_total{{route=\"{}\"}} {count}",
+                escape(route)
+            );
+        }
+
+        out
+    }
+}
+
+/// Stable output order between scrapes
+fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn escape(label: &str) -> String {
+    label
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Adds `/health`, `/ready` and `/metrics` to the current scope, and records the metrics of
+/// every route of the app. Use after `AxumPlugin`
+pub struct ObservabilityPlugin;
+
+impl Plugin for ObservabilityPlugin {
+    fn setup(self, app: &mut wok::prelude::App) {
+        app.init_resource::<Metrics>()
+            .init_resource::<ReadinessChecks>();
+
+        let metrics = Metrics::clone(&app.world_mut().get::<Res<'_, Metrics>>());
+        let record = move |req: Request, next: Next| {
+            let metrics = metrics.clone();
+            async move {
+                let method = req.method().to_string();
+                let route = req
+                    .extensions()
+                    .get::<MatchedPath>()
+                    .map_or(UNMATCHED, MatchedPath::as_str)
+                    .to_owned();
+
+                let start = Instant::now();
+                let response = next.run(req).await;
+                metrics.record(&method, &route, &response, start.elapsed());
+
+                response
+            }
+        };
+
+        app.add_objs(Layer.order(i32::MAX), axum::middleware::from_fn(record))
+            .add_systems(Route("/health"), get(health))
+            .add_systems(Route("/ready"), get(ready))
+            .add_systems(Route("/metrics"), get(render_metrics));
+    }
+}
+
+async fn health() -> &'static str {
+    "ok"
+}
+
+async fn ready(
+    world: RemoteWorldRef<'_>,
+    readiness: Res<'_, Readiness>,
+    checks: Res<'_, ReadinessChecks>,
+) -> (StatusCode, String) {
+    if !readiness.is_started() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "starting".to_owned());
+    }
+
+    let Some(world) = world.upgrade() else {
+        return (StatusCode::SERVICE_UNAVAILABLE, "stopping".to_owned());
+    };
+
+    for (name, check) in checks.0.iter() {
+        if let Err(err) = check(world.clone()).await {
+            tracing::warn!(check = name, %err, "readiness check failed");
+            return (StatusCode::SERVICE_UNAVAILABLE, format!("{name}: {err}"));
+        }
+    }
+
+    (StatusCode::OK, "ready".to_owned())
+}
+
+async fn render_metrics(metrics: Res<'_, Metrics>) -> axum::response::Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.render(),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_prometheus_text() {
+        let metrics = Metrics::default();
+
+        let mut response = StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        response
+            .extensions_mut()
+            .insert(LockWait(Duration::from_millis(20)));
+        response.extensions_mut().insert(SystemFailed);
+
+        metrics.record("GET", "/users/{id}", &response, Duration::from_millis(30));
+        let text = metrics.render();
+
+        assert!(text.contains(
+            "http_requests_total{method=\"GET\",route=\"/users/{id}\",status=\"500\"} 1"
+        ));
+        assert!(text.contains(
+            "http_request_duration_seconds_bucket{method=\"GET\",route=\"/users/{id}\",le=\"0.025\"} 0"
+        ));
+        assert!(text.contains(
+            "http_request_duration_seconds_bucket{method=\"GET\",route=\"/users/{id}\",le=\"0.05\"} 1"
+        ));
+        assert!(text.contains(
+            "wok_gateway_lock_wait_seconds_bucket{route=\"/users/{id}\",le=\"0.025\"} 1"
+        ));
+        assert!(text.contains("wok_system_errors_total{route=\"/users/{id}\"} 1"));
+    }
+}
@@ -0,0 +1,687 @@
+//! OpenAPI 3.1 documents built from the registered routes
+//! `documented` routes describe their `In<...>` extractors with `OperationInput` and their
+//! outputs with `OperationOutput`, implement them for custom extractors or responses to document
+//! them. Other routes are listed without their extractors nor responses
+//! ```rust
+//! use wok::prelude::*;
+//! use wok_axum::{AxumPlugin, Route, get, post, openapi::OpenApiPlugin};
+//!
+//! App::default()
+//!     .add_plugin(AxumPlugin)
+//!     .add_plugin(OpenApiPlugin::new("/openapi.json").title("My API"))
+//!     .add_systems(Route("/"), (get(my_handler).documented(), post(my_handler).documented()));
+//!
+//! async fn my_handler() {}
+//! ```
+
+use std::{borrow::Cow, collections::BTreeMap};
+
+use axum::{Json, routing::MethodFilter};
+use valigate::schema::{Schema, SchemaType};
+use wok::{
+    plugin::Plugin,
+    prelude::{ConfigureWorld, In, Res, Resource, WokUnknownError},
+};
+
+use crate::{Route, get};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct OpenApi {
+    pub openapi: &'static str,
+    pub info: Info,
+    pub paths: BTreeMap<String, PathItem>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Info {
+    pub title: Cow<'static, str>,
+    pub version: Cow<'static, str>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct PathItem {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Parameter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub get: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub put: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delete: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Operation>,
+}
+
+impl PathItem {
+    /// Sets the operation of every method matched by the filter
+    pub fn set(&mut self, method: MethodFilter, operation: Operation) {
+        let slots = [
+            (MethodFilter::GET, &mut self.get),
+            (MethodFilter::PUT, &mut self.put),
+            (MethodFilter::POST, &mut self.post),
+            (MethodFilter::DELETE, &mut self.delete),
+            (MethodFilter::OPTIONS, &mut self.options),
+            (MethodFilter::HEAD, &mut self.head),
+            (MethodFilter::PATCH, &mut self.patch),
+            (MethodFilter::TRACE, &mut self.trace),
+        ];
+
+        for (filter, slot) in slots {
+            if filter == method {
+                *slot = Some(operation.clone());
+            }
+        }
+    }
+
+    pub(crate) fn operations_mut(&mut self) -> impl Iterator<Item = &mut Operation> {
+        [
+            &mut self.get,
+            &mut self.put,
+            &mut self.post,
+            &mut self.delete,
+            &mut self.options,
+            &mut self.head,
+            &mut self.patch,
+            &mut self.trace,
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    pub(crate) fn merge(&mut self, other: PathItem) {
+        let slots = [
+            (&mut self.get, other.get),
+            (&mut self.put, other.put),
+            (&mut self.post, other.post),
+            (&mut self.delete, other.delete),
+            (&mut self.options, other.options),
+            (&mut self.head, other.head),
+            (&mut self.patch, other.patch),
+            (&mut self.trace, other.trace),
+        ];
+
+        for (slot, operation) in slots {
+            if operation.is_some() {
+                *slot = operation;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<Cow<'static, str>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Parameter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<RequestBody>,
+    pub responses: BTreeMap<Cow<'static, str>, Response>,
+}
+
+impl Operation {
+    pub fn new<I: OperationInput, O: OperationOutput>() -> Self {
+        let mut operation = Self::default();
+        I::operation_input(&mut operation);
+        O::operation_output(&mut operation);
+
+        operation
+    }
+
+    /// Placeholder for routes that are not `documented`
+    pub fn undocumented() -> Self {
+        let mut operation = Self::default();
+        operation.response("default", Response::new("Undocumented"));
+
+        operation
+    }
+
+    /// Adds a response unless one is already documented for the status
+    pub fn response(&mut self, status: impl Into<Cow<'static, str>>, response: Response) {
+        self.responses.entry(status.into()).or_insert(response);
+    }
+
+    pub fn body(&mut self, content_type: &'static str, schema: Schema) {
+        let body = self.request_body.get_or_insert_with(|| RequestBody {
+            required: true,
+            content: BTreeMap::new(),
+        });
+
+        body.content
+            .insert(content_type.into(), MediaType { schema });
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterIn {
+    Path,
+    Query,
+    Header,
+    Cookie,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Parameter {
+    pub name: Cow<'static, str>,
+    #[serde(rename = "in")]
+    pub location: ParameterIn,
+    pub required: bool,
+    pub schema: Schema,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RequestBody {
+    pub required: bool,
+    pub content: BTreeMap<Cow<'static, str>, MediaType>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MediaType {
+    pub schema: Schema,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Response {
+    pub description: Cow<'static, str>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub content: BTreeMap<Cow<'static, str>, MediaType>,
+}
+
+impl Response {
+    pub fn new(description: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            description: description.into(),
+            content: BTreeMap::new(),
+        }
+    }
+
+    pub fn content(mut self, content_type: &'static str, schema: Schema) -> Self {
+        self.content
+            .insert(content_type.into(), MediaType { schema });
+        self
+    }
+}
+
+/// Documents what a route extracts from the request
+pub trait OperationInput {
+    fn operation_input(_operation: &mut Operation) {}
+}
+
+/// Documents the responses of a route
+pub trait OperationOutput {
+    fn operation_output(operation: &mut Operation) {
+        operation.response("200", Response::new("OK"));
+    }
+}
+
+/// Titled schema for types only known by name, such as serde bodies
+fn named<T>(schema: Schema) -> Schema {
+    let name = std::any::type_name::<T>();
+    let name = name
+        .split('<')
+        .next()
+        .and_then(|path| path.rsplit("::").next())
+        .unwrap_or(name);
+
+    schema.with_title(name)
+}
+
+impl OperationInput for () {}
+impl<T: OperationInput + Send> OperationInput for In<T> {
+    fn operation_input(operation: &mut Operation) {
+        T::operation_input(operation);
+    }
+}
+
+impl<T: OperationInput> OperationInput for Option<T> {
+    fn operation_input(operation: &mut Operation) {
+        T::operation_input(operation);
+        if let Some(body) = operation.request_body.as_mut() {
+            body.required = false;
+        }
+    }
+}
+
+// Path params are read from the route path
+impl<T> OperationInput for axum::extract::Path<T> {}
+impl<T> OperationInput for axum::extract::Query<T> {}
+impl<T> OperationInput for axum::extract::State<T> {}
+impl<T> OperationInput for axum::Extension<T> {}
+impl OperationInput for axum::http::HeaderMap {}
+impl OperationInput for axum::http::Method {}
+impl OperationInput for axum::http::Uri {}
+impl OperationInput for axum::extract::Request {}
+
+impl OperationInput for String {
+    fn operation_input(operation: &mut Operation) {
+        operation.body("text/plain", Schema::new(SchemaType::String));
+    }
+}
+
+impl OperationInput for axum::body::Bytes {
+    fn operation_input(operation: &mut Operation) {
+        operation.body("application/octet-stream", Schema::any());
+    }
+}
+
+impl<T> OperationInput for Json<T> {
+    fn operation_input(operation: &mut Operation) {
+        operation.body("application/json", named::<T>(Schema::any()));
+    }
+}
+
+impl<T> OperationInput for axum::Form<T> {
+    fn operation_input(operation: &mut Operation) {
+        operation.body(
+            "application/x-www-form-urlencoded",
+            named::<T>(Schema::any()),
+        );
+    }
+}
+
+fn invalid_input(operation: &mut Operation, description: &'static str) {
+    operation.response(
+        "400",
+        Response::new(description).content(
+            "application/problem+json",
+            crate::problem::Problem::schema(),
+        ),
+    );
+}
+
+/// One parameter per property of an object schema
+fn object_parameters(operation: &mut Operation, location: ParameterIn, schema: Schema) {
+    for (name, property) in schema.properties {
+        let required = schema.required.contains(&name);
+        operation.parameters.push(Parameter {
+            name,
+            location,
+            required,
+            schema: property,
+        });
+    }
+}
+
+impl<T: valigate::Valid> OperationInput for crate::extract::JsonG<T> {
+    fn operation_input(operation: &mut Operation) {
+        operation.body("application/json", named::<T>(T::schema()));
+        invalid_input(operation, "Invalid body");
+    }
+}
+
+impl<T: valigate::Valid> OperationInput for crate::extract::QueryG<T> {
+    fn operation_input(operation: &mut Operation) {
+        object_parameters(operation, ParameterIn::Query, T::schema());
+        invalid_input(operation, "Invalid query");
+    }
+}
+
+// Path params are read from the route path
+impl<T: valigate::Valid> OperationInput for crate::extract::PathG<T> {
+    fn operation_input(operation: &mut Operation) {
+        invalid_input(operation, "Invalid path");
+    }
+}
+
+impl<T: valigate::Valid> OperationInput for crate::extract::FormG<T> {
+    fn operation_input(operation: &mut Operation) {
+        operation.body("application/x-www-form-urlencoded", named::<T>(T::schema()));
+        invalid_input(operation, "Invalid form");
+    }
+}
+
+impl<T: valigate::Valid> OperationInput for crate::extract::HeaderG<T> {
+    fn operation_input(operation: &mut Operation) {
+        object_parameters(operation, ParameterIn::Header, T::schema());
+        invalid_input(operation, "Invalid headers");
+    }
+}
+
+impl<T: valigate::Valid> OperationInput for crate::extract::MultipartG<T> {
+    fn operation_input(operation: &mut Operation) {
+        operation.body("multipart/form-data", named::<T>(T::schema()));
+        invalid_input(operation, "Invalid form");
+    }
+}
+
+macro_rules! impl_operation_input {
+    ($($name:ident),*) => {
+        impl<$($name: OperationInput),*> OperationInput for ($($name,)*) {
+            fn operation_input(operation: &mut Operation) {
+                $($name::operation_input(operation);)*
+            }
+        }
+    };
+}
+
+impl_operation_input!(T1);
+impl_operation_input!(T1, T2);
+impl_operation_input!(T1, T2, T3);
+impl_operation_input!(T1, T2, T3, T4);
+impl_operation_input!(T1, T2, T3, T4, T5);
+impl_operation_input!(T1, T2, T3, T4, T5, T6);
+impl_operation_input!(T1, T2, T3, T4, T5, T6, T7);
+impl_operation_input!(T1, T2, T3, T4, T5, T6, T7, T8);
+
+impl OperationOutput for () {}
+impl OperationOutput for axum::response::Response {}
+
+impl OperationOutput for &'static str {
+    fn operation_output(operation: &mut Operation) {
+        operation.response(
+            "200",
+            Response::new("OK").content("text/plain", Schema::new(SchemaType::String)),
+        );
+    }
+}
+
+impl OperationOutput for String {
+    fn operation_output(operation: &mut Operation) {
+        <&'static str>::operation_output(operation);
+    }
+}
+
+impl<T> OperationOutput for axum::response::Html<T> {
+    fn operation_output(operation: &mut Operation) {
+        operation.response(
+            "200",
+            Response::new("OK").content("text/html", Schema::new(SchemaType::String)),
+        );
+    }
+}
+
+impl<T> OperationOutput for Json<T> {
+    fn operation_output(operation: &mut Operation) {
+        operation.response(
+            "200",
+            Response::new("OK").content("application/json", named::<T>(Schema::any())),
+        );
+    }
+}
+
+/// The status is only known at runtime
+impl OperationOutput for axum::http::StatusCode {
+    fn operation_output(operation: &mut Operation) {
+        operation.response("default", Response::new("Status without body"));
+    }
+}
+
+/// The status is only known at runtime
+impl OperationOutput for crate::problem::Problem {
+    fn operation_output(operation: &mut Operation) {
+        operation.response(
+            "default",
+            Response::new("Problem").content(
+                "application/problem+json",
+                crate::problem::Problem::schema(),
+            ),
+        );
+    }
+}
+
+impl OperationOutput for WokUnknownError {
+    fn operation_output(operation: &mut Operation) {
+        operation.response("default", Response::new("Unexpected error"));
+    }
+}
+
+impl<T: OperationOutput, E: OperationOutput> OperationOutput for Result<T, E> {
+    fn operation_output(operation: &mut Operation) {
+        T::operation_output(operation);
+        E::operation_output(operation);
+    }
+}
+
+impl<T: OperationOutput> OperationOutput for (axum::http::StatusCode, T) {
+    fn operation_output(operation: &mut Operation) {
+        T::operation_output(operation);
+    }
+}
+
+impl<T: OperationOutput> OperationOutput for crate::response::Created<T> {
+    fn operation_output(operation: &mut Operation) {
+        let mut created = Operation::default();
+        T::operation_output(&mut created);
+
+        for (status, mut response) in created.responses {
+            if status == "200" {
+                response.description = "Created".into();
+                operation.response("201", response);
+            } else {
+                operation.response(status, response);
+            }
+        }
+
+        operation.response("400", Response::new("Bad request"));
+        operation.response("409", Response::new("Conflict"));
+    }
+}
+
+impl<T: OperationOutput> OperationOutput for crate::response::Maybe<T> {
+    fn operation_output(operation: &mut Operation) {
+        T::operation_output(operation);
+        operation.response("404", Response::new("Not found"));
+    }
+}
+
+impl<T: OperationOutput> OperationOutput for crate::response::Permitted<T> {
+    fn operation_output(operation: &mut Operation) {
+        T::operation_output(operation);
+        operation.response("401", Response::new("Unauthorized"));
+        operation.response("403", Response::new("Forbidden"));
+    }
+}
+
+impl<T: OperationOutput> OperationOutput for crate::response::Validated<T> {
+    fn operation_output(operation: &mut Operation) {
+        T::operation_output(operation);
+        operation.response("400", Response::new("Bad request"));
+    }
+}
+
+impl OperationOutput for crate::response::Deleted {
+    fn operation_output(operation: &mut Operation) {
+        operation.response("204", Response::new("Deleted"));
+    }
+}
+
+impl<S> OperationOutput for crate::stream::Sse<S> {
+    fn operation_output(operation: &mut Operation) {
+        operation.response(
+            "200",
+            Response::new("Event stream")
+                .content("text/event-stream", Schema::new(SchemaType::String)),
+        );
+    }
+}
+
+impl<S> OperationOutput for crate::stream::Chunked<S> {
+    fn operation_output(operation: &mut Operation) {
+        operation.response(
+            "200",
+            Response::new("Chunked body").content("application/octet-stream", Schema::any()),
+        );
+    }
+}
+
+/// Docs of every registered route, keyed by their full path
+#[derive(Default, Resource)]
+#[resource(mutable = true)]
+pub struct ApiDocs {
+    paths: BTreeMap<String, PathItem>,
+    prefixes: Vec<String>,
+}
+
+impl ApiDocs {
+    pub fn add(&mut self, path: &str, item: PathItem) {
+        let mut full_path: String = self.prefixes.concat();
+        full_path.push_str(path);
+
+        self.paths.entry(full_path).or_default().merge(item);
+    }
+
+    pub(crate) fn push_prefix(&mut self, prefix: &str) {
+        self.prefixes.push(prefix.trim_end_matches('/').to_string());
+    }
+
+    pub(crate) fn pop_prefix(&mut self) {
+        self.prefixes.pop();
+    }
+
+    pub fn document(&self, info: Info) -> OpenApi {
+        let paths = self
+            .paths
+            .iter()
+            .map(|(path, item)| {
+                let mut item = item.clone();
+                item.parameters = path_params(path);
+
+                (path.clone(), item)
+            })
+            .collect();
+
+        OpenApi {
+            openapi: "3.1.0",
+            info,
+            paths,
+        }
+    }
+}
+
+fn path_params(path: &str) -> Vec<Parameter> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| Parameter {
+            name: name.trim_start_matches('*').to_string().into(),
+            location: ParameterIn::Path,
+            required: true,
+            schema: Schema::new(SchemaType::String),
+        })
+        .collect()
+}
+
+#[derive(Resource, Clone)]
+struct OpenApiInfo(Info);
+
+/// Serves the OpenAPI document of all routes at `path`
+/// Routes added after this plugin are included too, the document is built on request
+pub struct OpenApiPlugin {
+    path: &'static str,
+    info: Info,
+}
+
+impl OpenApiPlugin {
+    pub fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            info: Info {
+                title: "API".into(),
+                version: "0.1.0".into(),
+            },
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<Cow<'static, str>>) -> Self {
+        self.info.title = title.into();
+        self
+    }
+
+    pub fn version(mut self, version: impl Into<Cow<'static, str>>) -> Self {
+        self.info.version = version.into();
+        self
+    }
+}
+
+impl Plugin for OpenApiPlugin {
+    fn setup(self, app: &mut wok::prelude::App) {
+        async fn serve_document(
+            docs: Res<'_, ApiDocs>,
+            info: Res<'_, OpenApiInfo>,
+        ) -> Json<OpenApi> {
+            Json(docs.document(info.0.clone()))
+        }
+
+        app.insert_resource(OpenApiInfo(self.info))
+            .add_systems(Route(self.path), get(serve_document).documented());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(dead_code)]
+    use super::*;
+
+    #[derive(valigate::Valid)]
+    #[gate(gate = (valigate::gates::MinLen(2), valigate::gates::MaxLen(20)))]
+    struct Name(String);
+
+    #[derive(valigate::Valid)]
+    struct Person {
+        name: Name,
+        nickname: Option<Name>,
+    }
+
+    #[test]
+    fn documents_gates_of_json_bodies() {
+        let operation = Operation::new::<In<crate::extract::JsonG<Person>>, Json<()>>();
+
+        let body = operation.request_body.expect("a body");
+        let schema = &body.content["application/json"].schema;
+        let name = &schema.properties["name"];
+
+        assert_eq!(name.ty, Some(SchemaType::String));
+        assert_eq!(name.min_length, Some(2));
+        assert_eq!(name.max_length, Some(20));
+        assert_eq!(schema.required, vec![Cow::Borrowed("name")]);
+        assert!(operation.responses.contains_key("200"));
+    }
+
+    #[derive(valigate::Valid, serde::Deserialize)]
+    #[gate(serde = true)]
+    struct Renamed {
+        #[serde(rename = "fullName")]
+        full_name: String,
+    }
+
+    #[test]
+    fn names_properties_and_errors_like_serde() {
+        use valigate::Valid;
+
+        let schema = Renamed::schema();
+        assert!(schema.properties.contains_key("fullName"));
+        assert_eq!(schema.required, vec![Cow::Borrowed("fullName")]);
+
+        let input = serde_urlencoded::from_str::<<Renamed as Valid>::In>("").unwrap();
+        let Err(err) = Renamed::parse(input) else {
+            panic!("fullName is missing");
+        };
+        let problem = crate::problem::Problem::validation(&err);
+        assert_eq!(problem.errors[0].pointer, "/fullName");
+    }
+
+    #[test]
+    fn collects_nested_paths_with_params() {
+        let mut docs = ApiDocs::default();
+        docs.push_prefix("/api");
+        docs.add("/persons/{id}", PathItem::default());
+        docs.pop_prefix();
+
+        let document = docs.document(Info {
+            title: "test".into(),
+            version: "1".into(),
+        });
+
+        let item = &document.paths["/api/persons/{id}"];
+        assert_eq!(item.parameters[0].name, "id");
+    }
+}
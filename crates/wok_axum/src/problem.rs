@@ -0,0 +1,260 @@
+//! RFC 9457 `application/problem+json` error responses
+//! Rejections and system errors respond with a `Problem`, rendered by the `ProblemFormat`
+//! resource when the router is served. Server errors get the correlation ID of the request
+//! span their error was logged in
+//! ```rust
+//! use wok::prelude::*;
+//! use wok_axum::{AxumPlugin, problem::{Problem, ProblemFormat}};
+//!
+//! App::default()
+//!     .add_plugin(AxumPlugin)
+//!     // Plain text errors instead of problem+json
+//!     .insert_resource(ProblemFormat::new(|problem: Problem| {
+//!         use axum::response::IntoResponse;
+//!         (problem.status, problem.title).into_response()
+//!     }));
+//! ```
+
+use std::{
+    borrow::Cow,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use axum::{
+    extract::Request,
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::Instrument;
+use valigate::schema::{Schema, SchemaType};
+use wok::prelude::Resource;
+
+/// Problem details of an error response
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub ty: Cow<'static, str>,
+    pub title: Cow<'static, str>,
+    #[serde(serialize_with = "serialize_status")]
+    pub status: StatusCode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    /// Invalid fields of the request
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<valigate::PointedError>,
+    /// ID of the request span, set on server errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+}
+
+fn serialize_status<S: serde::Serializer>(
+    status: &StatusCode,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u16(status.as_u16())
+}
+
+impl Problem {
+    /// Problem of `about:blank` type, titled with the status reason
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            ty: Cow::Borrowed("about:blank"),
+            title: Cow::Borrowed(status.canonical_reason().unwrap_or("Unknown error")),
+            status,
+            detail: None,
+            instance: None,
+            errors: vec![],
+            correlation_id: None,
+        }
+    }
+
+    pub fn with_type(mut self, ty: impl Into<Cow<'static, str>>) -> Self {
+        self.ty = ty.into();
+        self
+    }
+
+    pub fn with_title(mut self, title: impl Into<Cow<'static, str>>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// `400 Bad Request` listing the invalid fields of the input
+    pub fn validation(error: &valigate::Error) -> Self {
+        let mut problem = Problem::new(StatusCode::BAD_REQUEST).with_title("Invalid input");
+        problem.errors = error.flatten();
+
+        problem
+    }
+
+    /// OpenAPI schema of the problem+json body
+    pub fn schema() -> Schema {
+        let mut error = Schema::object();
+        error.property("pointer", Schema::new(SchemaType::String), true);
+        error.property("message", Schema::new(SchemaType::String), true);
+
+        let mut schema = Schema::object().with_title("Problem");
+        schema.property("type", Schema::new(SchemaType::String), true);
+        schema.property("title", Schema::new(SchemaType::String), true);
+        schema.property("status", Schema::new(SchemaType::Integer), true);
+        schema.property("detail", Schema::new(SchemaType::String), false);
+        schema.property("instance", Schema::new(SchemaType::String), false);
+        schema.property("errors", Schema::array(error), false);
+        schema.property("correlation_id", Schema::new(SchemaType::String), false);
+
+        schema
+    }
+
+    fn render(&self) -> Response {
+        let mut response = (self.status, axum::Json(self)).into_response();
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+
+        response
+    }
+}
+
+/// Renders the problem as problem+json, `ProblemFormat` replaces it once served
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let mut response = self.render();
+        response.extensions_mut().insert(self);
+
+        response
+    }
+}
+
+pub trait FormatProblem: Send + Sync + 'static {
+    fn format(&self, problem: Problem) -> Response;
+}
+
+impl<F: Fn(Problem) -> Response + Send + Sync + 'static> FormatProblem for F {
+    fn format(&self, problem: Problem) -> Response {
+        self(problem)
+    }
+}
+
+/// RFC 9457 problem+json, the default format
+pub struct ProblemJson;
+
+impl FormatProblem for ProblemJson {
+    fn format(&self, problem: Problem) -> Response {
+        problem.render()
+    }
+}
+
+/// Renders every `Problem` response of the served routers
+#[derive(Resource, Clone)]
+pub struct ProblemFormat(Arc<dyn FormatProblem>);
+
+impl ProblemFormat {
+    pub fn new(format: impl FormatProblem) -> Self {
+        Self(Arc::new(format))
+    }
+}
+
+impl Default for ProblemFormat {
+    fn default() -> Self {
+        Self::new(ProblemJson)
+    }
+}
+
+/// Correlation ID of the request, the `correlation_id` field of its `request` span
+#[derive(Debug, Clone)]
+pub struct CorrelationId(pub Arc<str>);
+
+impl CorrelationId {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        static START: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+
+        // unique within the process, and across restarts thanks to the start time
+        let start = START.get_or_init(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default()
+        });
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+
+        Self(format!("{start:x}-{n:x}").into())
+    }
+}
+
+/// Wraps every served route: opens the request span and renders problems with the format
+pub(crate) async fn problem_layer(format: ProblemFormat, mut req: Request, next: Next) -> Response {
+    let id = CorrelationId::next();
+    req.extensions_mut().insert(id.clone());
+
+    let span = tracing::info_span!(
+        "request",
+        correlation_id = %id.0,
+        method = %req.method(),
+        uri = %req.uri(),
+    );
+
+    let mut response = next.run(req).instrument(span).await;
+    let Some(mut problem) = response.extensions_mut().remove::<Problem>() else {
+        return response;
+    };
+
+    if problem.status.is_server_error() {
+        problem.correlation_id = Some(id.0.to_string());
+    }
+
+    let mut formatted = format.0.format(problem);
+    // keep headers such as `Retry-After`, the format sets the body ones
+    for (name, value) in response.headers() {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            formatted
+                .headers_mut()
+                .entry(name)
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("too short")]
+    struct TooShort;
+
+    #[test]
+    fn flattens_validation_errors_into_pointers() {
+        let mut tags = valigate::MapErrors::default();
+        tags.insert_index(1, valigate::FieldErrors::from_one(TooShort).into());
+
+        let mut root = valigate::MapErrors::default();
+        root.insert_field("tags", tags.into());
+        root.insert_field("a/b", valigate::FieldErrors::from_one(TooShort).into());
+
+        let problem = Problem::validation(&root.into());
+        let pointers: Vec<_> = problem.errors.iter().map(|e| e.pointer.as_str()).collect();
+
+        assert_eq!(problem.status, StatusCode::BAD_REQUEST);
+        assert_eq!(pointers, ["/a~1b", "/tags/1"]);
+        assert_eq!(problem.errors[0].message, "too short");
+    }
+}
@@ -0,0 +1,570 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use wok::{
+    plugin::Plugin,
+    prelude::{ConfigureApp, ConfigureWorld, Param, Res, ResMut, Resource, WokUnknownError},
+    remote_gateway::{RemoteWorldPorts, RemoteWorldRef},
+};
+
+use crate::{
+    Router, RouterLayers, RouterRoot,
+    limit::{MissingRateLimit, RateLimits, RatePolicies},
+    observe::Readiness,
+    problem::{ProblemFormat, problem_layer},
+};
+
+#[derive(Debug, serde::Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Addr {
+    // try to deserialize plain socket addr first
+    Plain(std::net::SocketAddr),
+    // then allow custom hosts
+    Unresolved(String),
+    HostPort(String, u16),
+    /// `{ unix = "/run/app.sock" }`, the socket file must not exist yet
+    Unix {
+        unix: PathBuf,
+    },
+}
+
+/// Each address is bound by its own listener, a host binds the first of its resolved addresses
+/// that is available
+#[derive(wok::prelude::Resource, serde::Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SocketAddrs {
+    Single(Addr),
+    Multiple(Vec<Addr>),
+}
+
+impl SocketAddrs {
+    fn as_slice(&self) -> &[Addr] {
+        match self {
+            SocketAddrs::Single(addr) => std::slice::from_ref(addr),
+            SocketAddrs::Multiple(addrs) => addrs,
+        }
+    }
+}
+
+/// PEM certificate chain and private key, terminates TLS with the `rustls` feature
+/// As a resource, it applies to the main `SocketAddrs` listener
+#[derive(wok::prelude::Resource, serde::Deserialize, Debug, Clone)]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+}
+
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct ListenerConfig {
+    pub addrs: SocketAddrs,
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+}
+
+/// Named listeners served next to the main one, each with the routes of its
+/// `AxumListenerPlugin`
+/// ```toml
+/// [listeners.admin]
+/// addrs = "127.0.0.1:9000"
+/// tls = { cert = "admin.pem", key = "admin.key" }
+/// ```
+#[derive(wok::prelude::Resource, serde::Deserialize, Debug, Clone, Default)]
+#[serde(transparent)]
+pub struct Listeners(pub HashMap<String, ListenerConfig>);
+
+/// Routers of the named listeners
+#[derive(Resource, Default)]
+#[resource(mutable = true)]
+pub struct ListenerRouters(HashMap<&'static str, Router>);
+
+/// Serves the routes of the plugin on the named listener instead of the main one
+/// Configure its addresses in the `Listeners` resource
+/// ```rust
+/// use wok::prelude::*;
+/// use wok_axum::{AxumPlugin, AxumListenerPlugin, Route, get};
+///
+/// App::default()
+///     .add_plugin(AxumPlugin)
+///     .add_plugin(AxumListenerPlugin("admin", AdminRoutes));
+///
+/// struct AdminRoutes;
+/// impl Plugin for AdminRoutes {
+///     fn setup(self, app: &mut wok::prelude::App) {
+///         app.add_systems(Route("/stats"), get(stats));
+///     }
+/// }
+///
+/// async fn stats() {}
+/// ```
+pub struct AxumListenerPlugin<P: Plugin>(pub &'static str, pub P);
+
+impl<P: Plugin> Plugin for AxumListenerPlugin<P> {
+    fn setup(self, app: &mut wok::prelude::App) {
+        let parent = app
+            .world_mut()
+            .state
+            .take_resource::<RouterRoot>()
+            .expect("missing previous `AxumPlugin`");
+        let parent_layers = app
+            .world_mut()
+            .state
+            .take_resource::<RouterLayers>()
+            .expect("missing previous `AxumPlugin`");
+
+        app.init_resource::<RouterRoot>()
+            .init_resource::<RouterLayers>();
+        app.add_plugin(self.1);
+
+        let child = app
+            .world_mut()
+            .state
+            .take_resource::<RouterRoot>()
+            .expect("Unexpected state, not recovering `RouterRoot` is not allowed");
+        let child_layers = app
+            .world_mut()
+            .state
+            .take_resource::<RouterLayers>()
+            .expect("Unexpected state, not recovering `RouterLayers` is not allowed");
+
+        let Some(child_router) = child.0 else {
+            panic!("Unexpected state, `RouterRoot` is not defined");
+        };
+        let child_router = child_layers.apply(child_router);
+
+        {
+            let mut routers = app.world_mut().get::<ResMut<'_, ListenerRouters>>();
+            let router = match routers.0.remove(self.0) {
+                Some(router) => router.merge(child_router),
+                None => child_router,
+            };
+            routers.0.insert(self.0, router);
+        }
+
+        app.insert_resource(parent).insert_resource(parent_layers);
+    }
+}
+
+/// Routers built by the plugins, finished when served
+#[derive(Param)]
+pub struct AxumRouters<'w> {
+    root: ResMut<'w, RouterRoot>,
+    layers: ResMut<'w, RouterLayers>,
+    listeners: ResMut<'w, ListenerRouters>,
+    format: Res<'w, ProblemFormat>,
+    readiness: Res<'w, Readiness>,
+    rate_policies: Res<'w, RatePolicies>,
+    rate_limits: Option<Res<'w, RateLimits>>,
+}
+
+impl AxumRouters<'_> {
+    /// Applies the scope layers and the world state to the main router, leaving
+    /// `RouterRoot` empty. Marks the app as started, main systems run after `Startup`
+    /// Fails when a rate limited route names a policy missing from `RateLimits`
+    pub(crate) fn finish_main(
+        &mut self,
+        world: RemoteWorldPorts,
+    ) -> Result<axum::Router, MissingRateLimit> {
+        self.rate_policies.check(self.rate_limits.as_deref())?;
+        self.readiness.start();
+
+        let router = self.root.0.take().expect("to have `AxumPlugin`");
+        let router = std::mem::take(&mut *self.layers).apply(router);
+
+        Ok(self.with_world(router, world))
+    }
+
+    /// Named listener routers, with the world state
+    fn finish_listeners(&mut self, world: RemoteWorldPorts) -> Vec<(&'static str, axum::Router)> {
+        std::mem::take(&mut self.listeners.0)
+            .into_iter()
+            .map(|(name, router)| (name, self.with_world(router, world.clone())))
+            .collect()
+    }
+
+    fn with_world(&self, router: Router, world: RemoteWorldPorts) -> axum::Router {
+        let format = ProblemFormat::clone(&self.format);
+
+        router
+            .layer(axum::middleware::from_fn(
+                move |req: axum::extract::Request, next: axum::middleware::Next| {
+                    problem_layer(format.clone(), req, next)
+                },
+            ))
+            // outermost, layer systems read the world from it
+            .layer(axum::Extension(world.clone()))
+            .with_state(world)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ServeError {
+    #[error("listener `{0}` has routes but no `Listeners` config")]
+    MissingListener(String),
+    #[error("TLS for `{0}` requires the `rustls` feature")]
+    TlsDisabled(String),
+    #[error("TLS is not supported on unix socket `{0}`")]
+    UnixTls(PathBuf),
+}
+
+/// Main runtime for wok_axum
+/// Requires the `AxumPlugin` & a `SocketAddrs` resource, with an optional `TlsConfig` for the
+/// main listener and `Listeners` for the routes of `AxumListenerPlugin`s
+pub async fn serve(
+    world: RemoteWorldRef<'_>,
+    addrs: Res<'_, SocketAddrs>,
+    tls: Option<Res<'_, TlsConfig>>,
+    listeners: Option<Res<'_, Listeners>>,
+    mut routers: AxumRouters<'_>,
+) -> Result<(), WokUnknownError> {
+    let world = world.upgrade().expect("the app to be active");
+
+    let main = ListenerConfig {
+        addrs: SocketAddrs::clone(&addrs),
+        tls: tls.map(|tls| TlsConfig::clone(&tls)),
+    };
+    let mut served = vec![("main".to_owned(), main, routers.finish_main(world.clone())?)];
+
+    for (name, router) in routers.finish_listeners(world) {
+        let config = listeners
+            .as_ref()
+            .and_then(|listeners| listeners.0.get(name))
+            .ok_or_else(|| ServeError::MissingListener(name.to_owned()))?;
+
+        served.push((name.to_owned(), config.clone(), router));
+    }
+
+    let mut bound = vec![];
+    for (name, config, router) in served {
+        for addr in config.addrs.as_slice() {
+            let listener = bind(&name, addr, config.tls.as_ref()).await?;
+            bound.push(listener.serve(router.clone()));
+        }
+    }
+
+    futures::future::try_join_all(bound).await?;
+
+    Ok(())
+}
+
+enum BoundListener {
+    Tcp(tokio::net::TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener),
+    #[cfg(feature = "rustls")]
+    Tls(std::net::TcpListener, axum_server::tls_rustls::RustlsConfig),
+}
+
+impl BoundListener {
+    async fn serve(self, router: axum::Router) -> std::io::Result<()> {
+        match self {
+            BoundListener::Tcp(listener) => axum::serve(listener, router).await,
+            #[cfg(unix)]
+            BoundListener::Unix(listener) => axum::serve(listener, router).await,
+            #[cfg(feature = "rustls")]
+            BoundListener::Tls(listener, config) => {
+                axum_server::from_tcp_rustls(listener, config)
+                    .serve(router.into_make_service())
+                    .await
+            }
+        }
+    }
+}
+
+async fn lookup_addr(addr: &Addr) -> std::io::Result<Vec<std::net::SocketAddr>> {
+    match addr {
+        Addr::Plain(addr) => Ok(vec![*addr]),
+        Addr::Unresolved(host) => tokio::net::lookup_host(host)
+            .await
+            .map(|addrs| addrs.collect()),
+        Addr::HostPort(host, port) => tokio::net::lookup_host((host.as_str(), *port))
+            .await
+            .map(|addrs| addrs.collect()),
+        Addr::Unix { .. } => Ok(vec![]),
+    }
+}
+
+async fn bind(
+    name: &str,
+    addr: &Addr,
+    tls: Option<&TlsConfig>,
+) -> Result<BoundListener, WokUnknownError> {
+    if let Addr::Unix { unix } = addr {
+        if tls.is_some() {
+            return Err(ServeError::UnixTls(unix.clone()).into());
+        }
+
+        #[cfg(unix)]
+        {
+            let listener = tokio::net::UnixListener::bind(unix)?;
+            tracing::info!(listener = name, "listening on: {}", unix.display());
+
+            return Ok(BoundListener::Unix(listener));
+        }
+
+        #[cfg(not(unix))]
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "unix sockets are not supported on this platform",
+        )
+        .into());
+    }
+
+    let addrs = lookup_addr(addr).await?;
+    let listener = tokio::net::TcpListener::bind(addrs.as_slice()).await?;
+    let local_addr = listener.local_addr()?;
+
+    let Some(tls) = tls else {
+        tracing::info!(listener = name, "listening on: http://{local_addr}");
+        return Ok(BoundListener::Tcp(listener));
+    };
+
+    #[cfg(feature = "rustls")]
+    {
+        let config =
+            axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert, &tls.key).await?;
+        tracing::info!(listener = name, "listening on: https://{local_addr}");
+
+        Ok(BoundListener::Tls(listener.into_std()?, config))
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    {
+        let _ = tls;
+        Err(ServeError::TlsDisabled(name.to_owned()).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use futures::future::Either;
+    use wok::{
+        prelude::{App, ConfigureApp, ConfigureWorld, Plugin, WokUnknownError},
+        setup::{RuntimeCfg, TokioRt},
+    };
+
+    use super::*;
+    use crate::{AxumPlugin, Route, get};
+
+    struct MainRoutes;
+    impl Plugin for MainRoutes {
+        fn setup(self, app: &mut App) {
+            app.add_systems(Route("/main"), get(hello));
+        }
+    }
+
+    struct AdminRoutes;
+    impl Plugin for AdminRoutes {
+        fn setup(self, app: &mut App) {
+            app.add_systems(Route("/stats"), get(hello));
+        }
+    }
+
+    async fn hello() -> &'static str {
+        "hello"
+    }
+
+    fn app() -> App {
+        App::default()
+            .add_plugin(AxumPlugin)
+            .add_plugin(MainRoutes)
+            .add_plugin(AxumListenerPlugin("admin", AdminRoutes))
+    }
+
+    fn free_addr() -> std::net::SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    /// Serves the app until `test` returns
+    async fn serving<Out>(app: App, test: impl Future<Output = Out>) -> Out {
+        let app = app.run(RuntimeCfg::default().with_async(TokioRt), serve);
+
+        match futures::future::select(Box::pin(app), Box::pin(test)).await {
+            Either::Left((result, _)) => panic!("the app stopped: {result:?}"),
+            Either::Right((out, _)) => out,
+        }
+    }
+
+    async fn serve_error(app: App) -> WokUnknownError {
+        app.run(RuntimeCfg::default().with_async(TokioRt), serve)
+            .await
+            .unwrap_err()
+    }
+
+    /// Status code of a `GET`, retried while the listener is not bound yet
+    async fn get_status<S, F>(connect: F, path: &'static str) -> u16
+    where
+        S: Read + Write,
+        F: Fn() -> std::io::Result<S> + Send + 'static,
+    {
+        tokio::task::spawn_blocking(move || {
+            let mut stream = (0..100)
+                .find_map(|_| {
+                    let stream = connect().ok();
+                    if stream.is_none() {
+                        std::thread::sleep(std::time::Duration::from_millis(20));
+                    }
+                    stream
+                })
+                .expect("the listener to be bound");
+
+            write!(
+                stream,
+                "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+            )
+            .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+
+            response[9..12].parse().unwrap()
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_named_listeners_on_their_own_addrs() {
+        let (main, admin) = (free_addr(), free_addr());
+        let listeners = Listeners(HashMap::from([(
+            "admin".to_owned(),
+            ListenerConfig {
+                addrs: SocketAddrs::Single(Addr::Plain(admin)),
+                tls: None,
+            },
+        )]));
+        let app = app()
+            .insert_resource(SocketAddrs::Single(Addr::Plain(main)))
+            .insert_resource(listeners);
+
+        let statuses = serving(app, async move {
+            let tcp = |addr| move || std::net::TcpStream::connect(addr);
+            [
+                get_status(tcp(main), "/main").await,
+                get_status(tcp(main), "/stats").await,
+                get_status(tcp(admin), "/stats").await,
+                get_status(tcp(admin), "/main").await,
+            ]
+        })
+        .await;
+
+        assert_eq!(statuses, [200, 404, 200, 404]);
+    }
+
+    #[tokio::test]
+    async fn fails_without_the_config_of_a_named_listener() {
+        let app = app().insert_resource(SocketAddrs::Single(Addr::Plain(free_addr())));
+
+        let err = serve_error(app).await;
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(ServeError::MissingListener(name)) if name == "admin"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn serves_unix_sockets() {
+        let path = std::env::temp_dir().join(format!("wok_axum_{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .add_plugin(MainRoutes)
+            .insert_resource(SocketAddrs::Single(Addr::Unix { unix: path.clone() }));
+
+        let socket = path.clone();
+        let status = serving(app, async move {
+            get_status(
+                move || std::os::unix::net::UnixStream::connect(&socket),
+                "/main",
+            )
+            .await
+        })
+        .await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(status, 200);
+    }
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/tls")
+            .join(name)
+    }
+
+    fn tls() -> TlsConfig {
+        TlsConfig {
+            cert: fixture("localhost.pem"),
+            key: fixture("localhost.key"),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejects_tls_on_unix_sockets() {
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .add_plugin(MainRoutes)
+            .insert_resource(SocketAddrs::Single(Addr::Unix {
+                unix: "wok_axum_tls.sock".into(),
+            }))
+            .insert_resource(tls());
+
+        let err = serve_error(app).await;
+        assert!(matches!(err.downcast_ref(), Some(ServeError::UnixTls(_))));
+    }
+
+    #[cfg(not(feature = "rustls"))]
+    #[tokio::test]
+    async fn tls_requires_the_rustls_feature() {
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .add_plugin(MainRoutes)
+            .insert_resource(SocketAddrs::Single(Addr::Plain(free_addr())))
+            .insert_resource(tls());
+
+        let err = serve_error(app).await;
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(ServeError::TlsDisabled(name)) if name == "main"
+        ));
+    }
+
+    #[cfg(feature = "rustls")]
+    #[tokio::test]
+    async fn serves_tls() {
+        use rustls::pki_types::{CertificateDer, pem::PemObject};
+
+        let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+
+        let mut roots = rustls::RootCertStore::empty();
+        roots
+            .add(CertificateDer::from_pem_file(fixture("ca.pem")).unwrap())
+            .unwrap();
+        let client = std::sync::Arc::new(
+            rustls::ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        );
+
+        let addr = free_addr();
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .add_plugin(MainRoutes)
+            .insert_resource(SocketAddrs::Single(Addr::Plain(addr)))
+            .insert_resource(tls());
+
+        let status = serving(app, async move {
+            let connect = move || {
+                let server_name = "localhost".try_into().unwrap();
+                let session = rustls::ClientConnection::new(client.clone(), server_name)
+                    .map_err(std::io::Error::other)?;
+                let tcp = std::net::TcpStream::connect(addr)?;
+                Ok(rustls::StreamOwned::new(session, tcp))
+            };
+            get_status(connect, "/main").await
+        })
+        .await;
+
+        assert_eq!(status, 200);
+    }
+}
@@ -0,0 +1,201 @@
+//! Streaming responses
+//! Systems return the stream, the world params are released as soon as the system resolves,
+//! not when the stream ends. Use `RemoteParams` with `RemoteWorldPorts::reserve_params` to
+//! access the world again for each item, registered with
+//! `RemoteParams::<Res<'static, Counter>>::init(app.world_mut())`
+//! ```rust
+//! use std::convert::Infallible;
+//!
+//! use futures::StreamExt;
+//! use wok::{prelude::*, remote_gateway::{RemoteParams, RemoteWorldRef}};
+//! use wok_axum::stream::{Event, Sse};
+//!
+//! #[derive(Resource)]
+//! struct Counter(usize);
+//!
+//! async fn counter_events(
+//!     world: RemoteWorldRef<'_>,
+//!     counter: Res<'_, RemoteParams<Res<'static, Counter>>>,
+//! ) -> Sse<impl futures::Stream<Item = Result<Event, Infallible>> + Send + use<>> {
+//!     let world = world.upgrade().expect("the app to be active");
+//!     let counter = *counter;
+//!
+//!     let events = futures::stream::repeat(()).take(3).then(move |_| {
+//!         let world = world.clone();
+//!         async move {
+//!             let mut permit = world.reserve_params(counter).await;
+//!             let count = permit.get().0;
+//!
+//!             Ok(Event::default().data(count.to_string()))
+//!         }
+//!     });
+//!
+//!     Sse::new(events)
+//! }
+//! ```
+
+use axum::{
+    body::{Body, Bytes},
+    response::{IntoResponse, Response, sse::KeepAlive},
+};
+use futures::{Stream, StreamExt};
+use wok::prelude::WokUnknownError;
+
+pub use axum::response::sse::Event;
+
+#[track_caller]
+fn log_error<E: Into<WokUnknownError> + 'static>(err: E) -> std::io::Error {
+    let err = WokUnknownError::convert(err);
+    tracing::error!(%err);
+
+    std::io::Error::other(err.to_string())
+}
+
+/// Server-Sent Events response, an error item is logged and ends the stream
+pub struct Sse<S> {
+    stream: S,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl<S> Sse<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            keep_alive: None,
+        }
+    }
+
+    pub fn keep_alive(mut self, keep_alive: KeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+}
+
+impl<S, E> IntoResponse for Sse<S>
+where
+    S: Stream<Item = Result<Event, E>> + Send + 'static,
+    E: Into<WokUnknownError> + 'static,
+{
+    fn into_response(self) -> Response {
+        let stream = self.stream.map(|item| match item {
+            Ok(event) => Ok(event),
+            Err(err) => Err(log_error(err)),
+        });
+        let sse = axum::response::Sse::new(stream);
+
+        match self.keep_alive {
+            Some(keep_alive) => sse.keep_alive(keep_alive).into_response(),
+            None => sse.into_response(),
+        }
+    }
+}
+
+/// Chunked body response, an error item is logged and aborts the body
+pub struct Chunked<S>(pub S);
+
+impl<S, B, E> IntoResponse for Chunked<S>
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: Into<Bytes> + 'static,
+    E: Into<WokUnknownError> + 'static,
+{
+    fn into_response(self) -> Response {
+        let stream = self.0.map(|item| match item {
+            Ok(chunk) => Ok(chunk.into()),
+            Err(err) => Err(log_error(err)),
+        });
+        Body::from_stream(stream).into_response()
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::{convert::Infallible, time::Duration};
+
+    use wok::{
+        prelude::*,
+        remote_gateway::{RemoteParams, RemoteWorldRef},
+    };
+
+    use super::*;
+    use crate::{AxumPlugin, Route, get, testing::TestApp};
+
+    #[derive(Resource)]
+    #[resource(mutable = true)]
+    struct Count(usize);
+
+    type Reads = Res<'static, Count>;
+    type Writes = ResMut<'static, Count>;
+
+    /// Counts the request, then reads the count for each event
+    async fn events(
+        world: RemoteWorldRef<'_>,
+        mut count: ResMut<'_, Count>,
+        reads: Res<'_, RemoteParams<Reads>>,
+    ) -> Sse<impl Stream<Item = Result<Event, Infallible>> + Send + use<>> {
+        let world = world.upgrade().expect("the app to be active");
+        let reads = *reads;
+        count.0 += 1;
+
+        let events = futures::stream::iter(0..2).then(move |_| {
+            let world = world.clone();
+            async move {
+                let mut permit = world.reserve_params(reads).await;
+                Ok(Event::default().data(permit.get().0.to_string()))
+            }
+        });
+
+        Sse::new(events)
+    }
+
+    /// Counts each chunk
+    async fn download(
+        world: RemoteWorldRef<'_>,
+        _: Res<'_, Count>,
+        writes: Res<'_, RemoteParams<Writes>>,
+    ) -> Chunked<impl Stream<Item = Result<String, Infallible>> + Send + use<>> {
+        let world = world.upgrade().expect("the app to be active");
+        let writes = *writes;
+
+        Chunked(futures::stream::iter(0..2).then(move |_| {
+            let world = world.clone();
+            async move {
+                let mut permit = world.reserve_params(writes).await;
+                let mut count = permit.get();
+                count.0 += 1;
+
+                Ok(count.0.to_string())
+            }
+        }))
+    }
+
+    #[tokio::test]
+    async fn streams_after_releasing_the_system_params() {
+        let mut app = App::default()
+            .add_plugin(AxumPlugin)
+            .insert_resource(Count(0))
+            .add_systems(Route("/events"), get(events))
+            .add_systems(Route("/download"), get(download));
+        RemoteParams::<Writes>::init(app.world_mut());
+
+        TestApp::new(app)
+            .params::<Reads>()
+            .run(|client| async move {
+                let responses = async {
+                    let events = client.get("/events").await;
+                    let download = client.get("/download").await;
+
+                    (events.text().to_owned(), download.text().to_owned())
+                };
+                let (events, download) = tokio::time::timeout(Duration::from_secs(5), responses)
+                    .await
+                    .expect("the streams to reserve their params");
+
+                assert_eq!(events, "data: 1\n\ndata: 1\n\n");
+                assert_eq!(download, "23");
+                assert_eq!(client.params::<Reads>().await.get().0, 3);
+            })
+            .await
+            .unwrap();
+    }
+}
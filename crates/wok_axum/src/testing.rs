@@ -0,0 +1,278 @@
+//! In memory requests against an app, without binding a port
+//! ```rust
+//! use wok::prelude::*;
+//! use wok_axum::{AxumPlugin, Route, get, testing::TestApp};
+//!
+//! #[derive(Resource)]
+//! #[resource(mutable = true)]
+//! struct Visits(usize);
+//!
+//! async fn visit(mut visits: ResMut<'_, Visits>) -> &'static str {
+//!     visits.0 += 1;
+//!     "hello"
+//! }
+//!
+//! async fn counts_visits() {
+//!     let app = App::default()
+//!         .add_plugin(AxumPlugin)
+//!         .insert_resource(Visits(0))
+//!         .add_systems(Route("/"), get(visit));
+//!
+//!     TestApp::new(app)
+//!         .params::<Res<'static, Visits>>()
+//!         .run(|client| async move {
+//!             let res = client.get("/").await;
+//!             assert_eq!(res.status(), 200);
+//!             assert_eq!(res.text(), "hello");
+//!
+//!             let mut visits = client.params::<Res<'static, Visits>>().await;
+//!             assert_eq!(visits.get().0, 1);
+//!         })
+//!         .await
+//!         .unwrap();
+//! }
+//! ```
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Arc,
+};
+
+use axum::{
+    body::{Body, Bytes},
+    http::{HeaderMap, Method, Request, StatusCode, header},
+};
+use futures::channel::oneshot;
+use tower::ServiceExt;
+use wok::{
+    prelude::{App, BorrowMutParam, ConfigureWorld, ResMut, Resource, WokUnknownError},
+    remote_gateway::{ParamsPermit, RemoteParams, RemoteWorldPorts, RemoteWorldRef},
+    setup::{RuntimeCfg, TokioRt},
+};
+
+use crate::AxumRouters;
+
+type ParamsMap = HashMap<TypeId, Arc<dyn Any + Send + Sync>>;
+
+/// Runs an app for a test, see `TestApp::run`
+pub struct TestApp {
+    app: App,
+    params: ParamsMap,
+}
+
+impl TestApp {
+    /// The app requires the `AxumPlugin`, `SocketAddrs` is not needed
+    pub fn new(app: App) -> Self {
+        Self {
+            app,
+            params: HashMap::new(),
+        }
+    }
+
+    /// Registers `P` to be read with `TestClient::params` while the app runs
+    pub fn params<P: BorrowMutParam + 'static>(mut self) -> Self {
+        let world = self.app.world_mut();
+        RemoteParams::<P>::init(world);
+
+        let params = *world.get::<wok::prelude::Res<'_, RemoteParams<P>>>();
+        self.params.insert(TypeId::of::<P>(), Arc::new(params));
+
+        self
+    }
+
+    /// Runs the startup systems, then the test with a client of the finished router.
+    /// The app stops once the test returns, do not return the client from it.
+    /// Needs a Tokio runtime
+    pub async fn run<F, Fut, Out>(self, test: F) -> Result<Out, WokUnknownError>
+    where
+        F: FnOnce(TestClient) -> Fut,
+        Fut: Future<Output = Out>,
+    {
+        let (ready_tx, ready_rx) = oneshot::channel();
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        let app = self
+            .app
+            .insert_resource(TestHarness(Some((ready_tx, stop_rx))));
+        let app_fut = app.run(RuntimeCfg::default().with_async(TokioRt), test_main);
+
+        let params = Arc::new(self.params);
+        let test_fut = async move {
+            // Closed when startup fails, the app reports the error
+            let (router, world) = ready_rx.await.ok()?;
+            let client = TestClient {
+                router,
+                world,
+                params,
+            };
+
+            let out = test(client).await;
+            let _ = stop_tx.send(());
+
+            Some(out)
+        };
+
+        let (app_result, out) = futures::future::join(app_fut, test_fut).await;
+        app_result?;
+
+        out.ok_or_else(|| WokUnknownError::from_message("the test app stopped before the test"))
+    }
+}
+
+type Ready = oneshot::Sender<(axum::Router, RemoteWorldPorts)>;
+
+#[derive(Resource)]
+#[resource(mutable = true)]
+struct TestHarness(Option<(Ready, oneshot::Receiver<()>)>);
+
+async fn test_main(
+    world: RemoteWorldRef<'_>,
+    mut harness: ResMut<'_, TestHarness>,
+    mut routers: AxumRouters<'_>,
+) -> Result<(), WokUnknownError> {
+    let world = world.upgrade().expect("the app to be active");
+    let router = routers.finish_main(world.clone())?;
+
+    let (ready, stop) = harness.0.take().expect("the test app to run once");
+    if ready.send((router, world)).is_err() {
+        return Ok(());
+    }
+
+    let _ = stop.await;
+    Ok(())
+}
+
+/// Sends requests to the app router in memory
+pub struct TestClient {
+    router: axum::Router,
+    world: RemoteWorldPorts,
+    params: Arc<ParamsMap>,
+}
+
+impl TestClient {
+    pub async fn request(&self, request: Request<Body>) -> TestResponse {
+        let response = self
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("the router to be infallible");
+
+        let (parts, body) = response.into_parts();
+        let body = axum::body::to_bytes(body, usize::MAX)
+            .await
+            .expect("to read the response body");
+
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.send(Method::GET, uri, Body::empty(), None).await
+    }
+
+    pub async fn delete(&self, uri: &str) -> TestResponse {
+        self.send(Method::DELETE, uri, Body::empty(), None).await
+    }
+
+    pub async fn post_json<T: serde::Serialize>(&self, uri: &str, body: &T) -> TestResponse {
+        self.send_json(Method::POST, uri, body).await
+    }
+
+    pub async fn put_json<T: serde::Serialize>(&self, uri: &str, body: &T) -> TestResponse {
+        self.send_json(Method::PUT, uri, body).await
+    }
+
+    pub async fn patch_json<T: serde::Serialize>(&self, uri: &str, body: &T) -> TestResponse {
+        self.send_json(Method::PATCH, uri, body).await
+    }
+
+    async fn send_json<T: serde::Serialize>(
+        &self,
+        method: Method,
+        uri: &str,
+        body: &T,
+    ) -> TestResponse {
+        let body = serde_json::to_vec(body).expect("to serialize the request body");
+        self.send(method, uri, Body::from(body), Some("application/json"))
+            .await
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        body: Body,
+        content_type: Option<&str>,
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(content_type) = content_type {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+
+        self.request(request.body(body).expect("a valid request"))
+            .await
+    }
+
+    /// Finished router of the app, to serve it on a listener when a test needs a real
+    /// connection such as a WebSocket
+    pub fn router(&self) -> axum::Router {
+        self.router.clone()
+    }
+
+    /// World of the running app
+    pub fn world(&self) -> &RemoteWorldPorts {
+        &self.world
+    }
+
+    /// Reserves params registered with `TestApp::params`, release them before the next request
+    /// that needs them
+    pub async fn params<P: BorrowMutParam + 'static>(&self) -> ParamsPermit<'_, P> {
+        let params = self
+            .params
+            .get(&TypeId::of::<P>())
+            .and_then(|params| params.downcast_ref::<RemoteParams<P>>())
+            .copied()
+            .unwrap_or_else(|| {
+                panic!(
+                    "`{}` was not registered with `TestApp::params`",
+                    std::any::type_name::<P>()
+                )
+            });
+
+        self.world.reserve_params(params).await
+    }
+}
+
+/// Buffered response
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    pub fn text(&self) -> &str {
+        std::str::from_utf8(&self.body).expect("an utf-8 body")
+    }
+
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).expect("a json body")
+    }
+}
@@ -0,0 +1,383 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{State, WebSocketUpgrade, ws::WebSocket},
+    routing::MethodFilter,
+};
+use futures::{
+    SinkExt, StreamExt,
+    lock::Mutex,
+    stream::{SplitSink, SplitStream},
+};
+use wok::{
+    prelude::{BorrowMutParam, In, IntoSystem, ProtoTaskSystem, System, WokUnknownError},
+    remote_gateway::RemoteWorldPorts,
+};
+use wok_core::world::{WorldCenter, gateway::SystemEntry};
+
+use crate::{
+    ConfigureRoute, MethodRouterMut,
+    openapi::{Operation, PathItem, Response},
+};
+
+pub use axum::extract::ws::Message;
+
+/// Sending half of a socket, can be cloned and moved to other tasks
+#[derive(Clone)]
+pub struct WsSender(Arc<Mutex<SplitSink<WebSocket, Message>>>);
+
+impl WsSender {
+    pub async fn send(&self, message: impl Into<Message>) -> Result<(), axum::Error> {
+        self.0.lock().await.send(message.into()).await
+    }
+}
+
+enum Incoming {
+    Stream(SplitStream<WebSocket>),
+    Single(Option<Message>),
+}
+
+/// Input of a WebSocket route system
+pub struct WebSocketSession {
+    sender: WsSender,
+    incoming: Incoming,
+}
+
+impl WebSocketSession {
+    /// Next message from the client, `None` once the socket is closed.
+    /// Per message routes only receive the message that triggered the run
+    pub async fn recv(&mut self) -> Option<Result<Message, axum::Error>> {
+        match &mut self.incoming {
+            Incoming::Stream(stream) => stream.next().await,
+            Incoming::Single(message) => message.take().map(Ok),
+        }
+    }
+
+    pub async fn send(&self, message: impl Into<Message>) -> Result<(), axum::Error> {
+        self.sender.send(message).await
+    }
+
+    pub fn sender(&self) -> WsSender {
+        self.sender.clone()
+    }
+}
+
+pub trait WsOutput: Send + 'static {
+    fn into_result(self) -> Result<(), WokUnknownError>;
+}
+
+impl WsOutput for () {
+    fn into_result(self) -> Result<(), WokUnknownError> {
+        Ok(())
+    }
+}
+
+impl<E: Into<WokUnknownError> + Send + 'static> WsOutput for Result<(), E> {
+    #[track_caller]
+    fn into_result(self) -> Result<(), WokUnknownError> {
+        match self {
+            Ok(()) => Ok(()),
+            Err(err) => Err(WokUnknownError::convert(err)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+enum Reservation {
+    #[default]
+    PerMessage,
+    PerConnection,
+}
+
+pub struct WsRoute<S> {
+    system: S,
+    reservation: Reservation,
+}
+
+impl<S> WsRoute<S> {
+    /// Runs the system once per received message, params are only reserved while it runs.
+    /// A failed run is logged and the socket stays open for the next message
+    pub fn per_message(mut self) -> Self {
+        self.reservation = Reservation::PerMessage;
+        self
+    }
+
+    /// Runs the system once for the whole connection, params stay reserved until it returns
+    pub fn per_connection(mut self) -> Self {
+        self.reservation = Reservation::PerConnection;
+        self
+    }
+}
+
+/// WebSocket route, upgrades `GET` requests and runs the system with the socket
+/// By default the system runs once per message, use `.per_connection()` to hand it the
+/// whole socket
+/// ```rust
+/// use wok::prelude::*;
+/// use wok_axum::{AxumPlugin, Route, ws::{self, WebSocketSession}};
+///
+/// App::default()
+///     .add_plugin(AxumPlugin)
+///     .add_systems(Route("/echo"), ws::ws(echo));
+///
+/// async fn echo(In(mut session): In<WebSocketSession>) {
+///     if let Some(Ok(message)) = session.recv().await {
+///         let _ = session.send(message).await;
+///     }
+/// }
+/// ```
+pub fn ws<S, Marker>(system: S) -> WsRoute<S::System>
+where
+    S: IntoSystem<Marker>,
+    S::System: System<In = In<WebSocketSession>, Out: WsOutput>,
+{
+    WsRoute {
+        system: system.into_system(),
+        reservation: Reservation::default(),
+    }
+}
+
+impl<S> ConfigureRoute for WsRoute<S>
+where
+    S: ProtoTaskSystem<Param: BorrowMutParam>,
+    S: System<In = In<WebSocketSession>, Out: WsOutput>,
+{
+    fn cfg(self, router: &mut MethodRouterMut<'_>, world: &mut WorldCenter) {
+        let system = world.register_system(self.system);
+        let reservation = self.reservation;
+
+        let handler = move |State(state): State<RemoteWorldPorts>, upgrade: WebSocketUpgrade| {
+            let system = system.clone();
+            async move {
+                upgrade.on_upgrade(move |socket| async move {
+                    let result = match reservation {
+                        Reservation::PerMessage => run_per_message(&state, &system, socket).await,
+                        Reservation::PerConnection => {
+                            run_per_connection(&state, &system, socket).await
+                        }
+                    };
+
+                    if let Err(err) = result {
+                        tracing::error!(%err);
+                    }
+                })
+            }
+        };
+
+        router.on(MethodFilter::GET, handler);
+    }
+
+    fn docs(&self, item: &mut PathItem) {
+        let mut operation = Operation::default();
+        operation.response("101", Response::new("Switching to the WebSocket protocol"));
+
+        item.set(MethodFilter::GET, operation);
+    }
+}
+
+async fn run_per_connection<S>(
+    state: &RemoteWorldPorts,
+    system: &SystemEntry<S>,
+    socket: WebSocket,
+) -> Result<(), WokUnknownError>
+where
+    S: ProtoTaskSystem<Param: BorrowMutParam>,
+    S: System<In = In<WebSocketSession>, Out: WsOutput>,
+{
+    let (sink, stream) = socket.split();
+    let session = WebSocketSession {
+        sender: WsSender(Arc::new(Mutex::new(sink))),
+        incoming: Incoming::Stream(stream),
+    };
+
+    state
+        .reserver()
+        .reserve(system.entry_ref())
+        .await
+        .task()
+        .run(session)
+        .await
+        .into_result()
+}
+
+async fn run_per_message<S>(
+    state: &RemoteWorldPorts,
+    system: &SystemEntry<S>,
+    socket: WebSocket,
+) -> Result<(), WokUnknownError>
+where
+    S: ProtoTaskSystem<Param: BorrowMutParam>,
+    S: System<In = In<WebSocketSession>, Out: WsOutput>,
+{
+    let (sink, mut stream) = socket.split();
+    let sender = WsSender(Arc::new(Mutex::new(sink)));
+
+    while let Some(message) = stream.next().await {
+        let message = message?;
+        match message {
+            Message::Close(_) => break,
+            // Pongs are answered by axum
+            Message::Ping(_) | Message::Pong(_) => continue,
+            Message::Text(_) | Message::Binary(_) => {}
+        }
+
+        let session = WebSocketSession {
+            sender: sender.clone(),
+            incoming: Incoming::Single(Some(message)),
+        };
+
+        let result = state
+            .reserver()
+            .reserve(system.entry_ref())
+            .await
+            .task()
+            .run(session)
+            .await
+            .into_result();
+
+        if let Err(err) = result {
+            tracing::error!(%err);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::SocketAddr, pin::pin};
+
+    use futures::future::{Either, select};
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite};
+    use wok::prelude::*;
+
+    use super::*;
+    use crate::{
+        AxumPlugin, Route,
+        testing::{TestApp, TestClient},
+    };
+
+    type Client = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+    #[derive(Resource)]
+    #[resource(mutable = true)]
+    struct Count(usize);
+
+    async fn count_messages(
+        In(mut session): In<WebSocketSession>,
+        mut count: ResMut<'_, Count>,
+    ) -> Result<(), WokUnknownError> {
+        while let Some(message) = session.recv().await {
+            if let Message::Text(_) = message? {
+                count.0 += 1;
+                session.send(count.0.to_string()).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn echo_non_empty(In(mut session): In<WebSocketSession>) -> Result<(), WokUnknownError> {
+        while let Some(message) = session.recv().await {
+            if let Message::Text(text) = message? {
+                if text.is_empty() {
+                    return Err(WokUnknownError::from_message("empty message"));
+                }
+                session.send(Message::Text(text)).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the app with its router served on a bound port, until `test` returns
+    async fn serving<Out>(test: impl AsyncFnOnce(&TestClient, SocketAddr) -> Out) -> Out {
+        let app = App::default()
+            .add_plugin(AxumPlugin)
+            .insert_resource(Count(0))
+            .add_systems(Route("/message"), ws(count_messages))
+            .add_systems(Route("/connection"), ws(count_messages).per_connection())
+            .add_systems(Route("/echo"), ws(echo_non_empty));
+
+        TestApp::new(app)
+            .params::<Res<'static, Count>>()
+            .run(|client| async move {
+                let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+                let addr = listener.local_addr().unwrap();
+                let server = tokio::spawn(axum::serve(listener, client.router()).into_future());
+
+                let out = test(&client, addr).await;
+                server.abort();
+                out
+            })
+            .await
+            .unwrap()
+    }
+
+    async fn connect(addr: SocketAddr, path: &str) -> Client {
+        let (client, _) = tokio_tungstenite::connect_async(format!("ws://{addr}{path}"))
+            .await
+            .unwrap();
+        client
+    }
+
+    async fn send(socket: &mut Client, text: &str) -> String {
+        socket.send(tungstenite::Message::text(text)).await.unwrap();
+        let reply = socket.next().await.unwrap().unwrap();
+        reply.to_text().unwrap().to_owned()
+    }
+
+    async fn read_count(client: &TestClient) -> usize {
+        client.params::<Res<'static, Count>>().await.get().0
+    }
+
+    #[tokio::test]
+    async fn releases_params_between_messages() {
+        let (replies, count) = serving(async |client, addr| {
+            let mut socket = connect(addr, "/message").await;
+            let replies = [send(&mut socket, "a").await, send(&mut socket, "b").await];
+
+            // Reserved while the socket is still open
+            (replies, read_count(client).await)
+        })
+        .await;
+
+        assert_eq!(replies, ["1", "2"]);
+        assert_eq!(count, 2);
+    }
+
+    #[tokio::test]
+    async fn holds_params_for_the_connection() {
+        let (replies, count) = serving(async |client, addr| {
+            let mut socket = connect(addr, "/connection").await;
+            let mut replies = vec![send(&mut socket, "a").await, send(&mut socket, "b").await];
+
+            // The running system holds the count, so the read waits until the socket closes
+            let mut reading = pin!(read_count(client));
+            match select(reading.as_mut(), pin!(send(&mut socket, "c"))).await {
+                Either::Left(_) => panic!("the count was read while the connection held it"),
+                Either::Right((reply, _)) => replies.push(reply),
+            }
+
+            socket.close(None).await.unwrap();
+            (replies, reading.await)
+        })
+        .await;
+
+        assert_eq!(replies, ["1", "2", "3"]);
+        assert_eq!(count, 3);
+    }
+
+    #[tokio::test]
+    async fn keeps_the_socket_open_after_a_failed_message() {
+        let reply = serving(async |_, addr| {
+            let mut socket = connect(addr, "/echo").await;
+            socket.send(tungstenite::Message::text("")).await.unwrap();
+
+            send(&mut socket, "still open").await
+        })
+        .await;
+
+        assert_eq!(reply, "still open");
+    }
+}
@@ -3,6 +3,8 @@ mod router;
 mod schedule;
 #[cfg(feature = "db")]
 pub mod db;
+#[cfg(feature = "migrate")]
+pub mod migrate;
 
 pub use plugin::{ClapPlugin, clap_runtime};
 
@@ -0,0 +1,105 @@
+use wok::prelude::{ConfigureWorld, In, Res, Resource, WokUnknownError};
+use wok_db::migrate::{DbMigrate, Migrations};
+
+use crate::schedule::{Route, SubRoutes};
+
+/// Adds the `migrate up`, `migrate down` and `migrate status` commands, requires the
+/// `Migrations` resource, such as from a manual `MigratePlugin`
+/// ```rust,ignore
+/// App::default()
+///     .add_plugin(ClapPlugin::parser::<AppArgs>())
+///     .add_plugin(SqlxPoolPlugin::default())
+///     .add_plugin(MigratePlugin::<SqlDb>::new(migrations).manual())
+///     .add_plugin(MigrateCommandsPlugin::<SqlDb>::default());
+/// ```
+pub struct MigrateCommandsPlugin<Db> {
+    _marker: std::marker::PhantomData<fn(Db)>,
+}
+
+impl<Db> Default for MigrateCommandsPlugin<Db> {
+    fn default() -> Self {
+        MigrateCommandsPlugin {
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct UpArgs {
+    /// Last version to apply, every pending migration when absent
+    #[arg(long)]
+    to: Option<i64>,
+}
+
+#[derive(clap::Args)]
+struct DownArgs {
+    /// Amount of applied migrations to revert, the latest first
+    #[arg(long, default_value_t = 1)]
+    steps: usize,
+}
+
+#[derive(clap::Args)]
+struct StatusArgs {}
+
+async fn migrate_up<Db: DbMigrate + Resource + Sync>(
+    args: In<UpArgs>,
+    db: Res<'_, Db>,
+    migrations: Res<'_, Migrations>,
+) -> Result<(), WokUnknownError> {
+    let applied = migrations.up(&*db, args.0.to).await?;
+
+    if applied.is_empty() {
+        println!("Nothing to apply");
+    }
+    for migration in applied {
+        println!("Applied {} {}", migration.version, migration.name);
+    }
+
+    Ok(())
+}
+
+async fn migrate_down<Db: DbMigrate + Resource + Sync>(
+    args: In<DownArgs>,
+    db: Res<'_, Db>,
+    migrations: Res<'_, Migrations>,
+) -> Result<(), WokUnknownError> {
+    let reverted = migrations.down(&*db, args.0.steps).await?;
+
+    if reverted.is_empty() {
+        println!("Nothing to revert");
+    }
+    for migration in reverted {
+        println!("Reverted {} {}", migration.version, migration.name);
+    }
+
+    Ok(())
+}
+
+async fn migrate_status<Db: DbMigrate + Resource + Sync>(
+    _: In<StatusArgs>,
+    db: Res<'_, Db>,
+    migrations: Res<'_, Migrations>,
+) -> Result<(), WokUnknownError> {
+    let status = migrations.status(&*db).await?;
+
+    if status.is_empty() {
+        println!("<None>");
+    }
+    for status in status {
+        println!("{} {} {}", status.version, status.name, status.state);
+    }
+
+    Ok(())
+}
+
+impl<Db: DbMigrate + Resource + Sync> wok::prelude::Plugin for MigrateCommandsPlugin<Db> {
+    fn setup(self, app: &mut wok::prelude::App) {
+        app.add_systems(
+            Route("migrate"),
+            SubRoutes::default()
+                .add(Route("up"), migrate_up::<Db>)
+                .add(Route("down"), migrate_down::<Db>)
+                .add(Route("status"), migrate_status::<Db>),
+        );
+    }
+}
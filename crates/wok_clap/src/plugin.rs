@@ -40,10 +40,7 @@ pub async fn clap_runtime(
 
     let args = match command.try_get_matches() {
         Ok(args) => args,
-        Err(e) => {
-            eprintln!("{}", e);
-            return Ok(());
-        }
+        Err(e) => return clap_error(e),
     };
 
     let Some((route, args)) = select_route(main.as_deref(), &args, router.as_ref()) else {
@@ -56,13 +53,21 @@ pub async fn clap_runtime(
 
     match result {
         Ok(out) => out,
-        Err(e) => {
-            eprintln!("{}", e);
-            Ok(())
-        }
+        Err(e) => clap_error(e),
     }
 }
 
+/// Help and version are printed and succeed, other errors exit with the clap exit code
+fn clap_error(err: clap::Error) -> Result<(), WokUnknownError> {
+    if !err.use_stderr() {
+        err.print()?;
+        return Ok(());
+    }
+
+    let exit_code = u8::try_from(err.exit_code()).unwrap_or(2);
+    Err(WokUnknownError::new(err).with_exit_code(exit_code))
+}
+
 fn select_route<'a>(
     main: Option<&'a MainHandler>,
     args: &'a ArgMatches,
@@ -1,7 +1,10 @@
+use std::panic::Location;
+
 use clap::{ArgMatches, Args, FromArgMatches};
 use one_route::OneRoute;
 use wok::prelude::{BorrowMutParam, ConfigureWorld, ProtoSystem, ResMut};
 use wok_core::{
+    error::into_unknown,
     prelude::{
         BorrowTaskSystem, In, InRef, IntoBlockingSystem, IntoSystem, ProtoTaskSystem, Resource,
         System, WokUnknownError,
@@ -24,13 +27,15 @@ pub struct MainHandler(pub SystemId, pub ClapHandler);
 pub struct Main;
 impl ScheduleLabel for Main {}
 
-impl<Arg: FromArgMatches + Send + Sync + 'static, S, Marker> ScheduleConfigure<S, Marker> for Main
+impl<Arg: FromArgMatches + Send + Sync + 'static, S, E, Marker> ScheduleConfigure<S, Marker>
+    for Main
 where
     S: IntoSystem<Marker>,
     S: 'static,
-    S::System: System<In = In<Arg>, Out = Result<(), WokUnknownError>>
-        + ProtoTaskSystem<Param: BorrowMutParam>,
+    S::System: System<In = In<Arg>, Out = Result<(), E>> + ProtoTaskSystem<Param: BorrowMutParam>,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
 {
+    #[track_caller]
     fn add(self, world: &mut wok_core::world::World, system: S) {
         let system = make_route_handler(system);
 
@@ -39,15 +44,20 @@ where
     }
 }
 
-fn make_route_handler<Arg: FromArgMatches + Send + Sync + 'static, Marker>(
+#[track_caller]
+fn make_route_handler<Arg, E, Marker>(
     system: impl IntoSystem<
         Marker,
-        System: System<In = In<Arg>, Out = Result<(), WokUnknownError>>
-                    + ProtoSystem<Param: BorrowMutParam>,
+        System: System<In = In<Arg>, Out = Result<(), E>> + ProtoSystem<Param: BorrowMutParam>,
     >,
-) -> impl BorrowTaskSystem<In = HandlerIn, Out = HandlerOut> + ProtoSystem<Param: BorrowMutParam> {
+) -> impl BorrowTaskSystem<In = HandlerIn, Out = HandlerOut> + ProtoSystem<Param: BorrowMutParam>
+where
+    Arg: FromArgMatches + Send + Sync + 'static,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
+{
+    let location = Location::caller();
     (|matches: InRef<'_, ArgMatches>| Arg::from_arg_matches(&matches))
-        .try_then(system)
+        .try_then(system.map(move |In(result): In<Result<(), E>>| into_unknown(result, location)))
         .into_system()
 }
 
@@ -73,12 +83,12 @@ impl ScheduleLabel for Route {}
 
 #[doc(hidden)]
 pub struct SingleRoute;
-impl<Arg, S, Marker> ScheduleConfigure<S, (Arg, Marker, SingleRoute)> for Route
+impl<Arg, S, E, Marker> ScheduleConfigure<S, (Arg, fn() -> E, Marker, SingleRoute)> for Route
 where
     Arg: FromArgMatches + Args + Send + Sync + 'static,
     S: IntoSystem<Marker>,
-    S::System: System<In = In<Arg>, Out = Result<(), WokUnknownError>>
-        + ProtoSystem<Param: BorrowMutParam>,
+    S::System: System<In = In<Arg>, Out = Result<(), E>> + ProtoSystem<Param: BorrowMutParam>,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
 {
     fn add(self, world: &mut wok_core::world::World, system: S) {
         self.command().add(world, system);
@@ -98,12 +108,12 @@ where
 
 pub struct RouteCommand(pub CommandInfo);
 
-impl<Arg, S, Marker> ScheduleConfigure<S, (Arg, Marker, SingleRoute)> for RouteCommand
+impl<Arg, S, E, Marker> ScheduleConfigure<S, (Arg, fn() -> E, Marker, SingleRoute)> for RouteCommand
 where
     Arg: FromArgMatches + Args + Send + Sync + 'static,
     S: IntoSystem<Marker>,
-    S::System: System<In = In<Arg>, Out = Result<(), WokUnknownError>>
-        + ProtoSystem<Param: BorrowMutParam>,
+    S::System: System<In = In<Arg>, Out = Result<(), E>> + ProtoSystem<Param: BorrowMutParam>,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
 {
     fn add(self, world: &mut wok_core::world::World, system: S) {
         let route = OneRoute::new(system);
@@ -267,16 +277,17 @@ mod sub_routes {
     }
 
     impl<Routes: ConfigureRoutesSet> SubRoutes<Routes> {
-        pub fn add<Marker, Arg, S>(
+        pub fn add<Marker, Arg, E, S>(
             self,
             route: Route,
             system: S,
         ) -> SubRoutes<impl ConfigureRoutesSet<Cardinality = cardinality::OneOrMore>>
         where
             Arg: FromArgMatches + Args + Send + Sync + 'static,
+            E: Into<WokUnknownError> + Send + Sync + 'static,
             S: IntoSystem<
                     Marker,
-                    System: System<In = In<Arg>, Out = Result<(), WokUnknownError>>
+                    System: System<In = In<Arg>, Out = Result<(), E>>
                                 + ProtoSystem<Param: BorrowMutParam>,
                 >,
         {
@@ -318,11 +329,12 @@ mod one_route {
         _marker: std::marker::PhantomData<Marker>,
     }
 
-    impl<S, Arg, Marker> OneRoute<S, (Arg, Marker)>
+    impl<S, Arg, E, Marker> OneRoute<S, (Arg, fn() -> E, Marker)>
     where
         Arg: FromArgMatches + Args + Send + Sync + 'static,
         S: IntoSystem<Marker>,
-        S::System: System<In = In<Arg>, Out = Result<(), WokUnknownError>>,
+        S::System: System<In = In<Arg>, Out = Result<(), E>>,
+        E: Into<WokUnknownError> + Send + Sync + 'static,
     {
         pub fn new(system: S) -> Self {
             Self {
@@ -332,12 +344,12 @@ mod one_route {
         }
     }
 
-    impl<Arg, Marker, S> ConfigureRoute for OneRoute<S, (Arg, Marker)>
+    impl<Arg, E, Marker, S> ConfigureRoute for OneRoute<S, (Arg, fn() -> E, Marker)>
     where
         Arg: FromArgMatches + Args + Send + Sync + 'static,
         S: IntoSystem<Marker>,
-        S::System: System<In = In<Arg>, Out = Result<(), WokUnknownError>>
-            + ProtoSystem<Param: BorrowMutParam>,
+        S::System: System<In = In<Arg>, Out = Result<(), E>> + ProtoSystem<Param: BorrowMutParam>,
+        E: Into<WokUnknownError> + Send + Sync + 'static,
     {
         fn one(
             self,
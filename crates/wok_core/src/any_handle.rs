@@ -122,6 +122,12 @@ impl<T: Sized + Sync + Send + 'static> AsMut<T> for HandleMut<T> {
 
 pub struct Handle<T: Sized + Sync + Send + 'static>(AnyHandle<T>);
 
+impl<T: Sized + Sync + Send + 'static> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Handle(self.0.self_clone())
+    }
+}
+
 impl<T: Sized + Sync + Send + 'static> AsRef<T> for Handle<T> {
     fn as_ref(&self) -> &T {
         // Safety: Given this point its guaranteed that there are only read owners
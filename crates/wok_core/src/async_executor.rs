@@ -1,3 +1,13 @@
+use std::{
+    collections::BTreeMap,
+    pin::Pin,
+    sync::{Arc, Condvar, LazyLock, Mutex, Once},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+use crate::prelude::Resource;
+
 pub trait AsyncExecutorabel {
     type AsyncRuntime: AsyncExecutor;
     fn create() -> Self::AsyncRuntime;
@@ -17,6 +27,13 @@ pub trait AsyncExecutor: Send + Sync + 'static {
     where
         Func: FnOnce() -> Out + Send + 'static,
         Out: Send + 'static;
+
+    /// Timer inserted as a resource on app run, used by systems that need to wait such as
+    /// `retry` and `timeout`. Defaults to one timer thread shared by the process, runtimes
+    /// should override it
+    fn timer(&self) -> Timer {
+        Timer::threaded()
+    }
 }
 
 pub struct FutSpawnError;
@@ -25,3 +42,215 @@ pub trait JoinHandle<Out: Send + 'static>:
     Future<Output = Result<Out, FutSpawnError>> + Send
 {
 }
+
+type SleepFn = dyn Fn(Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync;
+
+#[derive(Clone, Resource)]
+#[resource(usage = core)]
+pub struct Timer(Arc<SleepFn>);
+
+impl Timer {
+    pub fn new<F, Fut>(sleep: F) -> Self
+    where
+        F: Fn(Duration) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self(Arc::new(move |duration| Box::pin(sleep(duration))))
+    }
+
+    /// Runtime agnostic timer, all sleeps are served by one lazily started thread
+    pub fn threaded() -> Self {
+        Self::new(ThreadedSleep::new)
+    }
+
+    pub fn sleep(&self, duration: Duration) -> impl Future<Output = ()> + Send + 'static + use<> {
+        (self.0)(duration)
+    }
+}
+
+/// Pending deadlines of `Timer::threaded`, ordered so the next one is first
+#[derive(Default)]
+struct TimerQueue {
+    next_id: u64,
+    deadlines: BTreeMap<(Instant, u64), Waker>,
+}
+
+struct TimerThread {
+    queue: Mutex<TimerQueue>,
+    changed: Condvar,
+}
+
+static TIMER_THREAD: LazyLock<TimerThread> = LazyLock::new(|| TimerThread {
+    queue: Mutex::default(),
+    changed: Condvar::new(),
+});
+
+impl TimerThread {
+    fn get() -> &'static Self {
+        static START: Once = Once::new();
+        START.call_once(|| {
+            std::thread::Builder::new()
+                .name("wok-timer".to_owned())
+                .spawn(|| TIMER_THREAD.run())
+                .expect("failed to spawn the timer thread");
+        });
+
+        &TIMER_THREAD
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TimerQueue> {
+        self.queue.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn run(&self) {
+        let mut queue = self.lock();
+        loop {
+            let now = Instant::now();
+            let pending = queue.deadlines.split_off(&(now, u64::MAX));
+            let expired = std::mem::replace(&mut queue.deadlines, pending);
+
+            if !expired.is_empty() {
+                // Woken tasks may poll right away, so never hold the lock while waking
+                drop(queue);
+                expired.into_values().for_each(Waker::wake);
+                queue = self.lock();
+                continue;
+            }
+
+            queue = match queue.deadlines.first_key_value() {
+                Some(((deadline, _), _)) => {
+                    let timeout = deadline.saturating_duration_since(now);
+                    self.changed
+                        .wait_timeout(queue, timeout)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0
+                }
+                None => self
+                    .changed
+                    .wait(queue)
+                    .unwrap_or_else(|err| err.into_inner()),
+            };
+        }
+    }
+}
+
+/// Sleep served by the timer thread, dropping it cancels its deadline
+struct ThreadedSleep {
+    key: Option<(Instant, u64)>,
+    deadline: Instant,
+}
+
+impl ThreadedSleep {
+    fn new(duration: Duration) -> Self {
+        Self {
+            key: None,
+            deadline: Instant::now() + duration,
+        }
+    }
+}
+
+impl Future for ThreadedSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            if let Some(key) = self.key.take() {
+                TimerThread::get().lock().deadlines.remove(&key);
+            }
+            return Poll::Ready(());
+        }
+
+        let thread = TimerThread::get();
+        let mut queue = thread.lock();
+        let deadline = self.deadline;
+        let key = *self.key.get_or_insert_with(|| {
+            queue.next_id += 1;
+            (deadline, queue.next_id)
+        });
+        queue.deadlines.insert(key, cx.waker().clone());
+
+        // Only an earlier deadline changes how long the thread has to wait
+        if queue.deadlines.first_key_value().map(|(first, _)| *first) == Some(key) {
+            thread.changed.notify_one();
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for ThreadedSleep {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            TimerThread::get().lock().deadlines.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::Wake;
+
+    use super::*;
+
+    struct Unpark(std::thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = std::pin::pin!(fut);
+
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+            std::thread::park();
+        }
+    }
+
+    fn is_queued(sleep: &ThreadedSleep) -> bool {
+        sleep
+            .key
+            .is_some_and(|key| TimerThread::get().lock().deadlines.contains_key(&key))
+    }
+
+    #[test]
+    fn threaded_timer_wakes_earlier_sleeps_first() {
+        let mut long = std::pin::pin!(ThreadedSleep::new(Duration::from_secs(3600)));
+        let waker = Waker::noop();
+        assert!(
+            long.as_mut()
+                .poll(&mut Context::from_waker(waker))
+                .is_pending()
+        );
+
+        let start = Instant::now();
+        block_on(Timer::threaded().sleep(Duration::from_millis(10)));
+
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        assert!(is_queued(&long));
+    }
+
+    #[test]
+    fn threaded_timer_cancels_dropped_sleeps() {
+        let mut sleep = ThreadedSleep::new(Duration::from_secs(3600));
+        let waker = Waker::noop();
+        assert!(
+            Pin::new(&mut sleep)
+                .poll(&mut Context::from_waker(waker))
+                .is_pending()
+        );
+        assert!(is_queued(&sleep));
+
+        let key = sleep.key;
+        drop(sleep);
+
+        let queue = TimerThread::get().lock();
+        assert!(key.is_some_and(|key| !queue.deadlines.contains_key(&key)));
+    }
+}
@@ -11,7 +11,7 @@ pub mod runtime;
 
 pub mod prelude {
     pub use crate::commands::{Command, Commands};
-    pub use crate::error::{LabelledError, WokUnknownError};
+    pub use crate::error::{Context as _, ErrorHintExt, LabelledError, WokUnknownError};
     pub use crate::param::*;
     pub use crate::resources::{Immutable, Mutable, Resource};
     pub use crate::system::*;
@@ -22,7 +22,7 @@ pub mod prelude {
 }
 
 pub mod error {
-    use std::{fmt::Display, panic::Location};
+    use std::{any::Any, backtrace::Backtrace, borrow::Cow, fmt::Display, panic::Location};
 
     #[derive(Debug, thiserror::Error)]
     #[error("`{label}`: {error}")]
@@ -41,10 +41,21 @@ pub mod error {
     #[error("{0}")]
     pub struct MessageError(pub std::borrow::Cow<'static, str>);
 
+    #[derive(Debug)]
+    struct ContextFrame {
+        message: Cow<'static, str>,
+        location: Option<&'static Location<'static>>,
+    }
+
     #[derive(Debug)]
     pub struct WokUnknownError {
         inner: Box<dyn std::error::Error + Send + Sync + 'static>,
         location: &'static Location<'static>,
+        // Innermost first
+        context: Vec<ContextFrame>,
+        backtrace: Backtrace,
+        status: Option<u16>,
+        exit_code: Option<u8>,
     }
 
     impl WokUnknownError {
@@ -54,6 +65,11 @@ pub mod error {
             Self {
                 inner: Box::new(value),
                 location: Location::caller(),
+                context: Vec::new(),
+                // Only captured when `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` are set
+                backtrace: Backtrace::capture(),
+                status: None,
+                exit_code: None,
             }
         }
 
@@ -69,12 +85,189 @@ pub mod error {
             error: E,
             label: &'static str,
         ) -> WokUnknownError {
-            LabelledError::new(label, error).into()
+            Self::new(LabelledError::new(label, error))
+        }
+
+        /// Converts any `E: Into<WokUnknownError>` at the caller location, `Into::into` isn't
+        /// `#[track_caller]` so converting through it would point into `core`
+        #[track_caller]
+        #[inline]
+        pub fn convert<E: Into<Self> + 'static>(error: E) -> Self {
+            Self::convert_at(error, Location::caller())
+        }
+
+        /// Like [`WokUnknownError::convert`], but errors that are already a `WokUnknownError` keep
+        /// the location they were created at
+        pub fn convert_at<E: Into<Self> + 'static>(
+            error: E,
+            location: &'static Location<'static>,
+        ) -> Self {
+            let mut error = Some(error);
+            if let Some(error) = (&mut error as &mut dyn Any).downcast_mut::<Option<Self>>() {
+                return error.take().expect("set above");
+            }
+
+            let mut error: Self = error.expect("set above").into();
+            error.location = location;
+            error
+        }
+
+        /// Hint the HTTP status to respond with, used by `wok_axum` and `wok_tonic`
+        pub fn with_status(mut self, status: u16) -> Self {
+            self.status = Some(status);
+            self
+        }
+
+        /// Hint the process exit code, used by [`MainExit`]
+        pub fn with_exit_code(mut self, exit_code: u8) -> Self {
+            self.exit_code = Some(exit_code);
+            self
+        }
+
+        pub fn status(&self) -> Option<u16> {
+            self.status
+        }
+
+        pub fn exit_code(&self) -> Option<u8> {
+            self.exit_code
+        }
+
+        pub fn location(&self) -> &'static Location<'static> {
+            self.location
+        }
+
+        /// Wraps the error with a message and the caller location
+        #[track_caller]
+        pub fn context(mut self, message: impl Into<Cow<'static, str>>) -> Self {
+            self.context.push(ContextFrame {
+                message: message.into(),
+                location: Some(Location::caller()),
+            });
+            self
+        }
+
+        /// Wraps the error with a message without a location, such as the name of the failing
+        /// system
+        pub fn frame(mut self, message: impl Into<Cow<'static, str>>) -> Self {
+            self.context.push(ContextFrame {
+                message: message.into(),
+                location: None,
+            });
+            self
+        }
+
+        /// Context messages, outermost first
+        pub fn contexts(&self) -> impl Iterator<Item = (&str, Option<&'static Location<'static>>)> {
+            self.context
+                .iter()
+                .rev()
+                .map(|frame| (frame.message.as_ref(), frame.location))
+        }
+
+        pub fn backtrace(&self) -> Option<&Backtrace> {
+            match self.backtrace.status() {
+                std::backtrace::BacktraceStatus::Captured => Some(&self.backtrace),
+                _ => None,
+            }
+        }
+
+        fn fmt_report(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            struct At(Option<&'static Location<'static>>);
+            impl Display for At {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self.0 {
+                        Some(location) => write!(
+                            f,
+                            "\n{:8}at {}:{}:{}",
+                            "",
+                            location.file(),
+                            location.line(),
+                            location.column()
+                        ),
+                        None => Ok(()),
+                    }
+                }
+            }
+
+            let contexts = self
+                .contexts()
+                .map(|(message, location)| (message, At(location)));
+            let mut causes = contexts
+                .map(|(message, at)| format!("{message}{at}"))
+                .chain(std::iter::once(format!(
+                    "{}{}",
+                    self.inner,
+                    At(Some(self.location))
+                )))
+                .chain(
+                    std::iter::successors(self.inner.source(), |err| err.source())
+                        .map(|err| err.to_string()),
+                );
+
+            if let Some(first) = causes.next() {
+                write!(f, "{first}")?;
+            }
+
+            let mut causes = causes.enumerate().peekable();
+            if causes.peek().is_some() {
+                write!(f, "\n\nCaused by:")?;
+            }
+
+            for (i, cause) in causes {
+                write!(f, "\n{i:>5}: {cause}")?;
+            }
+
+            if let Some(backtrace) = self.backtrace() {
+                write!(f, "\n\nBacktrace:\n{backtrace}")?;
+            }
+
+            Ok(())
+        }
+
+        /// The wrapped error followed by its sources
+        pub fn chain(&self) -> impl Iterator<Item = &(dyn std::error::Error + 'static)> {
+            let first: &(dyn std::error::Error + 'static) = self.inner.as_ref();
+            std::iter::successors(Some(first), |err| err.source())
+        }
+
+        /// Whether the wrapped error is of type `E`, as [`Self::downcast`] checks
+        pub fn is<E: std::error::Error + 'static>(&self) -> bool {
+            self.inner.is::<E>()
+        }
+
+        /// Borrows the wrapped error if it is of type `E`, sources are not searched, see
+        /// [`Self::find`]
+        pub fn downcast_ref<E: std::error::Error + 'static>(&self) -> Option<&E> {
+            self.inner.downcast_ref::<E>()
+        }
+
+        /// Finds the first error of type `E` in the chain, sources included
+        pub fn find<E: std::error::Error + 'static>(&self) -> Option<&E> {
+            self.chain().find_map(|err| err.downcast_ref::<E>())
+        }
+
+        /// Takes the wrapped error back if it is of type `E`
+        pub fn downcast<E: std::error::Error + 'static>(self) -> Result<E, Self> {
+            if !self.inner.is::<E>() {
+                return Err(self);
+            }
+
+            Ok(*self.inner.downcast::<E>().expect("type checked above"))
         }
     }
 
+    /// `{}` prints the context messages and the error in a single line, `{:#}` prints a
+    /// multi-line report with locations, sources and the backtrace if captured
     impl Display for WokUnknownError {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if f.alternate() {
+                return self.fmt_report(f);
+            }
+
+            for (message, _) in self.contexts() {
+                write!(f, "{message}: ")?;
+            }
+
             write!(
                 f,
                 "({}:{}:{}): {}",
@@ -97,10 +290,149 @@ pub mod error {
         }
     }
 
+    /// Attach hints to the error of a `Result`
+    pub trait ErrorHintExt<T> {
+        fn with_status(self, status: u16) -> Result<T, WokUnknownError>;
+        fn with_exit_code(self, exit_code: u8) -> Result<T, WokUnknownError>;
+    }
+
+    impl<T, E> ErrorHintExt<T> for Result<T, E>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        #[track_caller]
+        fn with_status(self, status: u16) -> Result<T, WokUnknownError> {
+            match self {
+                Ok(value) => Ok(value),
+                Err(err) => Err(WokUnknownError::new(err).with_status(status)),
+            }
+        }
+
+        #[track_caller]
+        fn with_exit_code(self, exit_code: u8) -> Result<T, WokUnknownError> {
+            match self {
+                Ok(value) => Ok(value),
+                Err(err) => Err(WokUnknownError::new(err).with_exit_code(exit_code)),
+            }
+        }
+    }
+
+    impl<T> ErrorHintExt<T> for Result<T, WokUnknownError> {
+        fn with_status(self, status: u16) -> Result<T, WokUnknownError> {
+            self.map_err(|err| err.with_status(status))
+        }
+
+        fn with_exit_code(self, exit_code: u8) -> Result<T, WokUnknownError> {
+            self.map_err(|err| err.with_exit_code(exit_code))
+        }
+    }
+
+    /// Adds context to errors, similar to `anyhow::Context`
+    pub trait Context<T> {
+        fn context(self, message: impl Into<Cow<'static, str>>) -> Result<T, WokUnknownError>;
+
+        fn with_context<M, F>(self, f: F) -> Result<T, WokUnknownError>
+        where
+            M: Into<Cow<'static, str>>,
+            F: FnOnce() -> M;
+    }
+
+    impl<T, E> Context<T> for Result<T, E>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        #[track_caller]
+        fn context(self, message: impl Into<Cow<'static, str>>) -> Result<T, WokUnknownError> {
+            match self {
+                Ok(value) => Ok(value),
+                Err(err) => Err(WokUnknownError::new(err).context(message)),
+            }
+        }
+
+        #[track_caller]
+        fn with_context<M, F>(self, f: F) -> Result<T, WokUnknownError>
+        where
+            M: Into<Cow<'static, str>>,
+            F: FnOnce() -> M,
+        {
+            match self {
+                Ok(value) => Ok(value),
+                Err(err) => Err(WokUnknownError::new(err).context(f())),
+            }
+        }
+    }
+
+    impl<T> Context<T> for Result<T, WokUnknownError> {
+        #[track_caller]
+        fn context(self, message: impl Into<Cow<'static, str>>) -> Result<T, WokUnknownError> {
+            match self {
+                Ok(value) => Ok(value),
+                Err(err) => Err(err.context(message)),
+            }
+        }
+
+        #[track_caller]
+        fn with_context<M, F>(self, f: F) -> Result<T, WokUnknownError>
+        where
+            M: Into<Cow<'static, str>>,
+            F: FnOnce() -> M,
+        {
+            match self {
+                Ok(value) => Ok(value),
+                Err(err) => Err(err.context(f())),
+            }
+        }
+    }
+
+    impl<T> Context<T> for Option<T> {
+        #[track_caller]
+        fn context(self, message: impl Into<Cow<'static, str>>) -> Result<T, WokUnknownError> {
+            match self {
+                Some(value) => Ok(value),
+                None => Err(WokUnknownError::from_message(message)),
+            }
+        }
+
+        #[track_caller]
+        fn with_context<M, F>(self, f: F) -> Result<T, WokUnknownError>
+        where
+            M: Into<Cow<'static, str>>,
+            F: FnOnce() -> M,
+        {
+            match self {
+                Some(value) => Ok(value),
+                None => Err(WokUnknownError::from_message(f())),
+            }
+        }
+    }
+
+    /// Used by schedules to accept any `E: Into<WokUnknownError>`, typed errors are located where
+    /// the system was added
+    pub fn into_unknown<T, E>(
+        result: Result<T, E>,
+        location: &'static Location<'static>,
+    ) -> Result<T, WokUnknownError>
+    where
+        E: Into<WokUnknownError> + 'static,
+    {
+        match result {
+            Ok(value) => Ok(value),
+            Err(err) => Err(WokUnknownError::convert_at(err, location)),
+        }
+    }
+
     pub struct MainError(pub WokUnknownError);
+
+    /// Multi-line report, since `main` prints its error with `Debug`
+    impl Display for MainError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:#}", self.0)
+        }
+    }
+
     impl std::fmt::Debug for MainError {
         fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            <WokUnknownError as Display>::fmt(&self.0, f)
+            <MainError as Display>::fmt(self, f)
         }
     }
 
@@ -109,6 +441,111 @@ pub mod error {
             MainError(value)
         }
     }
+
+    /// `main` return type that exits with the error exit code hint, `1` by default
+    pub struct MainExit(pub Result<(), WokUnknownError>);
+
+    impl From<Result<(), WokUnknownError>> for MainExit {
+        fn from(value: Result<(), WokUnknownError>) -> Self {
+            MainExit(value)
+        }
+    }
+
+    impl std::process::Termination for MainExit {
+        fn report(self) -> std::process::ExitCode {
+            match self.0 {
+                Ok(()) => std::process::ExitCode::SUCCESS,
+                Err(err) => {
+                    eprintln!("Error: {err:#}");
+                    std::process::ExitCode::from(err.exit_code().unwrap_or(1))
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[derive(Debug, thiserror::Error)]
+        #[error("connection refused")]
+        struct Refused;
+
+        #[derive(Debug, thiserror::Error)]
+        #[error("could not connect")]
+        struct Connect(#[source] Refused);
+
+        #[test]
+        fn downcast_agrees_with_downcast_ref() {
+            let err = WokUnknownError::new(Connect(Refused));
+
+            assert!(err.is::<Connect>());
+            assert!(err.downcast_ref::<Connect>().is_some());
+            assert!(err.downcast_ref::<Refused>().is_none());
+            assert!(err.find::<Refused>().is_some());
+
+            let err = err.downcast::<Refused>().unwrap_err();
+            assert!(err.downcast::<Connect>().is_ok());
+        }
+
+        #[test]
+        fn chain_starts_with_the_wrapped_error() {
+            let err = WokUnknownError::new(Connect(Refused)).context("starting up");
+            let chain: Vec<_> = err.chain().map(|err| err.to_string()).collect();
+
+            assert_eq!(chain, ["could not connect", "connection refused"]);
+        }
+
+        #[test]
+        fn renders_frames_contexts_and_sources() {
+            let err = WokUnknownError::new(Connect(Refused))
+                .context("dialing")
+                .frame("startup system `connect`");
+
+            let line = err.to_string();
+            assert!(line.starts_with("startup system `connect`: dialing: ("));
+            assert!(line.ends_with("): could not connect"));
+
+            let report = format!("{err:#}");
+            let lines: Vec<_> = report.lines().take(8).collect();
+            let at = format!("        at {}:", file!());
+
+            assert_eq!(
+                lines[..4],
+                [
+                    "startup system `connect`",
+                    "",
+                    "Caused by:",
+                    "    0: dialing"
+                ]
+            );
+            assert!(lines[4].starts_with(&at));
+            assert_eq!(lines[5], "    1: could not connect");
+            assert!(lines[6].starts_with(&at));
+            assert_eq!(lines[7], "    2: connection refused");
+        }
+
+        #[test]
+        fn hints_survive_context() {
+            let err = WokUnknownError::new(Refused)
+                .with_status(503)
+                .with_exit_code(3)
+                .context("starting up");
+
+            assert_eq!(err.status(), Some(503));
+            assert_eq!(err.exit_code(), Some(3));
+        }
+
+        #[test]
+        fn hints_attach_to_results() {
+            let result: Result<(), Refused> = Err(Refused);
+            let err = result.with_status(503).with_exit_code(3).unwrap_err();
+
+            assert_eq!(err.status(), Some(503));
+            assert_eq!(err.exit_code(), Some(3));
+            assert!(err.is::<Refused>());
+        }
+    }
 }
 
 pub mod schedule;
@@ -107,6 +107,7 @@ impl Param for () {
 
 // We know this does not modify anything
 unsafe impl BorrowMutParam for () {}
+impl ReadonlyParam for () {}
 
 macro_rules! impl_param {
     ($($params:ident),*) => {
@@ -143,6 +144,11 @@ macro_rules! impl_param {
         where
             $($params: BorrowMutParam),*
         {}
+
+        impl<$($params),*> ReadonlyParam for ($($params),*)
+        where
+            $($params: ReadonlyParam),*
+        {}
     };
 }
 
@@ -306,6 +312,7 @@ impl<R: Resource> Param for Option<Res<'_, R>> {
 
 // We know Res does not modify the structure
 unsafe impl<R: Resource> BorrowMutParam for Option<Res<'_, R>> {}
+impl<R: Resource<Mutability = Immutable>> ReadonlyParam for Option<Res<'_, R>> {}
 
 impl<R: Resource<Mutability = Mutable>> Param for Option<ResMut<'_, R>> {
     type Owned = Option<<ResMut<'static, R> as Param>::Owned>;
@@ -423,7 +430,7 @@ impl<R: Resource> Param for ResMutMarker<R> {
     type AsRef<'r> = ResMutMarker<R>;
 
     fn init(rw: &mut SystemLock) {
-        if rw.register_resource_write(ResourceId::new::<R>()).is_err() {
+        if rw.register_resource_init(ResourceId::new::<R>()).is_err() {
             panic!(
                 "Resource of type `{}` was already registered",
                 std::any::type_name::<R>()
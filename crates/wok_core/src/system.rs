@@ -1,7 +1,10 @@
 pub use input::*;
 
 mod combinators;
+pub use combinators::{SkippedOutput, for_each, join, race};
 mod input;
+mod resilience;
+pub use resilience::{CircuitBreakerCfg, CircuitOpen, RetryPolicy, TimedOut};
 
 mod task;
 
@@ -1,9 +1,9 @@
-use futures::{FutureExt, future::Either};
+use futures::{FutureExt, StreamExt, future::Either};
 
-use crate::param::Param;
+use crate::param::{Param, ReadonlyParam};
 
 use super::{
-    IntoSystem, ProtoSystem, ProtoTaskSystem, System, SystemIn, SystemInput,
+    CloneInput, In, IntoSystem, ProtoSystem, ProtoTaskSystem, System, SystemIn, SystemInput,
     blocking::{IntoBlockingSystem, ProtoBlockingSystem},
 };
 
@@ -313,3 +313,552 @@ where
         self.system2.run(param.1, out)
     }
 }
+
+/// Output used by [`RunIfSystem`] when its condition does not hold
+pub trait SkippedOutput {
+    fn skipped() -> Self;
+}
+
+impl SkippedOutput for () {
+    fn skipped() -> Self {}
+}
+
+impl<T> SkippedOutput for Option<T> {
+    fn skipped() -> Self {
+        None
+    }
+}
+
+impl<T: SkippedOutput, E> SkippedOutput for Result<T, E> {
+    fn skipped() -> Self {
+        Ok(T::skipped())
+    }
+}
+
+#[derive(Clone)]
+pub struct RunIfSystem<S, C> {
+    system: S,
+    condition: C,
+}
+
+impl<S, C> System for RunIfSystem<S, C>
+where
+    S: ProtoTaskSystem,
+    S::Out: SkippedOutput,
+    C: ProtoBlockingSystem<In = (), Out = bool>,
+    C::Param: ReadonlyParam,
+{
+    type In = S::In;
+    type Out = S::Out;
+
+    fn init(&self, rw: &mut crate::world::SystemLock) {
+        self.condition.init(rw);
+        self.system.init(rw);
+    }
+}
+
+impl<S, C> ProtoSystem for RunIfSystem<S, C>
+where
+    S: ProtoTaskSystem,
+    S::Out: SkippedOutput,
+    C: ProtoBlockingSystem<In = (), Out = bool>,
+    C::Param: ReadonlyParam,
+{
+    type Param = (C::Param, S::Param);
+}
+
+impl<S, C> ProtoTaskSystem for RunIfSystem<S, C>
+where
+    S: ProtoTaskSystem,
+    S::Out: SkippedOutput,
+    C: ProtoBlockingSystem<In = (), Out = bool>,
+    C::Param: ReadonlyParam,
+{
+    fn run<'i>(
+        self,
+        (mut condition_param, param): <Self::Param as Param>::Owned,
+        input: SystemIn<'i, Self>,
+    ) -> impl Future<Output = Self::Out> + Send + 'i {
+        if !self
+            .condition
+            .run(C::Param::from_owned(&mut condition_param), ())
+        {
+            return Either::Left(std::future::ready(S::Out::skipped()));
+        }
+
+        Either::Right(self.system.run(param, input))
+    }
+}
+
+pub struct IntoRunIfSystem<S, C> {
+    pub system: S,
+    pub condition: C,
+}
+
+pub struct IsIntoRunIf;
+
+impl<S, SMarker, C, CMarker> IntoSystem<(SMarker, CMarker, IsIntoRunIf)> for IntoRunIfSystem<S, C>
+where
+    S: IntoSystem<SMarker>,
+    <S::System as System>::Out: SkippedOutput,
+    C: IntoBlockingSystem<CMarker>,
+    C::System: System<In = (), Out = bool>,
+    <C::System as ProtoSystem>::Param: ReadonlyParam,
+{
+    type System = RunIfSystem<S::System, C::System>;
+
+    fn into_system(self) -> Self::System {
+        RunIfSystem {
+            system: self.system.into_system(),
+            condition: self.condition.into_system(),
+        }
+    }
+}
+
+fn init_concurrent<S1: System, S2: System>(
+    combinator: &str,
+    system1: &S1,
+    system2: &S2,
+    rw: &mut crate::world::SystemLock,
+) {
+    let mut rw1 = crate::world::SystemLock::default();
+    system1.init(&mut rw1);
+
+    let mut rw2 = crate::world::SystemLock::default();
+    system2.init(&mut rw2);
+
+    if rw1.conflicts(&rw2) {
+        panic!(
+            "`{combinator}` systems `{}` and `{}` have conflicting resource access",
+            std::any::type_name::<S1>(),
+            std::any::type_name::<S2>()
+        );
+    }
+
+    system1.init(rw);
+    system2.init(rw);
+}
+
+/// Runs both systems concurrently with the same input, returning both outputs
+pub fn join<S1, S1Marker, S2, S2Marker>(system1: S1, system2: S2) -> IntoJoinSystem<S1, S2>
+where
+    S1: IntoSystem<S1Marker>,
+    S2: IntoSystem<S2Marker>,
+{
+    IntoJoinSystem { system1, system2 }
+}
+
+#[derive(Clone)]
+pub struct JoinSystem<S1, S2> {
+    system1: S1,
+    system2: S2,
+}
+
+impl<S1, S2> System for JoinSystem<S1, S2>
+where
+    S1: ProtoTaskSystem,
+    S1::In: CloneInput,
+    S2: ProtoTaskSystem,
+    S2::In: for<'i> SystemInput<Inner<'i> = SystemIn<'i, S1>>,
+{
+    type In = S1::In;
+    type Out = (S1::Out, S2::Out);
+
+    fn init(&self, rw: &mut crate::world::SystemLock) {
+        init_concurrent("join", &self.system1, &self.system2, rw);
+    }
+}
+
+impl<S1, S2> ProtoSystem for JoinSystem<S1, S2>
+where
+    S1: ProtoTaskSystem,
+    S1::In: CloneInput,
+    S2: ProtoTaskSystem,
+    S2::In: for<'i> SystemInput<Inner<'i> = SystemIn<'i, S1>>,
+{
+    type Param = (S1::Param, S2::Param);
+}
+
+impl<S1, S2> ProtoTaskSystem for JoinSystem<S1, S2>
+where
+    S1: ProtoTaskSystem,
+    S1::In: CloneInput,
+    S2: ProtoTaskSystem,
+    S2::In: for<'i> SystemInput<Inner<'i> = SystemIn<'i, S1>>,
+{
+    fn run<'i>(
+        self,
+        (param1, param2): <Self::Param as Param>::Owned,
+        input: SystemIn<'i, Self>,
+    ) -> impl Future<Output = Self::Out> + Send + 'i {
+        let input2 = S1::In::clone_input(&input);
+
+        futures::future::join(
+            self.system1.run(param1, input),
+            self.system2.run(param2, input2),
+        )
+    }
+}
+
+pub struct IntoJoinSystem<S1, S2> {
+    pub system1: S1,
+    pub system2: S2,
+}
+
+pub struct IsIntoJoin;
+
+impl<S1, S1Marker, S2, S2Marker> IntoSystem<(S1Marker, S2Marker, IsIntoJoin)>
+    for IntoJoinSystem<S1, S2>
+where
+    S1: IntoSystem<S1Marker>,
+    S2: IntoSystem<S2Marker>,
+    <S1::System as System>::In: CloneInput,
+    S2::System: System<In = <S1::System as System>::In>,
+{
+    type System = JoinSystem<S1::System, S2::System>;
+
+    fn into_system(self) -> Self::System {
+        JoinSystem {
+            system1: self.system1.into_system(),
+            system2: self.system2.into_system(),
+        }
+    }
+}
+
+/// Runs both systems concurrently with the same input, returning the output of the first one to
+/// finish. The other system is dropped.
+pub fn race<S1, S1Marker, S2, S2Marker>(system1: S1, system2: S2) -> IntoRaceSystem<S1, S2>
+where
+    S1: IntoSystem<S1Marker>,
+    S2: IntoSystem<S2Marker>,
+{
+    IntoRaceSystem { system1, system2 }
+}
+
+#[derive(Clone)]
+pub struct RaceSystem<S1, S2> {
+    system1: S1,
+    system2: S2,
+}
+
+impl<S1, S2> System for RaceSystem<S1, S2>
+where
+    S1: ProtoTaskSystem,
+    S1::In: CloneInput,
+    S2: ProtoTaskSystem<Out = S1::Out>,
+    S2::In: for<'i> SystemInput<Inner<'i> = SystemIn<'i, S1>>,
+{
+    type In = S1::In;
+    type Out = S1::Out;
+
+    fn init(&self, rw: &mut crate::world::SystemLock) {
+        init_concurrent("race", &self.system1, &self.system2, rw);
+    }
+}
+
+impl<S1, S2> ProtoSystem for RaceSystem<S1, S2>
+where
+    S1: ProtoTaskSystem,
+    S1::In: CloneInput,
+    S2: ProtoTaskSystem<Out = S1::Out>,
+    S2::In: for<'i> SystemInput<Inner<'i> = SystemIn<'i, S1>>,
+{
+    type Param = (S1::Param, S2::Param);
+}
+
+impl<S1, S2> ProtoTaskSystem for RaceSystem<S1, S2>
+where
+    S1: ProtoTaskSystem,
+    S1::In: CloneInput,
+    S2: ProtoTaskSystem<Out = S1::Out>,
+    S2::In: for<'i> SystemInput<Inner<'i> = SystemIn<'i, S1>>,
+{
+    fn run<'i>(
+        self,
+        (param1, param2): <Self::Param as Param>::Owned,
+        input: SystemIn<'i, Self>,
+    ) -> impl Future<Output = Self::Out> + Send + 'i {
+        let input2 = S1::In::clone_input(&input);
+        let fut1 = self.system1.run(param1, input);
+        let fut2 = self.system2.run(param2, input2);
+
+        async move {
+            let fut1 = std::pin::pin!(fut1);
+            let fut2 = std::pin::pin!(fut2);
+
+            futures::future::select(fut1, fut2).await.factor_first().0
+        }
+    }
+}
+
+pub struct IntoRaceSystem<S1, S2> {
+    pub system1: S1,
+    pub system2: S2,
+}
+
+pub struct IsIntoRace;
+
+impl<S1, S1Marker, S2, S2Marker> IntoSystem<(S1Marker, S2Marker, IsIntoRace)>
+    for IntoRaceSystem<S1, S2>
+where
+    S1: IntoSystem<S1Marker>,
+    S2: IntoSystem<S2Marker>,
+    <S1::System as System>::In: CloneInput,
+    S2::System: System<In = <S1::System as System>::In, Out = <S1::System as System>::Out>,
+{
+    type System = RaceSystem<S1::System, S2::System>;
+
+    fn into_system(self) -> Self::System {
+        RaceSystem {
+            system1: self.system1.into_system(),
+            system2: self.system2.into_system(),
+        }
+    }
+}
+
+const DEFAULT_FOR_EACH_CONCURRENCY: usize = 16;
+
+/// Runs the system over each item of the `In<I: IntoIterator>` input, keeping at most
+/// `max_concurrency` runs in flight. Outputs keep the order of the items.
+///
+/// Every run shares the same params, so they must be clonable (read-only)
+pub fn for_each<S, SMarker>(system: S) -> IntoForEachSystem<S>
+where
+    S: IntoSystem<SMarker>,
+{
+    IntoForEachSystem {
+        system,
+        max_concurrency: DEFAULT_FOR_EACH_CONCURRENCY,
+    }
+}
+
+pub struct ForEachSystem<S, I> {
+    system: S,
+    max_concurrency: usize,
+    _marker: std::marker::PhantomData<fn(I)>,
+}
+
+impl<S: Clone, I> Clone for ForEachSystem<S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            system: self.system.clone(),
+            max_concurrency: self.max_concurrency,
+            _marker: self._marker,
+        }
+    }
+}
+
+impl<S, I> System for ForEachSystem<S, I>
+where
+    S: ProtoTaskSystem,
+    <S::Param as Param>::Owned: Clone,
+    S::In: for<'i> SystemInput<Inner<'i> = I::Item>,
+    I: IntoIterator + Send + 'static,
+    I::IntoIter: Send,
+{
+    type In = In<I>;
+    type Out = Vec<S::Out>;
+
+    fn init(&self, rw: &mut crate::world::SystemLock) {
+        self.system.init(rw);
+    }
+}
+
+impl<S, I> ProtoSystem for ForEachSystem<S, I>
+where
+    S: ProtoTaskSystem,
+    <S::Param as Param>::Owned: Clone,
+    S::In: for<'i> SystemInput<Inner<'i> = I::Item>,
+    I: IntoIterator + Send + 'static,
+    I::IntoIter: Send,
+{
+    type Param = S::Param;
+}
+
+impl<S, I> ProtoTaskSystem for ForEachSystem<S, I>
+where
+    S: ProtoTaskSystem,
+    <S::Param as Param>::Owned: Clone,
+    S::In: for<'i> SystemInput<Inner<'i> = I::Item>,
+    I: IntoIterator + Send + 'static,
+    I::IntoIter: Send,
+{
+    fn run<'i>(
+        self,
+        param: <Self::Param as Param>::Owned,
+        items: SystemIn<'i, Self>,
+    ) -> impl Future<Output = Self::Out> + Send + 'i {
+        let Self {
+            system,
+            max_concurrency,
+            ..
+        } = self;
+
+        futures::stream::iter(items)
+            .map(move |item| system.clone().run(param.clone(), item))
+            .buffered(max_concurrency)
+            .collect()
+    }
+}
+
+pub struct IntoForEachSystem<S> {
+    pub system: S,
+    pub max_concurrency: usize,
+}
+
+impl<S> IntoForEachSystem<S> {
+    /// Maximum number of runs in flight, at least one
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+}
+
+pub struct IsIntoForEach;
+
+impl<S, SMarker, I> IntoSystem<(SMarker, fn(I), IsIntoForEach)> for IntoForEachSystem<S>
+where
+    S: IntoSystem<SMarker>,
+    <<S::System as ProtoSystem>::Param as Param>::Owned: Clone,
+    <S::System as System>::In: for<'i> SystemInput<Inner<'i> = I::Item>,
+    I: IntoIterator + Send + 'static,
+    I::IntoIter: Send,
+{
+    type System = ForEachSystem<S::System, I>;
+
+    fn into_system(self) -> Self::System {
+        ForEachSystem {
+            system: self.system.into_system(),
+            max_concurrency: self.max_concurrency.max(1),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use crate::{
+        param::{Res, ResMut},
+        prelude::Resource,
+        resources::ResourceId,
+        system::TaskSystem,
+        world::{ConfigureWorld, SystemLock, World},
+    };
+
+    use super::*;
+
+    #[derive(Resource)]
+    #[resource(usage = core)]
+    struct Enabled(bool);
+
+    #[derive(Resource)]
+    #[resource(usage = core, mutable = true)]
+    struct Counter(u32);
+
+    fn is_enabled(enabled: Res<'_, Enabled>) -> bool {
+        enabled.0
+    }
+
+    async fn increment(mut counter: ResMut<'_, Counter>) {
+        counter.0 += 1;
+    }
+
+    #[test]
+    fn run_if_locks_both_systems() {
+        let system = increment.run_if(is_enabled).into_system();
+
+        let mut rw = SystemLock::default();
+        system.init(&mut rw);
+
+        assert!(rw.has_resource_read(ResourceId::new::<Enabled>()));
+        assert!(rw.has_resource_write(ResourceId::new::<Counter>()));
+    }
+
+    #[test]
+    fn run_if_skips_on_false() {
+        let system = increment.run_if(is_enabled).into_system();
+
+        for (enabled, expected) in [(false, 0), (true, 1)] {
+            let mut world = World::default()
+                .insert_resource(Enabled(enabled))
+                .insert_resource(Counter(0));
+
+            unsafe { TaskSystem::owned_run(&system, world.state.as_unsafe_mut(), ()) }
+                .now_or_never()
+                .expect("system should not await");
+
+            assert_eq!(world.get::<Res<Counter>>().0, expected);
+        }
+    }
+
+    #[derive(Resource)]
+    #[resource(usage = core)]
+    struct Multiplier(u32);
+
+    async fn multiply(In(value): In<u32>, multiplier: Res<'_, Multiplier>) -> u32 {
+        value * multiplier.0
+    }
+
+    async fn double(In(value): In<u32>) -> u32 {
+        value * 2
+    }
+
+    async fn read_counter(counter: Res<'_, Counter>) -> u32 {
+        counter.0
+    }
+
+    async fn never(In(value): In<u32>) -> u32 {
+        std::future::pending::<()>().await;
+        value
+    }
+
+    #[test]
+    fn join_runs_both() {
+        let system = join(multiply, double).into_system();
+
+        let mut rw = SystemLock::default();
+        system.init(&mut rw);
+        assert!(rw.has_resource_read(ResourceId::new::<Multiplier>()));
+
+        let mut world = World::default().insert_resource(Multiplier(3));
+        let out = unsafe { TaskSystem::owned_run(&system, world.state.as_unsafe_mut(), 5) }
+            .now_or_never()
+            .expect("system should not await");
+
+        assert_eq!(out, (15, 10));
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicting resource access")]
+    fn join_panics_on_conflicting_locks() {
+        let system = join(increment, read_counter).into_system();
+        system.init(&mut SystemLock::default());
+    }
+
+    #[test]
+    fn race_returns_first() {
+        let system = race(never, double).into_system();
+
+        let mut world = World::default();
+        let out = unsafe { TaskSystem::owned_run(&system, world.state.as_unsafe_mut(), 4) }
+            .now_or_never()
+            .expect("system should not await");
+
+        assert_eq!(out, 8);
+    }
+
+    #[test]
+    fn for_each_keeps_order() {
+        let system = for_each(multiply).max_concurrency(2).into_system();
+
+        let mut world = World::default().insert_resource(Multiplier(2));
+        let out =
+            unsafe { TaskSystem::owned_run(&system, world.state.as_unsafe_mut(), vec![1, 2, 3]) }
+                .now_or_never()
+                .expect("system should not await");
+
+        assert_eq!(out, vec![2, 4, 6]);
+    }
+}
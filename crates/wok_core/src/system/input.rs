@@ -7,6 +7,11 @@ pub trait SystemInput: Sized + Send {
     fn wrap(this: Self::Inner<'_>) -> Self::Wrapped<'_>;
 }
 
+/// Input that can be handed to more than one system, see `join` and `race`
+pub trait CloneInput: SystemInput {
+    fn clone_input<'i>(input: &Self::Inner<'i>) -> Self::Inner<'i>;
+}
+
 impl SystemInput for () {
     type Wrapped<'i> = ();
     type Inner<'i> = ();
@@ -14,6 +19,10 @@ impl SystemInput for () {
     fn wrap(_this: Self::Inner<'_>) -> Self::Wrapped<'_> {}
 }
 
+impl CloneInput for () {
+    fn clone_input<'i>(_input: &Self::Inner<'i>) -> Self::Inner<'i> {}
+}
+
 pub struct In<T: Sized + 'static + Send>(pub T);
 
 impl<T: Send> Deref for In<T> {
@@ -33,6 +42,12 @@ impl<T: Sized + 'static + Send> SystemInput for In<T> {
     }
 }
 
+impl<T: Clone + Sized + 'static + Send> CloneInput for In<T> {
+    fn clone_input<'i>(input: &Self::Inner<'i>) -> Self::Inner<'i> {
+        input.clone()
+    }
+}
+
 pub struct InRef<'i, I: ?Sized + 'static + Send>(&'i I);
 
 impl<'i, I: ?Sized + Send> Deref for InRef<'i, I> {
@@ -55,3 +70,11 @@ where
     }
 }
 
+impl<I: ?Sized + 'static + Send> CloneInput for InRef<'_, I>
+where
+    for<'i> &'i I: Send,
+{
+    fn clone_input<'i>(input: &Self::Inner<'i>) -> Self::Inner<'i> {
+        *input
+    }
+}
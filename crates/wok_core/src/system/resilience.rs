@@ -0,0 +1,613 @@
+use std::{
+    hash::{BuildHasher, Hasher},
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::future::Either;
+
+use crate::{
+    async_executor::Timer,
+    param::{Param, Res},
+};
+
+use super::{CloneInput, IntoSystem, ProtoSystem, ProtoTaskSystem, System, SystemIn};
+
+/// Exponential backoff used by [`IntoSystem::retry`]
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first run
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: u32,
+    /// Randomize each backoff between half and the full duration
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2,
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn exponential(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Backoff to wait after the failed `attempt`, starting from 1
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .checked_pow(attempt.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff);
+
+        if !self.jitter {
+            return backoff;
+        }
+
+        let half = backoff / 2;
+        half + half.mul_f64(random_fraction())
+    }
+}
+
+// Good enough randomness for jitter without pulling a rng crate
+fn random_fraction() -> f64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default(),
+    );
+
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("system timed out after {0:?}")]
+pub struct TimedOut(pub Duration);
+
+#[derive(Debug, thiserror::Error)]
+#[error("circuit breaker is open")]
+pub struct CircuitOpen;
+
+/// Config for [`IntoSystem::circuit_breaker`]
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerCfg {
+    /// Consecutive failures before opening the circuit
+    pub failure_threshold: u32,
+    /// Time the circuit stays open before letting a trial run through
+    pub reset_timeout: Duration,
+}
+
+impl Default for CircuitBreakerCfg {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            reset_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl CircuitBreakerCfg {
+    pub fn failure_threshold(mut self, failure_threshold: u32) -> Self {
+        self.failure_threshold = failure_threshold;
+        self
+    }
+
+    pub fn reset_timeout(mut self, reset_timeout: Duration) -> Self {
+        self.reset_timeout = reset_timeout;
+        self
+    }
+}
+
+pub struct RetrySystem<S, Ok, Err> {
+    system: S,
+    policy: RetryPolicy,
+    _marker: PhantomData<fn(Ok) -> Err>,
+}
+
+impl<S: Clone, Ok, Err> Clone for RetrySystem<S, Ok, Err> {
+    fn clone(&self) -> Self {
+        Self {
+            system: self.system.clone(),
+            policy: self.policy.clone(),
+            _marker: self._marker,
+        }
+    }
+}
+
+impl<S, Ok, Err> System for RetrySystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: std::fmt::Display + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+    S::In: CloneInput,
+    <S::Param as Param>::Owned: Clone,
+{
+    type In = S::In;
+    type Out = S::Out;
+
+    fn init(&self, rw: &mut crate::world::SystemLock) {
+        Res::<Timer>::init(rw);
+        self.system.init(rw);
+    }
+}
+
+impl<S, Ok, Err> ProtoSystem for RetrySystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: std::fmt::Display + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+    S::In: CloneInput,
+    <S::Param as Param>::Owned: Clone,
+{
+    type Param = (Res<'static, Timer>, S::Param);
+}
+
+impl<S, Ok, Err> ProtoTaskSystem for RetrySystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: std::fmt::Display + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+    S::In: CloneInput,
+    <S::Param as Param>::Owned: Clone,
+{
+    async fn run<'i>(
+        self,
+        (timer, param): <Self::Param as Param>::Owned,
+        input: SystemIn<'i, Self>,
+    ) -> Self::Out {
+        let mut attempt = 1;
+
+        loop {
+            let result = self
+                .system
+                .clone()
+                .run(param.clone(), S::In::clone_input(&input))
+                .await;
+
+            match result {
+                Ok(out) => return Ok(out),
+                Err(err) if attempt >= self.policy.max_attempts => return Err(err),
+                Err(err) => tracing::warn!(
+                    "Attempt {attempt} of {} failed, retrying: {err}",
+                    self.policy.max_attempts
+                ),
+            }
+
+            timer.sleep(self.policy.backoff(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+pub struct IntoRetrySystem<S> {
+    pub system: S,
+    pub policy: RetryPolicy,
+}
+
+pub struct IsIntoRetry;
+
+impl<S, SMarker, Ok, Err> IntoSystem<(SMarker, fn(Ok) -> Err, IsIntoRetry)> for IntoRetrySystem<S>
+where
+    Ok: Send + 'static,
+    Err: std::fmt::Display + Send + 'static,
+    S: IntoSystem<SMarker>,
+    S::System: System<Out = Result<Ok, Err>>,
+    <S::System as System>::In: CloneInput,
+    <<S::System as ProtoSystem>::Param as Param>::Owned: Clone,
+{
+    type System = RetrySystem<S::System, Ok, Err>;
+
+    fn into_system(self) -> Self::System {
+        RetrySystem {
+            system: self.system.into_system(),
+            policy: self.policy,
+            _marker: PhantomData,
+        }
+    }
+}
+
+pub struct TimeoutSystem<S, Ok, Err> {
+    system: S,
+    duration: Duration,
+    _marker: PhantomData<fn(Ok) -> Err>,
+}
+
+impl<S: Clone, Ok, Err> Clone for TimeoutSystem<S, Ok, Err> {
+    fn clone(&self) -> Self {
+        Self {
+            system: self.system.clone(),
+            duration: self.duration,
+            _marker: self._marker,
+        }
+    }
+}
+
+impl<S, Ok, Err> System for TimeoutSystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: From<TimedOut> + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+{
+    type In = S::In;
+    type Out = S::Out;
+
+    fn init(&self, rw: &mut crate::world::SystemLock) {
+        Res::<Timer>::init(rw);
+        self.system.init(rw);
+    }
+}
+
+impl<S, Ok, Err> ProtoSystem for TimeoutSystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: From<TimedOut> + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+{
+    type Param = (Res<'static, Timer>, S::Param);
+}
+
+impl<S, Ok, Err> ProtoTaskSystem for TimeoutSystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: From<TimedOut> + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+{
+    fn run<'i>(
+        self,
+        (timer, param): <Self::Param as Param>::Owned,
+        input: SystemIn<'i, Self>,
+    ) -> impl Future<Output = Self::Out> + Send + 'i {
+        let duration = self.duration;
+        let fut = self.system.run(param, input);
+        let sleep = timer.sleep(duration);
+
+        async move {
+            let fut = std::pin::pin!(fut);
+            let sleep = std::pin::pin!(sleep);
+
+            match futures::future::select(fut, sleep).await {
+                Either::Left((out, _)) => out,
+                Either::Right(((), _)) => Err(TimedOut(duration).into()),
+            }
+        }
+    }
+}
+
+pub struct IntoTimeoutSystem<S> {
+    pub system: S,
+    pub duration: Duration,
+}
+
+pub struct IsIntoTimeout;
+
+impl<S, SMarker, Ok, Err> IntoSystem<(SMarker, fn(Ok) -> Err, IsIntoTimeout)>
+    for IntoTimeoutSystem<S>
+where
+    Ok: Send + 'static,
+    Err: From<TimedOut> + Send + 'static,
+    S: IntoSystem<SMarker>,
+    S::System: System<Out = Result<Ok, Err>>,
+{
+    type System = TimeoutSystem<S::System, Ok, Err>;
+
+    fn into_system(self) -> Self::System {
+        TimeoutSystem {
+            system: self.system.into_system(),
+            duration: self.duration,
+            _marker: PhantomData,
+        }
+    }
+}
+
+enum CircuitState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+/// Reopens the circuit when the trial call is dropped before finishing, otherwise the circuit
+/// would stay half-open and reject every call
+struct TrialGuard {
+    state: Arc<Mutex<CircuitState>>,
+    reset_timeout: Duration,
+}
+
+impl Drop for TrialGuard {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        if let CircuitState::HalfOpen = *state {
+            *state = CircuitState::Open {
+                until: Instant::now() + self.reset_timeout,
+            };
+        }
+    }
+}
+
+pub struct CircuitBreakerSystem<S, Ok, Err> {
+    system: S,
+    cfg: CircuitBreakerCfg,
+    // Shared between every clone of the system
+    state: Arc<Mutex<CircuitState>>,
+    _marker: PhantomData<fn(Ok) -> Err>,
+}
+
+impl<S: Clone, Ok, Err> Clone for CircuitBreakerSystem<S, Ok, Err> {
+    fn clone(&self) -> Self {
+        Self {
+            system: self.system.clone(),
+            cfg: self.cfg.clone(),
+            state: self.state.clone(),
+            _marker: self._marker,
+        }
+    }
+}
+
+impl<S, Ok, Err> CircuitBreakerSystem<S, Ok, Err> {
+    /// The guard is only given to the trial call of a half-open circuit
+    fn try_pass(&self) -> Result<Option<TrialGuard>, CircuitOpen> {
+        let mut state = self.state.lock().unwrap();
+        match *state {
+            CircuitState::Closed { .. } => Ok(None),
+            CircuitState::Open { until } if Instant::now() >= until => {
+                *state = CircuitState::HalfOpen;
+                Ok(Some(TrialGuard {
+                    state: self.state.clone(),
+                    reset_timeout: self.cfg.reset_timeout,
+                }))
+            }
+            CircuitState::Open { .. } | CircuitState::HalfOpen => Err(CircuitOpen),
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let mut state = self.state.lock().unwrap();
+        let failures = match (&*state, success) {
+            (_, true) => 0,
+            (CircuitState::Closed { failures }, false) => failures + 1,
+            (_, false) => self.cfg.failure_threshold,
+        };
+
+        *state = if failures >= self.cfg.failure_threshold {
+            CircuitState::Open {
+                until: Instant::now() + self.cfg.reset_timeout,
+            }
+        } else {
+            CircuitState::Closed { failures }
+        };
+    }
+}
+
+impl<S, Ok, Err> System for CircuitBreakerSystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: From<CircuitOpen> + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+{
+    type In = S::In;
+    type Out = S::Out;
+
+    fn init(&self, rw: &mut crate::world::SystemLock) {
+        self.system.init(rw);
+    }
+}
+
+impl<S, Ok, Err> ProtoSystem for CircuitBreakerSystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: From<CircuitOpen> + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+{
+    type Param = S::Param;
+}
+
+impl<S, Ok, Err> ProtoTaskSystem for CircuitBreakerSystem<S, Ok, Err>
+where
+    Ok: Send + 'static,
+    Err: From<CircuitOpen> + Send + 'static,
+    S: ProtoTaskSystem<Out = Result<Ok, Err>>,
+{
+    fn run<'i>(
+        self,
+        param: <Self::Param as Param>::Owned,
+        input: SystemIn<'i, Self>,
+    ) -> impl Future<Output = Self::Out> + Send + 'i {
+        let trial = match self.try_pass() {
+            Ok(trial) => trial,
+            Err(open) => return Either::Left(std::future::ready(Err(open.into()))),
+        };
+
+        let fut = self.system.clone().run(param, input);
+        Either::Right(async move {
+            let out = fut.await;
+            self.record(out.is_ok());
+            drop(trial);
+            out
+        })
+    }
+}
+
+pub struct IntoCircuitBreakerSystem<S> {
+    pub system: S,
+    pub cfg: CircuitBreakerCfg,
+}
+
+pub struct IsIntoCircuitBreaker;
+
+impl<S, SMarker, Ok, Err> IntoSystem<(SMarker, fn(Ok) -> Err, IsIntoCircuitBreaker)>
+    for IntoCircuitBreakerSystem<S>
+where
+    Ok: Send + 'static,
+    Err: From<CircuitOpen> + Send + 'static,
+    S: IntoSystem<SMarker>,
+    S::System: System<Out = Result<Ok, Err>>,
+{
+    type System = CircuitBreakerSystem<S::System, Ok, Err>;
+
+    fn into_system(self) -> Self::System {
+        CircuitBreakerSystem {
+            system: self.system.into_system(),
+            cfg: self.cfg,
+            state: Arc::new(Mutex::new(CircuitState::Closed { failures: 0 })),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use futures::FutureExt;
+
+    use crate::{
+        prelude::Resource,
+        system::TaskSystem,
+        world::{ConfigureWorld, World},
+    };
+
+    use super::*;
+
+    /// Runs of the system under test, kept per world so tests stay independent
+    #[derive(Default, Resource)]
+    #[resource(usage = core)]
+    struct Calls(AtomicU32);
+
+    async fn flaky(calls: Res<'_, Calls>) -> Result<u32, TimedOut> {
+        match calls.0.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => Err(TimedOut(Duration::ZERO)),
+            attempt => Ok(attempt),
+        }
+    }
+
+    async fn stuck() -> Result<(), TimedOut> {
+        std::future::pending().await
+    }
+
+    async fn failing() -> Result<(), CircuitOpen> {
+        Err(CircuitOpen)
+    }
+
+    fn world() -> World {
+        World::default()
+            .insert_resource(Timer::new(|_| std::future::ready(())))
+            .insert_resource(Calls::default())
+    }
+
+    #[test]
+    fn backoff_grows_until_max() {
+        let policy = RetryPolicy::exponential(5)
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(300))
+            .jitter(false);
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn retries_until_ok() {
+        let system = flaky.retry(RetryPolicy::exponential(3)).into_system();
+
+        let mut world = world();
+        let out = unsafe { TaskSystem::owned_run(&system, world.state.as_unsafe_mut(), ()) }
+            .now_or_never()
+            .expect("timer should not await");
+
+        assert_eq!(out.unwrap(), 2);
+    }
+
+    #[test]
+    fn times_out() {
+        let system = stuck.timeout(Duration::from_secs(1)).into_system();
+
+        let mut world = world();
+        let out = unsafe { TaskSystem::owned_run(&system, world.state.as_unsafe_mut(), ()) }
+            .now_or_never()
+            .expect("timer should not await");
+
+        assert!(out.is_err());
+    }
+
+    #[test]
+    fn circuit_opens() {
+        let cfg = CircuitBreakerCfg::default()
+            .failure_threshold(2)
+            .reset_timeout(Duration::from_secs(60));
+        let system = failing.circuit_breaker(cfg).into_system();
+
+        let mut world = world();
+        for _ in 0..3 {
+            let _ = unsafe { TaskSystem::owned_run(&system, world.state.as_unsafe_mut(), ()) }
+                .now_or_never();
+        }
+
+        assert!(system.try_pass().is_err());
+    }
+
+    async fn fails_then_hangs(calls: Res<'_, Calls>) -> Result<(), CircuitOpen> {
+        if calls.0.fetch_add(1, Ordering::SeqCst) == 0 {
+            return Err(CircuitOpen);
+        }
+
+        std::future::pending().await
+    }
+
+    #[test]
+    fn dropped_trial_reopens_circuit() {
+        let cfg = CircuitBreakerCfg::default()
+            .failure_threshold(1)
+            .reset_timeout(Duration::ZERO);
+        let system = fails_then_hangs.circuit_breaker(cfg).into_system();
+
+        let mut world = world();
+        for _ in 0..2 {
+            let out = unsafe { TaskSystem::owned_run(&system, world.state.as_unsafe_mut(), ()) }
+                .now_or_never();
+            assert!(!matches!(out, Some(Ok(()))));
+        }
+
+        // The hanging trial was dropped, the next one passes once the timeout is over
+        assert!(system.try_pass().is_ok());
+    }
+}
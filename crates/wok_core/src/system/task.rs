@@ -6,7 +6,12 @@ use crate::{
 };
 
 use super::{
-    IntoBlockingSystem, ProtoSystem, System, SystemIn, SystemInput, combinators::IntoMapSystem,
+    IntoBlockingSystem, ProtoSystem, System, SystemIn, SystemInput,
+    combinators::{IntoMapSystem, IntoRunIfSystem, SkippedOutput},
+    resilience::{
+        CircuitBreakerCfg, CircuitOpen, IntoCircuitBreakerSystem, IntoRetrySystem,
+        IntoTimeoutSystem, RetryPolicy, TimedOut,
+    },
 };
 
 pub type ScopedFut<'i, Out> = Pin<Box<dyn Future<Output = Out> + Send + 'i>>;
@@ -93,6 +98,62 @@ pub trait IntoSystem<Marker> {
             system2: system,
         }
     }
+
+    /// Only runs the system when `condition` returns `true`, otherwise it
+    /// returns [`SkippedOutput::skipped`]
+    fn run_if<C, CMarker>(self, condition: C) -> IntoRunIfSystem<Self, C>
+    where
+        Self: Sized,
+        <Self::System as System>::Out: SkippedOutput,
+        C: IntoBlockingSystem<CMarker>,
+        C::System: System<In = (), Out = bool>,
+        <C::System as ProtoSystem>::Param: crate::param::ReadonlyParam,
+    {
+        IntoRunIfSystem {
+            system: self,
+            condition,
+        }
+    }
+
+    /// Runs the system again on `Err` following the backoff of `policy`, each failed attempt is
+    /// logged at warn. Requires the `Timer` resource, inserted by the app on run
+    fn retry<Ok, Err>(self, policy: RetryPolicy) -> IntoRetrySystem<Self>
+    where
+        Self: Sized,
+        Err: std::fmt::Display,
+        Self::System: System<Out = Result<Ok, Err>>,
+        <Self::System as System>::In: super::CloneInput,
+        <<Self::System as ProtoSystem>::Param as Param>::Owned: Clone,
+    {
+        IntoRetrySystem {
+            system: self,
+            policy,
+        }
+    }
+
+    /// Fails with [`TimedOut`] if the system does not finish within `duration`.
+    /// Requires the `Timer` resource, inserted by the app on run
+    fn timeout<Ok, Err>(self, duration: std::time::Duration) -> IntoTimeoutSystem<Self>
+    where
+        Self: Sized,
+        Self::System: System<Out = Result<Ok, Err>>,
+        Err: From<TimedOut>,
+    {
+        IntoTimeoutSystem {
+            system: self,
+            duration,
+        }
+    }
+
+    /// Fails fast with [`CircuitOpen`] after too many consecutive errors
+    fn circuit_breaker<Ok, Err>(self, cfg: CircuitBreakerCfg) -> IntoCircuitBreakerSystem<Self>
+    where
+        Self: Sized,
+        Self::System: System<Out = Result<Ok, Err>>,
+        Err: From<CircuitOpen>,
+    {
+        IntoCircuitBreakerSystem { system: self, cfg }
+    }
 }
 
 impl<S: ProtoTaskSystem> TaskSystem for S {
@@ -253,6 +253,14 @@ impl WorldCenter {
         let id = self.system_locks.systems_rw.add(draft.locks);
         SystemEntry::new(id, draft.system)
     }
+
+    /// Registers the locks of `P` alone, so they can be reserved without a system
+    pub fn register_params<P: crate::param::Param>(&mut self) -> SystemId {
+        let mut rw = SystemLock::default();
+        P::init(&mut rw);
+
+        self.system_locks.systems_rw.add(rw)
+    }
 }
 
 pub struct World {
@@ -339,6 +347,7 @@ pub trait ConfigureWorld: Sized {
         self
     }
 
+    #[track_caller]
     fn add_systems<Sch, T, Marker>(mut self, schedule: Sch, into_cfg: T) -> Self
     where
         Sch: ScheduleLabel + ScheduleConfigure<T, Marker>,
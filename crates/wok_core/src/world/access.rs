@@ -106,6 +106,8 @@ impl WorldLocks {
 #[derive(Default)]
 pub struct SystemLock {
     resources: HashSet<(ResourceId, AccessMode)>,
+    /// Written resources the system only inserts, such as with `ResInit`
+    inits: HashSet<ResourceId>,
 }
 
 pub enum AlreadyRegistered {
@@ -122,6 +124,11 @@ impl SystemLock {
         self.resources.contains(&(resource, AccessMode::Read))
     }
 
+    /// Whether the resource is written only to insert it
+    pub fn has_resource_init(&self, resource: ResourceId) -> bool {
+        self.inits.contains(&resource)
+    }
+
     pub fn register_resource_read(
         &mut self,
         resource: ResourceId,
@@ -143,9 +150,35 @@ impl SystemLock {
         }
 
         self.resources.insert((resource, AccessMode::Write));
+        self.inits.remove(&resource);
         Ok(())
     }
 
+    /// Registers a write of a resource the system only inserts, it still conflicts like any
+    /// other write
+    pub fn register_resource_init(
+        &mut self,
+        resource: ResourceId,
+    ) -> Result<(), AlreadyRegistered> {
+        let used = self.has_resource_write(resource);
+        self.register_resource_write(resource)?;
+
+        if !used {
+            self.inits.insert(resource);
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if both locks can not be held at the same time
+    pub fn conflicts(&self, other: &SystemLock) -> bool {
+        self.resources.iter().any(|&(resource, mode)| match mode {
+            AccessMode::Read => other.has_resource_write(resource),
+            AccessMode::Write => {
+                other.has_resource_read(resource) || other.has_resource_write(resource)
+            }
+        })
+    }
+
     pub fn entries(&self) -> impl Iterator<Item = &(ResourceId, AccessMode)> {
         self.resources.iter()
     }
@@ -15,17 +15,23 @@ mod remote {
 
     use super::{SystemEntryRef, WorldBorrowMut};
 
-    #[derive(Debug, Clone)]
+    /// Releases the locks of a system once, when released or dropped
+    #[derive(Debug)]
     pub struct ReleaseSystem {
         system_id: SystemId,
-        sx: SystemReleaser,
+        sx: Option<SystemReleaser>,
     }
 
     impl ReleaseSystem {
-        pub async fn release(self) {
-            if self.sx.0.send(self.system_id).await.is_err() {
+        pub async fn release(mut self) {
+            if let Some(sx) = &self.sx
+                && sx.0.send(self.system_id).await.is_err()
+            {
                 println!("WARNING: failed to release system {:?}", self.system_id);
-            };
+            }
+
+            // Released, the drop must not release the locks again
+            self.sx = None;
         }
     }
 
@@ -68,7 +74,10 @@ mod remote {
 
     impl ReleaseSystem {
         pub fn new(system_id: SystemId, sx: SystemReleaser) -> Self {
-            Self { system_id, sx }
+            Self {
+                system_id,
+                sx: Some(sx),
+            }
         }
     }
 
@@ -79,7 +88,11 @@ mod remote {
 
     impl Drop for ReleaseSystem {
         fn drop(&mut self) {
-            if self.sx.0.try_send(self.system_id).is_err() {
+            let Some(sx) = self.sx.take() else {
+                return;
+            };
+
+            if sx.0.try_send(self.system_id).is_err() {
                 println!("WARNING: failed to release system {:?}", self.system_id);
             }
         }
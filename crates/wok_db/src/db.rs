@@ -1,6 +1,9 @@
 use wok::prelude::WokUnknownError;
 
-use crate::Record;
+use crate::{
+    Record,
+    query::{Condition, Direction, Order},
+};
 
 pub trait DbQuery<O> {
     fn execute(self) -> impl Future<Output = Result<O, WokUnknownError>> + Send;
@@ -16,10 +19,167 @@ pub trait DbList<D>: 'static {
     fn list<'q>(&'q self, table: &'static str) -> Self::ListQuery<'q>;
 }
 
+/// Page of a listing, records are ordered by id unless `order_by` is given
+#[derive(Debug, Clone)]
+pub struct ListParams<R> {
+    pub limit: Option<usize>,
+    pub start: Option<usize>,
+    /// Cursor, only records after this id are listed. Backends refuse it unless the records are
+    /// ordered by id ascending, see [`ListParams::check_cursor`]
+    pub after: Option<R>,
+    /// Every condition must match
+    pub filters: Vec<Condition>,
+    /// The id breaks ties
+    pub order: Vec<Order>,
+}
+
+impl<R> Default for ListParams<R> {
+    fn default() -> Self {
+        Self {
+            limit: None,
+            start: None,
+            after: None,
+            filters: Vec::new(),
+            order: Vec::new(),
+        }
+    }
+}
+
+impl<R> ListParams<R> {
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    pub fn after(mut self, id: R) -> Self {
+        self.after = Some(id);
+        self
+    }
+
+    pub fn filter(mut self, condition: Condition) -> Self {
+        self.filters.push(condition);
+        self
+    }
+
+    pub fn order_by(mut self, order: Order) -> Self {
+        self.order.push(order);
+        self
+    }
+
+    /// Checks `after` is only combined with an ascending order on the id, as a cursor skips every
+    /// record with a lower id
+    pub fn check_cursor(&self, id_field: &str) -> Result<(), CursorOrderError> {
+        if self.after.is_none() {
+            return Ok(());
+        }
+
+        match self
+            .order
+            .iter()
+            .find(|order| order.field != id_field || order.direction != Direction::Asc)
+        {
+            Some(order) => Err(CursorOrderError(order.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("cannot list after a cursor when ordered by `{}` {:?}, only by id ascending", .0.field, .0.direction)]
+pub struct CursorOrderError(pub Order);
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(untagged)]
+pub enum FilterValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<FilterValue>),
+}
+
+/// Declared type of a filterable field, raw values such as query string params are parsed by it
+/// instead of guessing from their text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterType {
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+impl FilterType {
+    /// `None` when the value does not parse as this type
+    pub fn parse(self, value: &str) -> Option<FilterValue> {
+        match self {
+            FilterType::Bool => value.parse().ok().map(FilterValue::Bool),
+            FilterType::Int => value.parse().ok().map(FilterValue::Int),
+            FilterType::Float => value.parse().ok().map(FilterValue::Float),
+            FilterType::String => Some(FilterValue::String(value.to_owned())),
+        }
+    }
+}
+
+pub trait DbListPage<R, D>: 'static {
+    type ListPageQuery<'q>: DbQuery<Vec<D>>;
+    fn list_page<'q>(
+        &'q self,
+        table: &'static str,
+        params: ListParams<R>,
+    ) -> Self::ListPageQuery<'q>;
+}
+
 pub enum DbDeleteError {
     None,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum DbUpdateError {
+    #[error("record not found")]
+    NotFound,
+    /// Breaks a unique index
+    #[error("record conflicts with another one")]
+    Conflict,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum DbUpsertError {
+    /// Breaks a unique index
+    #[error("record conflicts with another one")]
+    Conflict,
+}
+
+/// Whether an upsert created the record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upserted {
+    Created,
+    Updated,
+}
+
+/// Replaces the whole record
+pub trait DbUpdate<R, D>: 'static {
+    type UpdateQuery<'q>: DbQuery<Result<(), DbUpdateError>>;
+    fn update<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpdateQuery<'q>;
+}
+
+/// Merges the given fields into the record, `D` usually skips serializing absent fields
+pub trait DbMerge<R, D>: 'static {
+    type MergeQuery<'q>: DbQuery<Result<(), DbUpdateError>>;
+    fn merge<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::MergeQuery<'q>;
+}
+
+/// Replaces the whole record, creating it when missing
+pub trait DbUpsert<R, D>: 'static {
+    type UpsertQuery<'q>: DbQuery<Result<Upserted, DbUpsertError>>;
+    fn upsert<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpsertQuery<'q>;
+}
+
 pub trait DbDelete<R>: 'static {
     type DeleteQuery<'q>: DbQuery<Result<(), DbDeleteError>>;
     fn delete<'q>(&'q self, table: &'static str, id: R) -> Self::DeleteQuery<'q>;
@@ -30,6 +190,17 @@ pub trait DbSelectSingle<R, D>: 'static {
     fn select<'q>(&'q self, table: &'static str, id: R) -> Self::SelectQuery<'q>;
 }
 
+/// Runs the queries of a transaction handle atomically
+/// Depending on the backend, queries of the handle run right away or are queued by `execute`
+/// Queued queries only run once committed, so their errors come out of the commit instead
+pub trait DbTransaction: 'static {
+    type Transaction: RecordDb + Clone + Send + Sync;
+    type CommitQuery<'q>: DbQuery<()>;
+
+    fn begin(&self) -> Self::Transaction;
+    fn commit<'q>(&'q self, tx: Self::Transaction) -> Self::CommitQuery<'q>;
+}
+
 pub trait RecordDb: Sized + 'static {
     fn record<R: Record>(&self) -> DbRecordManager<'_, Self, R> {
         DbRecordManager {
@@ -38,6 +209,38 @@ pub trait RecordDb: Sized + 'static {
             _marker: std::marker::PhantomData,
         }
     }
+
+    /// Queries made with `tx` are committed together once `steps` returns `Ok`
+    /// Nothing is written when `steps` returns an error, and the whole transaction is rolled
+    /// back when any query fails, such as an update of a missing record
+    /// ```rust,ignore
+    /// db.transaction(|tx| async move {
+    ///     tx.record::<PersonId>().create(person).execute().await?;
+    ///     // Backends queuing queries, such as SurrealDB, always return `Ok(())` here and
+    ///     // report a missing record by failing the whole transaction instead
+    ///     tx.record::<ColorId>().merge(color_id, patch).execute().await??;
+    ///     Ok(())
+    /// })
+    /// .await?;
+    /// ```
+    fn transaction<F, Fut, O>(
+        &self,
+        steps: F,
+    ) -> impl Future<Output = Result<O, WokUnknownError>> + Send
+    where
+        Self: DbTransaction + Sync,
+        F: FnOnce(Self::Transaction) -> Fut + Send,
+        Fut: Future<Output = Result<O, WokUnknownError>> + Send,
+        O: Send,
+    {
+        async move {
+            let tx = self.begin();
+            let output = steps(tx.clone()).await?;
+            self.commit(tx).execute().await?;
+
+            Ok(output)
+        }
+    }
 }
 
 pub struct DbRecordManager<'db, Db, R: Record> {
@@ -69,6 +272,47 @@ impl<'db, Db, R: Record> DbRecordManager<'db, Db, R> {
         self.db.list(self.table)
     }
 
+    pub fn list_page<D>(self, params: ListParams<R>) -> Db::ListPageQuery<'db>
+    where
+        Db: DbListPage<R, D>,
+    {
+        self.db.list_page(self.table, params)
+    }
+
+    /// Lists the records matching a filtered and ordered query, see `query::Field`
+    pub fn query<D>(self) -> RecordQuery<'db, Db, R, D>
+    where
+        Db: DbListPage<R, D>,
+    {
+        RecordQuery {
+            table: self.table,
+            db: self.db,
+            params: ListParams::default(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn update<D>(self, id: R, data: D) -> Db::UpdateQuery<'db>
+    where
+        Db: DbUpdate<R, D>,
+    {
+        self.db.update(self.table, id, data)
+    }
+
+    pub fn merge<D>(self, id: R, data: D) -> Db::MergeQuery<'db>
+    where
+        Db: DbMerge<R, D>,
+    {
+        self.db.merge(self.table, id, data)
+    }
+
+    pub fn upsert<D>(self, id: R, data: D) -> Db::UpsertQuery<'db>
+    where
+        Db: DbUpsert<R, D>,
+    {
+        self.db.upsert(self.table, id, data)
+    }
+
     pub fn delete(self, id: R) -> Db::DeleteQuery<'db>
     where
         Db: DbDelete<R>,
@@ -76,10 +320,54 @@ impl<'db, Db, R: Record> DbRecordManager<'db, Db, R> {
         self.db.delete(self.table, id)
     }
 
-    pub fn select(self, id: R) -> Db::SelectQuery<'db>
+    pub fn select<D>(self, id: R) -> Db::SelectQuery<'db>
     where
-        Db: DbSelectSingle<R, R>,
+        Db: DbSelectSingle<R, D>,
     {
         self.db.select(self.table, id)
     }
 }
+
+pub struct RecordQuery<'db, Db, R, D> {
+    table: &'static str,
+    db: &'db Db,
+    params: ListParams<R>,
+    _marker: std::marker::PhantomData<fn() -> D>,
+}
+
+impl<'db, Db, R, D> RecordQuery<'db, Db, R, D> {
+    pub fn filter(mut self, condition: Condition) -> Self {
+        self.params = self.params.filter(condition);
+        self
+    }
+
+    pub fn order_by(mut self, order: Order) -> Self {
+        self.params = self.params.order_by(order);
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.params = self.params.limit(limit);
+        self
+    }
+
+    pub fn start(mut self, start: usize) -> Self {
+        self.params = self.params.start(start);
+        self
+    }
+
+    pub fn after(mut self, id: R) -> Self {
+        self.params = self.params.after(id);
+        self
+    }
+}
+
+impl<'db, Db, R, D> DbQuery<Vec<D>> for RecordQuery<'db, Db, R, D>
+where
+    Db: DbListPage<R, D> + Sync,
+    R: Send,
+{
+    async fn execute(self) -> Result<Vec<D>, WokUnknownError> {
+        self.db.list_page(self.table, self.params).execute().await
+    }
+}
@@ -1,3 +1,6 @@
+// Lets the derives name `::wok_db` from within this crate
+extern crate self as wok_db;
+
 pub trait Record: Send + Sync + 'static + Clone + Copy {
     const TABLE: &'static str;
 }
@@ -6,7 +9,7 @@ pub trait RecordGenerate: Record {
     fn generate() -> Self;
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct RecordEntry<R, D> {
     pub id: R,
     #[serde(flatten)]
@@ -14,14 +17,43 @@ pub struct RecordEntry<R, D> {
 }
 
 pub mod db;
+#[cfg(feature = "memory")]
+pub mod memory;
+pub mod migrate;
+pub mod query;
+#[cfg(feature = "sqlx")]
+pub mod sqlx;
 #[cfg(feature = "surrealdb")]
 pub mod surrealdb;
 
 pub mod id_strategy {
+    use crate::{Record, RecordEntry, RecordGenerate};
+
     pub trait IdStrategy<I>: Sized + Send + Sync + 'static {
         type Wrap<D>;
         fn wrap<D>(body: D) -> Self::Wrap<D>;
     }
 
+    /// Ids are generated before creating the record, see `RecordGenerate`
     pub struct GenerateId;
+
+    impl<R: RecordGenerate> IdStrategy<R> for GenerateId {
+        type Wrap<D> = RecordEntry<R, D>;
+        fn wrap<D>(body: D) -> Self::Wrap<D> {
+            RecordEntry {
+                id: R::generate(),
+                data: body,
+            }
+        }
+    }
+
+    /// Ids are assigned by the db on create, such as a sequence
+    pub struct DbGenerated;
+
+    impl<R: Record> IdStrategy<R> for DbGenerated {
+        type Wrap<D> = D;
+        fn wrap<D>(body: D) -> Self::Wrap<D> {
+            body
+        }
+    }
 }
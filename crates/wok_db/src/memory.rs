@@ -0,0 +1,752 @@
+//! In memory backend for tests and prototyping, records are kept as JSON objects
+//! Records are keyed by their serialized id, data without an `id` field gets the next integer of
+//! its table, as with `DbGenerated`
+//! ```rust,ignore
+//! App::default()
+//!     .insert_resource(MemoryDb::default())
+//!     .add_plugin(
+//!         CRUDCfgBuilder::default()
+//!             .db::<MemoryDb>()
+//!             .id::<GenerateId>()
+//!             .for_record::<PersonId>()
+//!             .create_one::<Person>(),
+//!     );
+//! ```
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use wok::prelude::WokUnknownError;
+
+use crate::{
+    Record,
+    db::{
+        DbCreate, DbDelete, DbDeleteError, DbList, DbListPage, DbMerge, DbQuery, DbSelectSingle,
+        DbTransaction, DbUpdate, DbUpdateError, DbUpsert, DbUpsertError, FilterValue, ListParams,
+        RecordDb, Upserted,
+    },
+    query::{CompareOp, Condition, Direction, Order},
+};
+
+const ID_FIELD: &str = "id";
+
+/// Id of a stored record, ordered as `compare` does
+#[derive(Debug, Clone)]
+struct Key(Value);
+
+impl PartialEq for Key {
+    fn eq(&self, other: &Self) -> bool {
+        compare(&self.0, &other.0).is_eq()
+    }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Key {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(&self.0, &other.0)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Table {
+    rows: BTreeMap<Key, Value>,
+    /// Last integer id, generated or not
+    sequence: i64,
+}
+
+impl Table {
+    fn insert(&mut self, key: Key, row: Value) {
+        if let Some(id) = key.0.as_i64() {
+            self.sequence = self.sequence.max(id);
+        }
+
+        self.rows.insert(key, row);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Tables {
+    tables: HashMap<&'static str, Table>,
+    /// Bumped on every write, tells whether a transaction is stale
+    version: u64,
+}
+
+/// Records live as long as the resource, clones share them
+/// Transactions work on a copy of every table, committing fails with a `DbUpdateError::Conflict`
+/// when anything was written in between
+#[derive(wok::prelude::Resource, Debug, Clone, Default)]
+pub struct MemoryDb {
+    tables: Arc<RwLock<Tables>>,
+    /// Version of the db this transaction copied
+    forked_at: Option<u64>,
+}
+
+impl MemoryDb {
+    fn read<O>(&self, read: impl FnOnce(&Tables) -> O) -> O {
+        let tables = self.tables.read().unwrap_or_else(|err| err.into_inner());
+        read(&tables)
+    }
+
+    fn write<O>(&self, write: impl FnOnce(&mut Tables) -> O) -> O {
+        let mut tables = self.tables.write().unwrap_or_else(|err| err.into_inner());
+        tables.version += 1;
+        write(&mut tables)
+    }
+
+    fn query<'db, O>(
+        &'db self,
+        run: impl FnOnce(&MemoryDb) -> Result<O, WokUnknownError> + Send + 'db,
+    ) -> MemoryQuery<'db, O> {
+        MemoryQuery(Box::new(move || run(self)))
+    }
+}
+
+impl RecordDb for MemoryDb {}
+
+/// Runs on `execute`, every query holds the lock for its whole duration
+pub struct MemoryQuery<'db, O>(Box<dyn FnOnce() -> Result<O, WokUnknownError> + Send + 'db>);
+
+impl<'db, O: Send> DbQuery<O> for MemoryQuery<'db, O> {
+    async fn execute(self) -> Result<O, WokUnknownError> {
+        (self.0)()
+    }
+}
+
+fn key_of<R: Serialize>(id: &R) -> Result<Key, WokUnknownError> {
+    Ok(Key(serde_json::to_value(id)?))
+}
+
+/// Serializes the record data, which must be a map
+fn object_of<D: Serialize>(data: &D) -> Result<Map<String, Value>, WokUnknownError> {
+    match serde_json::to_value(data)? {
+        Value::Object(object) => Ok(object),
+        _ => Err(WokUnknownError::from_message(
+            "memory records must serialize to a map",
+        )),
+    }
+}
+
+fn row_of(key: &Key, mut object: Map<String, Value>) -> Value {
+    object.insert(ID_FIELD.to_owned(), key.0.clone());
+    Value::Object(object)
+}
+
+fn from_row<D: DeserializeOwned>(row: &Value) -> Result<D, WokUnknownError> {
+    Ok(D::deserialize(row)?)
+}
+
+/// Fields absent from the patch are kept, nested maps are merged too
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (field, value) in patch {
+                match target.get_mut(&field) {
+                    Some(current) => merge(current, value),
+                    None => {
+                        target.insert(field, value);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// Total order over every value, values of different types are ordered by type
+fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => {
+                let (a, b) = (
+                    a.as_f64().unwrap_or(f64::NAN),
+                    b.as_f64().unwrap_or(f64::NAN),
+                );
+                a.total_cmp(&b)
+            }
+        },
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Value::Object(a), Value::Object(b)) => a
+            .iter()
+            .zip(b)
+            .map(|((a_field, a), (b_field, b))| a_field.cmp(b_field).then_with(|| compare(a, b)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Missing fields are null, `address.city` looks into nested maps
+fn lookup<'v>(row: &'v Value, path: &str) -> &'v Value {
+    static NULL: Value = Value::Null;
+
+    path.split('.')
+        .try_fold(row, |value, field| value.get(field))
+        .unwrap_or(&NULL)
+}
+
+fn json_of(value: &FilterValue) -> Value {
+    match value {
+        FilterValue::Null => Value::Null,
+        FilterValue::Bool(value) => Value::Bool(*value),
+        FilterValue::Int(value) => Value::from(*value),
+        FilterValue::Float(value) => Value::from(*value),
+        FilterValue::String(value) => Value::String(value.clone()),
+        FilterValue::List(values) => Value::Array(values.iter().map(json_of).collect()),
+    }
+}
+
+fn matches(condition: &Condition, row: &Value) -> bool {
+    match condition {
+        Condition::Compare { field, op, value } => {
+            let (field, value) = (lookup(row, field), json_of(value));
+            let ordering = compare(field, &value);
+            // Values of different types are neither lower nor greater
+            let comparable = std::mem::discriminant(field) == std::mem::discriminant(&value);
+
+            match op {
+                CompareOp::Eq => ordering.is_eq(),
+                CompareOp::Ne => ordering.is_ne(),
+                CompareOp::Lt => comparable && ordering.is_lt(),
+                CompareOp::Le => comparable && ordering.is_le(),
+                CompareOp::Gt => comparable && ordering.is_gt(),
+                CompareOp::Ge => comparable && ordering.is_ge(),
+            }
+        }
+        Condition::Contains { field, value } => match (lookup(row, field), value) {
+            (Value::String(text), FilterValue::String(part)) => text.contains(part.as_str()),
+            (Value::Array(items), value) => {
+                let value = json_of(value);
+                items.iter().any(|item| compare(item, &value).is_eq())
+            }
+            _ => false,
+        },
+        Condition::In { field, values } => {
+            let field = lookup(row, field);
+            values
+                .iter()
+                .any(|value| compare(field, &json_of(value)).is_eq())
+        }
+        Condition::And(conditions) => conditions.iter().all(|condition| matches(condition, row)),
+        Condition::Or(conditions) => conditions.iter().any(|condition| matches(condition, row)),
+        Condition::Not(condition) => !matches(condition, row),
+    }
+}
+
+fn compare_rows(order: &[Order], a: &Value, b: &Value) -> Ordering {
+    order
+        .iter()
+        .map(|order| {
+            let ordering = compare(lookup(a, &order.field), lookup(b, &order.field));
+            match order.direction {
+                Direction::Asc => ordering,
+                Direction::Desc => ordering.reverse(),
+            }
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Data with an `id` field keeps it, such as a `RecordEntry`, any other gets the next integer
+/// Creating a record with a taken id fails with a `DbUpdateError::Conflict`
+impl<R, D> DbCreate<R, D> for MemoryDb
+where
+    R: Record + DeserializeOwned,
+    D: Serialize,
+{
+    type CreateQuery<'q> = MemoryQuery<'q, R>;
+
+    fn create<'q>(&'q self, table: &'static str, data: D) -> Self::CreateQuery<'q> {
+        let object = object_of(&data);
+
+        self.query(move |db| {
+            let mut object = object?;
+            let given = object.remove(ID_FIELD).map(Key);
+
+            let key = db.write(|tables| {
+                let table = tables.tables.entry(table).or_default();
+                let key = given.unwrap_or_else(|| Key(Value::from(table.sequence + 1)));
+
+                if table.rows.contains_key(&key) {
+                    return Err(WokUnknownError::new(DbUpdateError::Conflict).with_status(409));
+                }
+
+                table.insert(key.clone(), row_of(&key, object));
+                Ok(key)
+            })?;
+
+            Ok(R::deserialize(key.0)?)
+        })
+    }
+}
+
+impl<D> DbList<D> for MemoryDb
+where
+    D: DeserializeOwned + Send + 'static,
+{
+    type ListQuery<'q> = MemoryQuery<'q, Vec<D>>;
+
+    fn list<'q>(&'q self, table: &'static str) -> Self::ListQuery<'q> {
+        self.query(move |db| {
+            db.read(|tables| {
+                let Some(table) = tables.tables.get(table) else {
+                    return Ok(Vec::new());
+                };
+
+                table.rows.values().map(from_row).collect()
+            })
+        })
+    }
+}
+
+impl<R, D> DbListPage<R, D> for MemoryDb
+where
+    R: Record + Serialize,
+    D: DeserializeOwned + Send + 'static,
+{
+    type ListPageQuery<'q> = MemoryQuery<'q, Vec<D>>;
+
+    fn list_page<'q>(
+        &'q self,
+        table: &'static str,
+        params: ListParams<R>,
+    ) -> Self::ListPageQuery<'q> {
+        self.query(move |db| {
+            params.check_cursor(ID_FIELD)?;
+            let after = params.after.as_ref().map(key_of).transpose()?;
+
+            db.read(|tables| {
+                let Some(table) = tables.tables.get(table) else {
+                    return Ok(Vec::new());
+                };
+
+                let mut rows: Vec<_> = table
+                    .rows
+                    .iter()
+                    .filter(|(key, _)| after.as_ref().is_none_or(|after| *key > after))
+                    .map(|(_, row)| row)
+                    .filter(|row| params.filters.iter().all(|filter| matches(filter, row)))
+                    .collect();
+
+                // Stable, so ties stay ordered by id
+                rows.sort_by(|a, b| compare_rows(&params.order, a, b));
+
+                rows.into_iter()
+                    .skip(params.start.unwrap_or_default())
+                    .take(params.limit.unwrap_or(usize::MAX))
+                    .map(from_row)
+                    .collect()
+            })
+        })
+    }
+}
+
+impl<R, D> DbSelectSingle<R, D> for MemoryDb
+where
+    R: Record + Serialize,
+    D: DeserializeOwned + Send + 'static,
+{
+    type SelectQuery<'q> = MemoryQuery<'q, Option<D>>;
+
+    fn select<'q>(&'q self, table: &'static str, id: R) -> Self::SelectQuery<'q> {
+        self.query(move |db| {
+            let key = key_of(&id)?;
+
+            db.read(|tables| {
+                tables
+                    .tables
+                    .get(table)
+                    .and_then(|table| table.rows.get(&key))
+                    .map(from_row)
+                    .transpose()
+            })
+        })
+    }
+}
+
+impl<R, D> DbUpdate<R, D> for MemoryDb
+where
+    R: Record + Serialize,
+    D: Serialize,
+{
+    type UpdateQuery<'q> = MemoryQuery<'q, Result<(), DbUpdateError>>;
+
+    fn update<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpdateQuery<'q> {
+        let object = object_of(&data);
+
+        self.query(move |db| {
+            let (key, object) = (key_of(&id)?, object?);
+
+            Ok(db.write(|tables| {
+                let table = tables.tables.entry(table).or_default();
+                let Some(row) = table.rows.get_mut(&key) else {
+                    return Err(DbUpdateError::NotFound);
+                };
+
+                *row = row_of(&key, object);
+                Ok(())
+            }))
+        })
+    }
+}
+
+impl<R, D> DbMerge<R, D> for MemoryDb
+where
+    R: Record + Serialize,
+    D: Serialize,
+{
+    type MergeQuery<'q> = MemoryQuery<'q, Result<(), DbUpdateError>>;
+
+    fn merge<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::MergeQuery<'q> {
+        let object = object_of(&data);
+
+        self.query(move |db| {
+            let (key, mut object) = (key_of(&id)?, object?);
+            object.remove(ID_FIELD);
+
+            Ok(db.write(|tables| {
+                let table = tables.tables.entry(table).or_default();
+                let Some(row) = table.rows.get_mut(&key) else {
+                    return Err(DbUpdateError::NotFound);
+                };
+
+                merge(row, Value::Object(object));
+                Ok(())
+            }))
+        })
+    }
+}
+
+impl<R, D> DbUpsert<R, D> for MemoryDb
+where
+    R: Record + Serialize,
+    D: Serialize,
+{
+    type UpsertQuery<'q> = MemoryQuery<'q, Result<Upserted, DbUpsertError>>;
+
+    fn upsert<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpsertQuery<'q> {
+        let object = object_of(&data);
+
+        self.query(move |db| {
+            let (key, object) = (key_of(&id)?, object?);
+
+            Ok(db.write(|tables| {
+                let table = tables.tables.entry(table).or_default();
+                let upserted = match table.rows.contains_key(&key) {
+                    true => Upserted::Updated,
+                    false => Upserted::Created,
+                };
+
+                table.insert(key.clone(), row_of(&key, object));
+                Ok(upserted)
+            }))
+        })
+    }
+}
+
+impl<R> DbDelete<R> for MemoryDb
+where
+    R: Record + Serialize,
+{
+    type DeleteQuery<'q> = MemoryQuery<'q, Result<(), DbDeleteError>>;
+
+    fn delete<'q>(&'q self, table: &'static str, id: R) -> Self::DeleteQuery<'q> {
+        self.query(move |db| {
+            let key = key_of(&id)?;
+
+            Ok(db.write(|tables| {
+                tables
+                    .tables
+                    .get_mut(table)
+                    .and_then(|table| table.rows.remove(&key))
+                    .map(|_| ())
+                    .ok_or(DbDeleteError::None)
+            }))
+        })
+    }
+}
+
+/// Transactions are `MemoryDb` copies, their queries run right away but stay out of the
+/// original until committed
+impl DbTransaction for MemoryDb {
+    type Transaction = MemoryDb;
+    type CommitQuery<'q> = MemoryQuery<'q, ()>;
+
+    fn begin(&self) -> Self::Transaction {
+        let tables = self.read(Tables::clone);
+
+        MemoryDb {
+            forked_at: Some(tables.version),
+            tables: Arc::new(RwLock::new(tables)),
+        }
+    }
+
+    fn commit<'q>(&'q self, tx: Self::Transaction) -> Self::CommitQuery<'q> {
+        self.query(move |db| {
+            let forked_at = tx
+                .forked_at
+                .filter(|_| !Arc::ptr_eq(&tx.tables, &db.tables));
+            let Some(forked_at) = forked_at else {
+                return Err(WokUnknownError::from_message(
+                    "only transactions of this db can be committed",
+                ));
+            };
+
+            let mut tables = db.tables.write().unwrap_or_else(|err| err.into_inner());
+            if tables.version != forked_at {
+                return Err(WokUnknownError::new(DbUpdateError::Conflict).with_status(409));
+            }
+
+            let version = tables.version + 1;
+            *tables = tx.read(Tables::clone);
+            tables.version = version;
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Record, RecordEntry,
+        db::{DbQuery, DbUpdateError, RecordDb, Upserted},
+        query::Field,
+    };
+
+    use super::MemoryDb;
+
+    #[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct PersonId(i64);
+
+    impl Record for PersonId {
+        const TABLE: &'static str = "person";
+    }
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Person {
+        name: String,
+        age: Option<i32>,
+    }
+
+    impl Person {
+        const fn name() -> Field<Self, String> {
+            Field::new("name")
+        }
+
+        const fn age() -> Field<Self, Option<i32>> {
+            Field::new("age")
+        }
+    }
+
+    #[derive(serde::Serialize)]
+    struct PersonPatch {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        age: Option<i32>,
+    }
+
+    fn person(name: &str, age: Option<i32>) -> Person {
+        Person {
+            name: name.to_owned(),
+            age,
+        }
+    }
+
+    #[tokio::test]
+    async fn creates_and_queries_records() {
+        let db = MemoryDb::default();
+
+        let ana = db
+            .record::<PersonId>()
+            .create(person("Ana", Some(31)))
+            .execute()
+            .await
+            .unwrap();
+        db.record::<PersonId>()
+            .create(person("Bob", None))
+            .execute()
+            .await
+            .unwrap();
+        let bea = db
+            .record::<PersonId>()
+            .create(RecordEntry {
+                id: PersonId(10),
+                data: person("Bea", Some(20)),
+            })
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!((ana, bea), (PersonId(1), PersonId(10)));
+
+        let taken = db
+            .record::<PersonId>()
+            .create(RecordEntry {
+                id: bea,
+                data: person("Bea", None),
+            })
+            .execute()
+            .await;
+        assert!(taken.is_err());
+
+        let found: Option<Person> = db.record::<PersonId>().select(ana).execute().await.unwrap();
+        assert_eq!(found, Some(person("Ana", Some(31))));
+
+        let page: Vec<RecordEntry<PersonId, Person>> = db
+            .record::<PersonId>()
+            .query()
+            .filter(Person::age().is_null().or(Person::age().gt(Some(25))))
+            .order_by(Person::name().desc())
+            .limit(1)
+            .start(1)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, ana);
+
+        let after: Vec<Person> = db
+            .record::<PersonId>()
+            .query()
+            .filter(
+                Person::name()
+                    .contains("b")
+                    .or(Person::name().contains("B")),
+            )
+            .after(ana)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(after, vec![person("Bob", None), person("Bea", Some(20))]);
+
+        let named: Vec<Person> = db
+            .record::<PersonId>()
+            .query()
+            .filter(Person::name().is_in(["Ana".to_owned(), "Bea".to_owned()]))
+            .order_by(Field::<Person, i64>::new("id").asc())
+            .after(ana)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(named, vec![person("Bea", Some(20))]);
+
+        let unordered: Result<Vec<Person>, _> = db
+            .record::<PersonId>()
+            .query()
+            .order_by(Person::name().asc())
+            .after(ana)
+            .execute()
+            .await;
+        assert!(unordered.is_err());
+    }
+
+    #[tokio::test]
+    async fn updates_upserts_and_deletes() {
+        let db = MemoryDb::default();
+
+        let ana = db
+            .record::<PersonId>()
+            .create(person("Ana", None))
+            .execute()
+            .await
+            .unwrap();
+
+        let merged = db
+            .record::<PersonId>()
+            .merge(ana, PersonPatch { age: Some(32) });
+        assert_eq!(merged.execute().await.unwrap(), Ok(()));
+
+        let missing = db
+            .record::<PersonId>()
+            .update(PersonId(99), person("Nobody", None));
+        assert_eq!(
+            missing.execute().await.unwrap(),
+            Err(DbUpdateError::NotFound)
+        );
+
+        let upserted = db
+            .record::<PersonId>()
+            .upsert(PersonId(7), person("Cid", None));
+        assert_eq!(upserted.execute().await.unwrap(), Ok(Upserted::Created));
+
+        let upserted = db
+            .record::<PersonId>()
+            .upsert(PersonId(7), person("Cid", Some(40)));
+        assert_eq!(upserted.execute().await.unwrap(), Ok(Upserted::Updated));
+
+        let deleted = db.record::<PersonId>().delete(PersonId(7));
+        assert!(deleted.execute().await.unwrap().is_ok());
+        let deleted = db.record::<PersonId>().delete(PersonId(7));
+        assert!(deleted.execute().await.unwrap().is_err());
+
+        let left: Vec<RecordEntry<PersonId, Person>> =
+            db.record::<PersonId>().list().execute().await.unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!((left[0].id, left[0].data.age), (ana, Some(32)));
+    }
+
+    #[tokio::test]
+    async fn commits_transactions_atomically() {
+        let db = MemoryDb::default();
+
+        let failed = db
+            .transaction(|tx| async move {
+                tx.record::<PersonId>()
+                    .create(person("Ana", None))
+                    .execute()
+                    .await?;
+                tx.record::<PersonId>()
+                    .update(PersonId(99), person("Nobody", None))
+                    .execute()
+                    .await??;
+                Ok(())
+            })
+            .await;
+        assert!(failed.is_err());
+
+        let people: Vec<Person> = db.record::<PersonId>().list().execute().await.unwrap();
+        assert!(people.is_empty());
+
+        let ana = db
+            .transaction(|tx| async move {
+                tx.record::<PersonId>()
+                    .create(person("Ana", None))
+                    .execute()
+                    .await
+            })
+            .await
+            .unwrap();
+
+        let found: Option<Person> = db.record::<PersonId>().select(ana).execute().await.unwrap();
+        assert_eq!(found, Some(person("Ana", None)));
+    }
+}
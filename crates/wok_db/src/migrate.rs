@@ -0,0 +1,625 @@
+//! Versioned schema migrations, applied in version order and recorded in the
+//! `MIGRATIONS_TABLE` table of the db along with a checksum of their script
+//! Migrations are either embedded or read from a directory of `<version>_<name>.up.<ext>` and
+//! `<version>_<name>.down.<ext>` files, the down script is optional
+//! ```rust,ignore
+//! let migrations = Migrations::new()
+//!     .with_migration(
+//!         Migration::new(1, "create_person", include_str!("../migrations/1_create_person.up.sql"))
+//!             .down(include_str!("../migrations/1_create_person.down.sql")),
+//!     );
+//!
+//! App::default()
+//!     .add_plugin(SqlxPoolPlugin::default())
+//!     // Added after the db plugin, startup systems added later wait for the migrations
+//!     .add_plugin(MigratePlugin::<SqlDb>::new(migrations));
+//! ```
+
+use std::{borrow::Cow, collections::BTreeMap, fmt::Display, path::Path};
+
+use wok::prelude::{Commands, Res, ResTake, Resource, WokUnknownError};
+
+/// Table recording the applied migrations
+pub const MIGRATIONS_TABLE: &str = "_wok_migrations";
+
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: i64,
+    pub name: Cow<'static, str>,
+    pub up: Cow<'static, str>,
+    /// Migrations without a down script cannot be reverted
+    pub down: Option<Cow<'static, str>>,
+}
+
+impl Migration {
+    pub fn new(
+        version: i64,
+        name: impl Into<Cow<'static, str>>,
+        up: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Migration {
+            version,
+            name: name.into(),
+            up: up.into(),
+            down: None,
+        }
+    }
+
+    pub fn down(mut self, down: impl Into<Cow<'static, str>>) -> Self {
+        self.down = Some(down.into());
+        self
+    }
+
+    /// FNV-1a hash of the up script, only meant to notice edits of applied migrations
+    pub fn checksum(&self) -> String {
+        let hash = self.up.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+        });
+
+        format!("{hash:016x}")
+    }
+}
+
+/// Migration recorded by the db
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub checksum: String,
+}
+
+/// Backends running migration scripts, each one runs in a transaction along with its record
+/// Scripts must not begin or commit transactions themselves
+pub trait DbMigrate: 'static {
+    /// Creates the migrations table when missing
+    fn applied(
+        &self,
+    ) -> impl Future<Output = Result<Vec<AppliedMigration>, WokUnknownError>> + Send;
+
+    fn apply(
+        &self,
+        migration: &Migration,
+    ) -> impl Future<Output = Result<(), WokUnknownError>> + Send;
+
+    /// Runs the down script, the migration must have one
+    fn revert(
+        &self,
+        migration: &Migration,
+    ) -> impl Future<Output = Result<(), WokUnknownError>> + Send;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MigrationError {
+    #[error("migration {0} was edited after being applied")]
+    Changed(i64),
+    #[error("migration {0} was applied but is missing")]
+    Missing(i64),
+    #[error("migration {0} has no down script")]
+    Irreversible(i64),
+    #[error("migration version {0} is used more than once")]
+    Duplicate(i64),
+    #[error("invalid migration file `{0}`, expected `<version>_<name>.up.<ext>`")]
+    InvalidFileName(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Pending,
+    Applied,
+    /// Applied with another script, see `Migration::checksum`
+    Changed,
+    /// Applied but no longer known
+    Missing,
+}
+
+impl Display for MigrationState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MigrationState::Pending => "pending",
+            MigrationState::Applied => "applied",
+            MigrationState::Changed => "changed",
+            MigrationState::Missing => "missing",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+}
+
+/// Known migrations, ordered by version
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Migrations(BTreeMap<i64, Migration>);
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// # Panics
+    /// When another migration has the same version
+    pub fn with_migration(mut self, migration: Migration) -> Self {
+        if let Err(err) = self.insert(migration) {
+            panic!("{err}");
+        }
+
+        self
+    }
+
+    fn insert(&mut self, migration: Migration) -> Result<(), MigrationError> {
+        let version = migration.version;
+        if self.0.insert(version, migration).is_some() {
+            return Err(MigrationError::Duplicate(version));
+        }
+
+        Ok(())
+    }
+
+    /// Reads the `<version>_<name>.up.<ext>` and `<version>_<name>.down.<ext>` files of the
+    /// directory, files without `.up` nor `.down` are up scripts
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, WokUnknownError> {
+        let mut ups = BTreeMap::new();
+        let mut downs = BTreeMap::new();
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if hidden || !path.is_file() {
+                continue;
+            }
+
+            let file_name = path.to_string_lossy().into_owned();
+            let invalid = || MigrationError::InvalidFileName(file_name.clone());
+
+            let stem = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .ok_or_else(invalid)?;
+            let (stem, is_down) = match stem.rsplit_once('.') {
+                Some((stem, "up")) => (stem, false),
+                Some((stem, "down")) => (stem, true),
+                Some(_) => return Err(invalid().into()),
+                None => (stem, false),
+            };
+
+            let (version, name) = stem.split_once('_').ok_or_else(invalid)?;
+            let version: i64 = version.parse().map_err(|_| invalid())?;
+            let script = std::fs::read_to_string(&path)?;
+
+            let scripts = if is_down { &mut downs } else { &mut ups };
+            if scripts
+                .insert(version, (name.to_owned(), script, file_name.clone()))
+                .is_some()
+            {
+                return Err(MigrationError::Duplicate(version).into());
+            }
+        }
+
+        let mut migrations = Migrations::new();
+        for (version, (name, up, _)) in ups {
+            let mut migration = Migration::new(version, name, up);
+            migration.down = downs.remove(&version).map(|(_, down, _)| down.into());
+            migrations.insert(migration)?;
+        }
+
+        // Down scripts without an up script are likely misnamed
+        if let Some((_, (_, _, file_name))) = downs.into_iter().next() {
+            return Err(MigrationError::InvalidFileName(file_name).into());
+        }
+
+        Ok(migrations)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Migration> {
+        self.0.values()
+    }
+
+    /// Every known and applied migration, ordered by version
+    pub async fn status<Db: DbMigrate + Sync>(
+        &self,
+        db: &Db,
+    ) -> Result<Vec<MigrationStatus>, WokUnknownError> {
+        let mut applied: BTreeMap<_, _> = db
+            .applied()
+            .await?
+            .into_iter()
+            .map(|applied| (applied.version, applied))
+            .collect();
+
+        let mut status: Vec<_> = self
+            .iter()
+            .map(|migration| {
+                let state = match applied.remove(&migration.version) {
+                    None => MigrationState::Pending,
+                    Some(applied) if applied.checksum != migration.checksum() => {
+                        MigrationState::Changed
+                    }
+                    Some(_) => MigrationState::Applied,
+                };
+
+                MigrationStatus {
+                    version: migration.version,
+                    name: migration.name.clone().into_owned(),
+                    state,
+                }
+            })
+            .collect();
+
+        status.extend(applied.into_values().map(|applied| MigrationStatus {
+            version: applied.version,
+            name: applied.name,
+            state: MigrationState::Missing,
+        }));
+        status.sort_by_key(|status| status.version);
+
+        Ok(status)
+    }
+
+    /// Applies the pending migrations up to the `to` version, returns the applied ones
+    /// Fails before applying anything when an applied migration was edited or is missing
+    pub async fn up<Db: DbMigrate + Sync>(
+        &self,
+        db: &Db,
+        to: Option<i64>,
+    ) -> Result<Vec<&Migration>, WokUnknownError> {
+        let status = self.status(db).await?;
+        check_unchanged(&status)?;
+
+        let mut applied = Vec::new();
+        for status in status {
+            if status.state != MigrationState::Pending || to.is_some_and(|to| status.version > to) {
+                continue;
+            }
+
+            let migration = &self.0[&status.version];
+            db.apply(migration).await?;
+            tracing::info!(
+                "Applied migration {} `{}`",
+                migration.version,
+                migration.name
+            );
+
+            applied.push(migration);
+        }
+
+        Ok(applied)
+    }
+
+    /// Reverts the last `steps` applied migrations, returns the reverted ones
+    pub async fn down<Db: DbMigrate + Sync>(
+        &self,
+        db: &Db,
+        steps: usize,
+    ) -> Result<Vec<&Migration>, WokUnknownError> {
+        let status = self.status(db).await?;
+        check_unchanged(&status)?;
+
+        let targets: Vec<_> = status
+            .iter()
+            .rev()
+            .filter(|status| status.state == MigrationState::Applied)
+            .take(steps)
+            .map(|status| &self.0[&status.version])
+            .collect();
+
+        // Checked first so a partial revert does not leave the db halfway
+        if let Some(migration) = targets.iter().find(|migration| migration.down.is_none()) {
+            return Err(MigrationError::Irreversible(migration.version).into());
+        }
+
+        let mut reverted = Vec::new();
+        for migration in targets {
+            db.revert(migration).await?;
+            tracing::info!(
+                "Reverted migration {} `{}`",
+                migration.version,
+                migration.name
+            );
+
+            reverted.push(migration);
+        }
+
+        Ok(reverted)
+    }
+}
+
+fn check_unchanged(status: &[MigrationStatus]) -> Result<(), MigrationError> {
+    for status in status {
+        match status.state {
+            MigrationState::Changed => return Err(MigrationError::Changed(status.version)),
+            MigrationState::Missing => return Err(MigrationError::Missing(status.version)),
+            MigrationState::Pending | MigrationState::Applied => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Inserts the `Migrations` resource and applies them on startup
+/// The db is taken while migrating, so startup systems using it that are added after this
+/// plugin wait for the migrations. It must be added after the plugin connecting the db, and
+/// panics when a startup system using the db was added before it
+pub struct MigratePlugin<Db> {
+    migrations: Migrations,
+    on_startup: bool,
+    _marker: std::marker::PhantomData<fn(Db)>,
+}
+
+impl<Db> MigratePlugin<Db> {
+    pub fn new(migrations: Migrations) -> Self {
+        MigratePlugin {
+            migrations,
+            on_startup: true,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Only inserts the `Migrations` resource, such as when they are applied from a command
+    pub fn manual(mut self) -> Self {
+        self.on_startup = false;
+        self
+    }
+}
+
+impl<Db: DbMigrate + Resource + Sync> wok::prelude::Plugin for MigratePlugin<Db> {
+    fn setup(self, app: &mut wok::prelude::App) {
+        use wok::prelude::ConfigureWorld;
+
+        async fn apply_migrations<Db: DbMigrate + Resource + Sync>(
+            db: ResTake<Db>,
+            migrations: Res<'_, Migrations>,
+            mut commands: Commands<'_>,
+        ) -> Result<(), WokUnknownError> {
+            let db = db.into_inner();
+            let result = migrations.up(&db, None).await.map(|applied| applied.len());
+
+            // Put back once the system finishes, even on failure
+            commands.insert_resource(db);
+
+            if result? == 0 {
+                tracing::info!("Migrations are up to date");
+            }
+
+            Ok(())
+        }
+
+        app.insert_resource(self.migrations);
+
+        if self.on_startup {
+            assert!(
+                !wok::prelude::Startup::has_access::<Db>(app.world_mut()),
+                "`MigratePlugin<{}>` must be added before the startup systems using the db",
+                std::any::type_name::<Db>()
+            );
+
+            app.add_systems(wok::prelude::Startup, apply_migrations::<Db>);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_migrations_dir() {
+        let dir = std::env::temp_dir().join(format!("wok_db_migrations_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let files = [
+            ("2_add_age.sql", "ALTER TABLE person ADD age INTEGER"),
+            ("1_create_person.up.sql", "CREATE TABLE person (name TEXT)"),
+            ("1_create_person.down.sql", "DROP TABLE person"),
+        ];
+        for (name, script) in files {
+            std::fs::write(dir.join(name), script).unwrap();
+        }
+
+        let migrations = Migrations::from_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let migrations: Vec<_> = migrations.unwrap().iter().cloned().collect();
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(
+            (migrations[0].version, migrations[0].name.as_ref()),
+            (1, "create_person")
+        );
+        assert_eq!(migrations[0].down.as_deref(), Some("DROP TABLE person"));
+        assert_eq!(
+            (migrations[1].version, migrations[1].name.as_ref()),
+            (2, "add_age")
+        );
+        assert!(migrations[1].down.is_none());
+    }
+
+    #[test]
+    fn checksums_the_up_script() {
+        let migration = Migration::new(1, "create_person", "CREATE TABLE person (name TEXT)");
+        let edited = Migration::new(1, "create_person", "CREATE TABLE person (name TEXT NULL)");
+
+        assert_eq!(migration.checksum(), migration.clone().down("").checksum());
+        assert_ne!(migration.checksum(), edited.checksum());
+    }
+
+    /// Records migrations in memory, applying and reverting only touch the records
+    #[derive(Resource, Default)]
+    #[resource(mutable = true)]
+    struct FakeDb(std::sync::Mutex<Vec<AppliedMigration>>);
+
+    impl FakeDb {
+        fn versions(&self) -> Vec<i64> {
+            let applied = self.0.lock().unwrap();
+            applied.iter().map(|applied| applied.version).collect()
+        }
+    }
+
+    impl DbMigrate for FakeDb {
+        async fn applied(&self) -> Result<Vec<AppliedMigration>, WokUnknownError> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        async fn apply(&self, migration: &Migration) -> Result<(), WokUnknownError> {
+            self.0.lock().unwrap().push(AppliedMigration {
+                version: migration.version,
+                name: migration.name.clone().into_owned(),
+                checksum: migration.checksum(),
+            });
+            Ok(())
+        }
+
+        async fn revert(&self, migration: &Migration) -> Result<(), WokUnknownError> {
+            let mut applied = self.0.lock().unwrap();
+            applied.retain(|applied| applied.version != migration.version);
+            Ok(())
+        }
+    }
+
+    fn migrations() -> Migrations {
+        let mut migrations = Migrations::new();
+        for (version, down) in [(1, Some("DROP a")), (2, None), (3, Some("DROP c"))] {
+            let mut migration =
+                Migration::new(version, format!("m{version}"), format!("CREATE {version}"));
+            migration.down = down.map(Into::into);
+            migrations.insert(migration).unwrap();
+        }
+        migrations
+    }
+
+    fn states(status: &[MigrationStatus]) -> Vec<(i64, MigrationState)> {
+        status
+            .iter()
+            .map(|status| (status.version, status.state))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn applies_pending_migrations_up_to_a_version() {
+        let db = FakeDb::default();
+        let migrations = migrations();
+
+        let applied = migrations.up(&db, Some(2)).await.unwrap();
+        assert_eq!(
+            applied.iter().map(|m| m.version).collect::<Vec<_>>(),
+            [1, 2]
+        );
+        assert_eq!(
+            states(&migrations.status(&db).await.unwrap()),
+            [
+                (1, MigrationState::Applied),
+                (2, MigrationState::Applied),
+                (3, MigrationState::Pending)
+            ]
+        );
+
+        let applied = migrations.up(&db, None).await.unwrap();
+        assert_eq!(applied.iter().map(|m| m.version).collect::<Vec<_>>(), [3]);
+        assert!(migrations.up(&db, None).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn refuses_changed_or_missing_migrations() {
+        let db = FakeDb::default();
+        migrations().up(&db, Some(2)).await.unwrap();
+
+        let mut edited = migrations();
+        edited.0.get_mut(&1).unwrap().up = "CREATE 1 edited".into();
+        assert_eq!(
+            states(&edited.status(&db).await.unwrap())[0],
+            (1, MigrationState::Changed)
+        );
+        let err = edited.up(&db, None).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(MigrationError::Changed(1))
+        ));
+
+        let mut missing = migrations();
+        missing.0.remove(&2);
+        assert_eq!(
+            states(&missing.status(&db).await.unwrap())[1],
+            (2, MigrationState::Missing)
+        );
+        let err = missing.down(&db, 1).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(MigrationError::Missing(2))
+        ));
+
+        // Nothing past the check was applied
+        assert_eq!(db.versions(), [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn reverts_the_last_steps() {
+        let db = FakeDb::default();
+        let migrations = migrations();
+        migrations.up(&db, None).await.unwrap();
+
+        let reverted = migrations.down(&db, 1).await.unwrap();
+        assert_eq!(reverted.iter().map(|m| m.version).collect::<Vec<_>>(), [3]);
+        assert_eq!(db.versions(), [1, 2]);
+        assert!(matches!(
+            migrations.status(&db).await.unwrap()[2].state,
+            MigrationState::Pending
+        ));
+    }
+
+    #[tokio::test]
+    async fn checks_every_step_is_reversible_before_reverting() {
+        let db = FakeDb::default();
+        let migrations = migrations();
+        migrations.up(&db, None).await.unwrap();
+
+        // Migration 2 has no down script, so 3 must not be reverted either
+        let err = migrations.down(&db, 2).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref(),
+            Some(MigrationError::Irreversible(2))
+        ));
+        assert_eq!(db.versions(), [1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be added before the startup systems using the db")]
+    fn panics_when_the_db_is_read_before_migrating() {
+        use wok::prelude::{App, ConfigureApp, ConfigureWorld, Startup};
+
+        async fn read_db(_db: Res<'_, FakeDb>) {}
+
+        App::default()
+            .insert_resource(FakeDb::default())
+            .add_systems(Startup, read_db)
+            .add_plugin(MigratePlugin::<FakeDb>::new(migrations()));
+    }
+
+    #[test]
+    #[should_panic(expected = "must be added before the startup systems using the db")]
+    fn panics_when_the_db_is_written_before_migrating() {
+        use wok::prelude::{App, ConfigureApp, ConfigureWorld, ResMut, Startup};
+
+        async fn write_db(_db: ResMut<'_, FakeDb>) {}
+
+        App::default()
+            .insert_resource(FakeDb::default())
+            .add_systems(Startup, write_db)
+            .add_plugin(MigratePlugin::<FakeDb>::new(migrations()));
+    }
+
+    #[test]
+    fn allows_connecting_the_db_before_migrating() {
+        use wok::prelude::{App, ConfigureApp, ConfigureWorld, ResInit, Startup};
+
+        async fn connect(mut db: ResInit<'_, FakeDb>) {
+            db.init(FakeDb::default());
+        }
+
+        App::default()
+            .add_systems(Startup, connect)
+            .add_plugin(MigratePlugin::<FakeDb>::new(migrations()));
+    }
+}
@@ -0,0 +1,264 @@
+//! Typed filters and ordering over record fields
+//! Field descriptors are generated by the `FromSurrealBind` derive on a `<Type>Fields` type, one
+//! associated function per named field
+//! ```rust,ignore
+//! let colors: Vec<Color> = db
+//!     .record::<ColorId>()
+//!     .query()
+//!     .filter(ColorFields::name().contains("re").or(ColorFields::value().eq("#ff0000".into())))
+//!     .order_by(ColorFields::name().asc())
+//!     .limit(10)
+//!     .start(20)
+//!     .execute()
+//!     .await?;
+//! ```
+//! Values are always bound as query params, field names are checked by each backend
+
+use std::{borrow::Cow, marker::PhantomData};
+
+use crate::db::FilterValue;
+
+/// Field `V` of the record data `T`
+pub struct Field<T, V> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> (T, V)>,
+}
+
+impl<T, V> Clone for Field<T, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, V> Copy for Field<T, V> {}
+
+impl<T, V> Field<T, V> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn asc(self) -> Order {
+        Order {
+            field: Cow::Borrowed(self.name),
+            direction: Direction::Asc,
+        }
+    }
+
+    pub fn desc(self) -> Order {
+        Order {
+            field: Cow::Borrowed(self.name),
+            direction: Direction::Desc,
+        }
+    }
+
+    pub fn is_null(self) -> Condition {
+        self.compare(CompareOp::Eq, FilterValue::Null)
+    }
+
+    /// String fields containing the text
+    pub fn contains(self, text: impl Into<String>) -> Condition {
+        Condition::Contains {
+            field: Cow::Borrowed(self.name),
+            value: FilterValue::String(text.into()),
+        }
+    }
+
+    fn compare(self, op: CompareOp, value: FilterValue) -> Condition {
+        Condition::Compare {
+            field: Cow::Borrowed(self.name),
+            op,
+            value,
+        }
+    }
+}
+
+/// Comparisons need `V` to convert to a [`FilterValue`], implement `From<V>` for custom scalars
+impl<T, V: Into<FilterValue>> Field<T, V> {
+    pub fn eq(self, value: V) -> Condition {
+        self.compare(CompareOp::Eq, value.into())
+    }
+
+    pub fn ne(self, value: V) -> Condition {
+        self.compare(CompareOp::Ne, value.into())
+    }
+
+    pub fn lt(self, value: V) -> Condition {
+        self.compare(CompareOp::Lt, value.into())
+    }
+
+    pub fn le(self, value: V) -> Condition {
+        self.compare(CompareOp::Le, value.into())
+    }
+
+    pub fn gt(self, value: V) -> Condition {
+        self.compare(CompareOp::Gt, value.into())
+    }
+
+    pub fn ge(self, value: V) -> Condition {
+        self.compare(CompareOp::Ge, value.into())
+    }
+
+    pub fn is_in(self, values: impl IntoIterator<Item = V>) -> Condition {
+        Condition::In {
+            field: Cow::Borrowed(self.name),
+            values: values.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare {
+        field: Cow<'static, str>,
+        op: CompareOp,
+        value: FilterValue,
+    },
+    Contains {
+        field: Cow<'static, str>,
+        value: FilterValue,
+    },
+    In {
+        field: Cow<'static, str>,
+        values: Vec<FilterValue>,
+    },
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    /// Equality on a field only known at runtime, such as a query string param
+    pub fn field_eq(field: impl Into<Cow<'static, str>>, value: FilterValue) -> Self {
+        Condition::Compare {
+            field: field.into(),
+            op: CompareOp::Eq,
+            value,
+        }
+    }
+
+    pub fn and(self, other: Condition) -> Self {
+        match self {
+            Condition::And(mut all) => {
+                all.push(other);
+                Condition::And(all)
+            }
+            this => Condition::And(vec![this, other]),
+        }
+    }
+
+    pub fn or(self, other: Condition) -> Self {
+        match self {
+            Condition::Or(mut any) => {
+                any.push(other);
+                Condition::Or(any)
+            }
+            this => Condition::Or(vec![this, other]),
+        }
+    }
+
+    /// Every field name of the condition
+    pub fn fields(&self) -> Vec<&str> {
+        match self {
+            Condition::Compare { field, .. }
+            | Condition::Contains { field, .. }
+            | Condition::In { field, .. } => vec![field],
+            Condition::And(conditions) | Condition::Or(conditions) => {
+                conditions.iter().flat_map(Condition::fields).collect()
+            }
+            Condition::Not(condition) => condition.fields(),
+        }
+    }
+}
+
+impl std::ops::Not for Condition {
+    type Output = Condition;
+
+    fn not(self) -> Self::Output {
+        Condition::Not(Box::new(self))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    pub field: Cow<'static, str>,
+    pub direction: Direction,
+}
+
+/// Whether the name is a plain field path, `name` or `address.city`
+/// Backends that cannot bind field names only accept these
+pub fn is_field_path(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('.').all(|part| {
+            part.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+macro_rules! filter_value_from {
+    ($variant:ident($inner:ty): $($ty:ty),+) => {
+        $(
+            impl From<$ty> for FilterValue {
+                fn from(value: $ty) -> Self {
+                    FilterValue::$variant(<$inner>::from(value))
+                }
+            }
+        )+
+    };
+}
+
+filter_value_from!(Bool(bool): bool);
+filter_value_from!(Int(i64): i8, i16, i32, i64, u8, u16, u32);
+filter_value_from!(Float(f64): f32, f64);
+filter_value_from!(String(String): char, String, &str);
+
+impl<V: Into<FilterValue>> From<Option<V>> for FilterValue {
+    fn from(value: Option<V>) -> Self {
+        value.map_or(FilterValue::Null, Into::into)
+    }
+}
+
+impl<V: Into<FilterValue>> From<Vec<V>> for FilterValue {
+    fn from(values: Vec<V>) -> Self {
+        FilterValue::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+/// RFC 3339 text, as the SQL backends store it
+#[cfg(feature = "chrono")]
+impl From<chrono::DateTime<chrono::Utc>> for FilterValue {
+    fn from(value: chrono::DateTime<chrono::Utc>) -> Self {
+        FilterValue::String(value.to_rfc3339())
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for FilterValue {
+    fn from(value: uuid::Uuid) -> Self {
+        FilterValue::String(value.to_string())
+    }
+}
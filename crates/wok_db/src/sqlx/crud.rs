@@ -0,0 +1,810 @@
+use sqlx::{
+    Any,
+    any::{AnyArguments, AnyRow},
+    query::Query,
+};
+use wok::prelude::WokUnknownError;
+
+use crate::{
+    db::{
+        DbCreate, DbDelete, DbDeleteError, DbList, DbListPage, DbMerge, DbQuery, DbSelectSingle,
+        DbUpdate, DbUpdateError, DbUpsert, DbUpsertError, FilterValue, ListParams, RecordDb,
+        Upserted,
+    },
+    query::{CompareOp, Condition, Direction, Order, is_field_path},
+};
+
+use super::{AsSqlRow, FromSqlRow, SqlDb, SqlDialect, SqlRecord, SqlValue};
+
+impl RecordDb for SqlDb {}
+
+/// SQL text and its params, bound in order as `$1`, `$2`...
+#[derive(Default)]
+struct Statement {
+    sql: String,
+    values: Vec<SqlValue>,
+}
+
+impl Statement {
+    fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Placeholder of the value
+    fn param(&mut self, value: SqlValue) -> String {
+        self.values.push(value);
+        format!("${}", self.values.len())
+    }
+
+    fn query(&self) -> Query<'_, Any, AnyArguments<'_>> {
+        let mut query = sqlx::query(&self.sql);
+        for value in &self.values {
+            query = match value.clone() {
+                SqlValue::Null => query.bind(None::<String>),
+                SqlValue::Bool(value) => query.bind(value),
+                SqlValue::Int(value) => query.bind(value),
+                SqlValue::Float(value) => query.bind(value),
+                SqlValue::Text(value) => query.bind(value),
+                SqlValue::Bytes(value) => query.bind(value),
+            };
+        }
+
+        query
+    }
+
+    async fn execute(&self, db: &SqlDb) -> Result<u64, sqlx::Error> {
+        let result = self.query().execute(db.pool()).await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn fetch_optional(&self, db: &SqlDb) -> Result<Option<AnyRow>, sqlx::Error> {
+        self.query().fetch_optional(db.pool()).await
+    }
+
+    async fn fetch_one(&self, db: &SqlDb) -> Result<AnyRow, sqlx::Error> {
+        self.query().fetch_one(db.pool()).await
+    }
+
+    async fn fetch_all(&self, db: &SqlDb) -> Result<Vec<AnyRow>, sqlx::Error> {
+        self.query().fetch_all(db.pool()).await
+    }
+}
+
+/// Tables and columns are never bound, only plain names are allowed so they can be quoted
+fn ident(name: &str) -> Result<String, WokUnknownError> {
+    if !is_field_path(name) || name.contains('.') {
+        return Err(WokUnknownError::from_message(format!(
+            "`{name}` is not a valid SQL column, nested fields are not supported"
+        )));
+    }
+
+    Ok(format!("\"{name}\""))
+}
+
+fn is_unique_violation(err: &sqlx::Error) -> bool {
+    matches!(err, sqlx::Error::Database(err) if err.is_unique_violation())
+}
+
+fn filter_value(value: FilterValue) -> Result<SqlValue, WokUnknownError> {
+    Ok(match value {
+        FilterValue::Null => SqlValue::Null,
+        FilterValue::Bool(value) => SqlValue::Bool(value),
+        FilterValue::Int(value) => SqlValue::Int(value),
+        FilterValue::Float(value) => SqlValue::Float(value),
+        FilterValue::String(value) => SqlValue::Text(value),
+        FilterValue::List(_) => {
+            return Err(WokUnknownError::from_message(
+                "lists can only be compared with `is_in`",
+            ));
+        }
+    })
+}
+
+/// `LIKE` pattern matching the text anywhere
+fn like_pattern(text: &str) -> String {
+    let mut pattern = String::with_capacity(text.len() + 2);
+    pattern.push('%');
+    for c in text.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+
+    pattern
+}
+
+fn compile(statement: &mut Statement, condition: Condition) -> Result<String, WokUnknownError> {
+    Ok(match condition {
+        // `= NULL` is never true
+        Condition::Compare {
+            field,
+            op: op @ (CompareOp::Eq | CompareOp::Ne),
+            value: FilterValue::Null,
+        } => {
+            let not = if op == CompareOp::Ne { " NOT" } else { "" };
+            format!("{} IS{not} NULL", ident(&field)?)
+        }
+        Condition::Compare { field, op, value } => {
+            let op = match op {
+                CompareOp::Eq => "=",
+                CompareOp::Ne => "<>",
+                CompareOp::Lt => "<",
+                CompareOp::Le => "<=",
+                CompareOp::Gt => ">",
+                CompareOp::Ge => ">=",
+            };
+            let field = ident(&field)?;
+            let value = statement.param(filter_value(value)?);
+
+            format!("{field} {op} {value}")
+        }
+        Condition::Contains { field, value } => {
+            let text = match value {
+                FilterValue::String(text) => text,
+                _ => {
+                    return Err(WokUnknownError::from_message(
+                        "`contains` only matches text",
+                    ));
+                }
+            };
+            let field = ident(&field)?;
+            let pattern = statement.param(SqlValue::Text(like_pattern(&text)));
+
+            format!("{field} LIKE {pattern} ESCAPE '\\'")
+        }
+        Condition::In { field, values } => {
+            if values.is_empty() {
+                return Ok("1 = 0".to_owned());
+            }
+
+            let field = ident(&field)?;
+            let values = values
+                .into_iter()
+                .map(|value| Ok(statement.param(filter_value(value)?)))
+                .collect::<Result<Vec<_>, WokUnknownError>>()?;
+
+            format!("{field} IN ({})", values.join(", "))
+        }
+        Condition::And(conditions) => join(statement, conditions, " AND ", "1 = 1")?,
+        Condition::Or(conditions) => join(statement, conditions, " OR ", "1 = 0")?,
+        Condition::Not(condition) => format!("NOT ({})", compile(statement, *condition)?),
+    })
+}
+
+fn join(
+    statement: &mut Statement,
+    conditions: Vec<Condition>,
+    separator: &str,
+    empty: &str,
+) -> Result<String, WokUnknownError> {
+    if conditions.is_empty() {
+        return Ok(empty.to_owned());
+    }
+
+    let compiled = conditions
+        .into_iter()
+        .map(|condition| Ok(format!("({})", compile(statement, condition)?)))
+        .collect::<Result<Vec<_>, WokUnknownError>>()?;
+
+    Ok(compiled.join(separator))
+}
+
+fn order_sql(order: &[Order], id_column: &str) -> Result<String, WokUnknownError> {
+    let mut fields = Vec::with_capacity(order.len() + 1);
+    for order in order {
+        let direction = match order.direction {
+            Direction::Asc => "ASC",
+            Direction::Desc => "DESC",
+        };
+        fields.push(format!("{} {direction}", ident(&order.field)?));
+    }
+
+    if !order.iter().any(|order| order.field == id_column) {
+        fields.push(format!("{} ASC", ident(id_column)?));
+    }
+
+    Ok(format!(" ORDER BY {}", fields.join(", ")))
+}
+
+impl<R, D> DbCreate<R, D> for SqlDb
+where
+    R: SqlRecord,
+    D: AsSqlRow,
+{
+    type CreateQuery<'q> = SqlCreate<'q, R, D>;
+
+    fn create<'q>(&'q self, table: &'static str, data: D) -> Self::CreateQuery<'q> {
+        SqlCreate {
+            db: self,
+            _marker: std::marker::PhantomData,
+            table,
+            data,
+        }
+    }
+}
+
+/// Columns absent from the data are left to their default, such as a sequence for the id
+pub struct SqlCreate<'db, R, D> {
+    db: &'db SqlDb,
+    _marker: std::marker::PhantomData<R>,
+    table: &'static str,
+    data: D,
+}
+
+impl<'db, R, D> DbQuery<R> for SqlCreate<'db, R, D>
+where
+    R: SqlRecord,
+    D: AsSqlRow,
+{
+    async fn execute(self) -> Result<R, WokUnknownError> {
+        let table = ident(self.table)?;
+        let id_column = ident(R::ID_COLUMN)?;
+        let columns = self.data.sql_columns();
+
+        let mut statement = Statement::default();
+        let sql = if columns.is_empty() {
+            format!("INSERT INTO {table} DEFAULT VALUES RETURNING {id_column}")
+        } else {
+            let mut names = Vec::with_capacity(columns.len());
+            let mut values = Vec::with_capacity(columns.len());
+            for (column, value) in columns {
+                names.push(ident(column)?);
+                values.push(statement.param(value));
+            }
+
+            format!(
+                "INSERT INTO {table} ({}) VALUES ({}) RETURNING {id_column}",
+                names.join(", "),
+                values.join(", ")
+            )
+        };
+        statement.sql = sql;
+
+        let row = match statement.fetch_one(self.db).await {
+            Ok(row) => row,
+            Err(err) if is_unique_violation(&err) => {
+                return Err(WokUnknownError::new(err).with_status(409));
+            }
+            Err(err) => return Err(WokUnknownError::new(err)),
+        };
+
+        Ok(R::from_sql(&row, R::ID_COLUMN)?)
+    }
+}
+
+impl<D: FromSqlRow> DbList<D> for SqlDb {
+    type ListQuery<'q> = SqlList<'q, D>;
+
+    fn list<'q>(&'q self, table: &'static str) -> Self::ListQuery<'q> {
+        SqlList {
+            db: self,
+            table,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+pub struct SqlList<'db, Out> {
+    db: &'db SqlDb,
+    table: &'static str,
+    _marker: std::marker::PhantomData<Out>,
+}
+
+impl<'db, D: FromSqlRow> DbQuery<Vec<D>> for SqlList<'db, D> {
+    async fn execute(self) -> Result<Vec<D>, WokUnknownError> {
+        let statement = Statement::new(format!("SELECT * FROM {}", ident(self.table)?));
+        let rows = statement.fetch_all(self.db).await?;
+
+        Ok(rows
+            .iter()
+            .map(D::from_row)
+            .collect::<Result<_, sqlx::Error>>()?)
+    }
+}
+
+impl<R, D> DbListPage<R, D> for SqlDb
+where
+    R: SqlRecord,
+    D: FromSqlRow,
+{
+    type ListPageQuery<'q> = SqlListPage<'q, R, D>;
+
+    fn list_page<'q>(
+        &'q self,
+        table: &'static str,
+        params: ListParams<R>,
+    ) -> Self::ListPageQuery<'q> {
+        SqlListPage {
+            db: self,
+            table,
+            params,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+pub struct SqlListPage<'db, R, Out> {
+    db: &'db SqlDb,
+    table: &'static str,
+    params: ListParams<R>,
+    _marker: std::marker::PhantomData<Out>,
+}
+
+impl<'db, R, D> DbQuery<Vec<D>> for SqlListPage<'db, R, D>
+where
+    R: SqlRecord,
+    D: FromSqlRow,
+{
+    async fn execute(self) -> Result<Vec<D>, WokUnknownError> {
+        self.params.check_cursor(R::ID_COLUMN)?;
+        let ListParams {
+            limit,
+            start,
+            after,
+            filters,
+            order,
+        } = self.params;
+
+        let mut statement = Statement::default();
+        let mut conditions = Vec::new();
+        if let Some(after) = after {
+            let id_column = ident(R::ID_COLUMN)?;
+            conditions.push(format!("{id_column} > {}", statement.param(after.to_sql())));
+        }
+
+        for condition in filters {
+            conditions.push(format!("({})", compile(&mut statement, condition)?));
+        }
+
+        let mut sql = format!("SELECT * FROM {}", ident(self.table)?);
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+
+        sql.push_str(&order_sql(&order, R::ID_COLUMN)?);
+
+        let to_int = |value: usize| SqlValue::Int(i64::try_from(value).unwrap_or(i64::MAX));
+        match (limit, start, self.db.dialect()) {
+            (Some(limit), _, _) => {
+                sql.push_str(&format!(" LIMIT {}", statement.param(to_int(limit))));
+            }
+            // SQLite only takes `OFFSET` after a `LIMIT`
+            (None, Some(_), SqlDialect::Sqlite) => sql.push_str(" LIMIT -1"),
+            (None, _, _) => {}
+        }
+        if let Some(start) = start {
+            sql.push_str(&format!(" OFFSET {}", statement.param(to_int(start))));
+        }
+
+        statement.sql = sql;
+        let rows = statement.fetch_all(self.db).await?;
+
+        Ok(rows
+            .iter()
+            .map(D::from_row)
+            .collect::<Result<_, sqlx::Error>>()?)
+    }
+}
+
+impl<R, D> DbSelectSingle<R, D> for SqlDb
+where
+    R: SqlRecord,
+    D: FromSqlRow,
+{
+    type SelectQuery<'q> = SqlSelectSingle<'q, R>;
+
+    fn select<'q>(&'q self, table: &'static str, id: R) -> Self::SelectQuery<'q> {
+        SqlSelectSingle {
+            db: self,
+            table,
+            id,
+        }
+    }
+}
+
+pub struct SqlSelectSingle<'db, R> {
+    db: &'db SqlDb,
+    table: &'static str,
+    id: R,
+}
+
+impl<'db, R, D> DbQuery<Option<D>> for SqlSelectSingle<'db, R>
+where
+    R: SqlRecord,
+    D: FromSqlRow,
+{
+    async fn execute(self) -> Result<Option<D>, WokUnknownError> {
+        let mut statement = Statement::default();
+        let id = statement.param(self.id.to_sql());
+        statement.sql = format!(
+            "SELECT * FROM {} WHERE {} = {id}",
+            ident(self.table)?,
+            ident(R::ID_COLUMN)?
+        );
+
+        let row = statement.fetch_optional(self.db).await?;
+        Ok(row.as_ref().map(D::from_row).transpose()?)
+    }
+}
+
+/// Whether the record exists, updates without columns do not touch any row
+async fn exists<R: SqlRecord>(
+    db: &SqlDb,
+    table: &'static str,
+    id: &R,
+) -> Result<bool, WokUnknownError> {
+    let mut statement = Statement::default();
+    let id = statement.param(id.to_sql());
+    statement.sql = format!(
+        "SELECT 1 FROM {} WHERE {} = {id}",
+        ident(table)?,
+        ident(R::ID_COLUMN)?
+    );
+
+    Ok(statement.fetch_optional(db).await?.is_some())
+}
+
+/// Shared by updates and merges, only the columns of the data are set
+pub struct SqlUpdate<'db, R, D> {
+    db: &'db SqlDb,
+    table: &'static str,
+    id: R,
+    data: D,
+}
+
+impl<'db, R, D> DbQuery<Result<(), DbUpdateError>> for SqlUpdate<'db, R, D>
+where
+    R: SqlRecord,
+    D: AsSqlRow,
+{
+    async fn execute(self) -> Result<Result<(), DbUpdateError>, WokUnknownError> {
+        let columns = self.data.sql_columns();
+        if columns.is_empty() {
+            return match exists(self.db, self.table, &self.id).await? {
+                true => Ok(Ok(())),
+                false => Ok(Err(DbUpdateError::NotFound)),
+            };
+        }
+
+        let mut statement = Statement::default();
+        let mut sets = Vec::with_capacity(columns.len());
+        for (column, value) in columns {
+            sets.push(format!("{} = {}", ident(column)?, statement.param(value)));
+        }
+
+        let id = statement.param(self.id.to_sql());
+        statement.sql = format!(
+            "UPDATE {} SET {} WHERE {} = {id}",
+            ident(self.table)?,
+            sets.join(", "),
+            ident(R::ID_COLUMN)?
+        );
+
+        match statement.execute(self.db).await {
+            Ok(0) => Ok(Err(DbUpdateError::NotFound)),
+            Ok(_) => Ok(Ok(())),
+            Err(err) if is_unique_violation(&err) => Ok(Err(DbUpdateError::Conflict)),
+            Err(err) => Err(WokUnknownError::new(err)),
+        }
+    }
+}
+
+/// Rows have no absent fields, an update sets the columns of the data like a merge
+impl<R, D> DbUpdate<R, D> for SqlDb
+where
+    R: SqlRecord,
+    D: AsSqlRow,
+{
+    type UpdateQuery<'q> = SqlUpdate<'q, R, D>;
+
+    fn update<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpdateQuery<'q> {
+        SqlUpdate {
+            db: self,
+            table,
+            id,
+            data,
+        }
+    }
+}
+
+impl<R, D> DbMerge<R, D> for SqlDb
+where
+    R: SqlRecord,
+    D: AsSqlRow,
+{
+    type MergeQuery<'q> = SqlUpdate<'q, R, D>;
+
+    fn merge<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::MergeQuery<'q> {
+        SqlUpdate {
+            db: self,
+            table,
+            id,
+            data,
+        }
+    }
+}
+
+pub struct SqlUpsert<'db, R, D> {
+    db: &'db SqlDb,
+    table: &'static str,
+    id: R,
+    data: D,
+}
+
+impl<'db, R, D> DbQuery<Result<Upserted, DbUpsertError>> for SqlUpsert<'db, R, D>
+where
+    R: SqlRecord,
+    D: AsSqlRow,
+{
+    async fn execute(self) -> Result<Result<Upserted, DbUpsertError>, WokUnknownError> {
+        let found = exists(self.db, self.table, &self.id).await?;
+        let id_column = ident(R::ID_COLUMN)?;
+
+        let mut statement = Statement::default();
+        let mut names = vec![id_column.clone()];
+        let mut values = vec![statement.param(self.id.to_sql())];
+        let mut sets = Vec::new();
+        for (column, value) in self.data.sql_columns() {
+            let column = ident(column)?;
+            sets.push(format!("{column} = excluded.{column}"));
+            names.push(column);
+            values.push(statement.param(value));
+        }
+
+        let on_conflict = match sets.is_empty() {
+            true => "DO NOTHING".to_owned(),
+            false => format!("DO UPDATE SET {}", sets.join(", ")),
+        };
+        statement.sql = format!(
+            "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({id_column}) {on_conflict}",
+            ident(self.table)?,
+            names.join(", "),
+            values.join(", ")
+        );
+
+        match statement.execute(self.db).await {
+            Ok(_) if found => Ok(Ok(Upserted::Updated)),
+            Ok(_) => Ok(Ok(Upserted::Created)),
+            Err(err) if is_unique_violation(&err) => Ok(Err(DbUpsertError::Conflict)),
+            Err(err) => Err(WokUnknownError::new(err)),
+        }
+    }
+}
+
+impl<R, D> DbUpsert<R, D> for SqlDb
+where
+    R: SqlRecord,
+    D: AsSqlRow,
+{
+    type UpsertQuery<'q> = SqlUpsert<'q, R, D>;
+
+    fn upsert<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpsertQuery<'q> {
+        SqlUpsert {
+            db: self,
+            table,
+            id,
+            data,
+        }
+    }
+}
+
+impl<R: SqlRecord> DbDelete<R> for SqlDb {
+    type DeleteQuery<'q> = SqlDelete<'q, R>;
+
+    fn delete<'q>(&'q self, table: &'static str, id: R) -> Self::DeleteQuery<'q> {
+        SqlDelete {
+            db: self,
+            table,
+            id,
+        }
+    }
+}
+
+pub struct SqlDelete<'db, R> {
+    db: &'db SqlDb,
+    table: &'static str,
+    id: R,
+}
+
+impl<'db, R: SqlRecord> DbQuery<Result<(), DbDeleteError>> for SqlDelete<'db, R> {
+    async fn execute(self) -> Result<Result<(), DbDeleteError>, WokUnknownError> {
+        let mut statement = Statement::default();
+        let id = statement.param(self.id.to_sql());
+        statement.sql = format!(
+            "DELETE FROM {} WHERE {} = {id}",
+            ident(self.table)?,
+            ident(R::ID_COLUMN)?
+        );
+
+        match statement.execute(self.db).await? {
+            0 => Ok(Err(DbDeleteError::None)),
+            _ => Ok(Ok(())),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use crate::{
+        Record, RecordEntry,
+        db::{DbQuery, DbUpdateError, RecordDb, Upserted},
+        query::Field,
+        sqlx::{AsSqlRow, FromSqlRow, SqlColumn, SqlDb, SqlRecord},
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, SqlColumn)]
+    struct PersonId(i64);
+
+    impl Record for PersonId {
+        const TABLE: &'static str = "person";
+    }
+
+    impl SqlRecord for PersonId {}
+
+    #[derive(Debug, PartialEq, AsSqlRow, FromSqlRow)]
+    struct Person {
+        name: String,
+        age: Option<i32>,
+    }
+
+    impl Person {
+        const fn name() -> Field<Self, String> {
+            Field::new("name")
+        }
+
+        const fn age() -> Field<Self, Option<i32>> {
+            Field::new("age")
+        }
+    }
+
+    #[derive(AsSqlRow)]
+    struct PersonPatch {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        age: Option<i32>,
+    }
+
+    fn person(name: &str, age: Option<i32>) -> Person {
+        Person {
+            name: name.to_owned(),
+            age,
+        }
+    }
+
+    async fn memory_db() -> SqlDb {
+        let db = SqlDb::connect("sqlite::memory:", None).await.unwrap();
+        sqlx::query(
+            "CREATE TABLE person (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL UNIQUE, age INTEGER)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+
+        db
+    }
+
+    #[tokio::test]
+    async fn creates_and_queries_records() {
+        let db = memory_db().await;
+
+        let ana = db
+            .record::<PersonId>()
+            .create(person("Ana", Some(31)))
+            .execute()
+            .await
+            .unwrap();
+        db.record::<PersonId>()
+            .create(person("Bob", None))
+            .execute()
+            .await
+            .unwrap();
+        db.record::<PersonId>()
+            .create(person("Bea_", Some(20)))
+            .execute()
+            .await
+            .unwrap();
+
+        let found = db
+            .record::<PersonId>()
+            .select::<Person>(ana)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(found, Some(person("Ana", Some(31))));
+
+        // `_` is matched literally
+        let names: Vec<Person> = db
+            .record::<PersonId>()
+            .query()
+            .filter(Person::name().contains("_"))
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(names, vec![person("Bea_", Some(20))]);
+
+        let page: Vec<RecordEntry<PersonId, Person>> = db
+            .record::<PersonId>()
+            .query()
+            .filter(Person::age().is_null().or(Person::age().gt(Some(25))))
+            .order_by(Person::name().desc())
+            .limit(1)
+            .start(1)
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, ana);
+    }
+
+    #[tokio::test]
+    async fn updates_upserts_and_deletes() {
+        let db = memory_db().await;
+
+        let ana = db
+            .record::<PersonId>()
+            .create(person("Ana", None))
+            .execute()
+            .await
+            .unwrap();
+        let bob = db
+            .record::<PersonId>()
+            .create(person("Bob", None))
+            .execute()
+            .await
+            .unwrap();
+
+        let merged = db
+            .record::<PersonId>()
+            .merge(ana, PersonPatch { age: Some(32) });
+        assert_eq!(merged.execute().await.unwrap(), Ok(()));
+
+        let missing = db
+            .record::<PersonId>()
+            .merge(PersonId(99), PersonPatch { age: None });
+        assert_eq!(
+            missing.execute().await.unwrap(),
+            Err(DbUpdateError::NotFound)
+        );
+
+        let taken = db.record::<PersonId>().update(bob, person("Ana", None));
+        assert_eq!(taken.execute().await.unwrap(), Err(DbUpdateError::Conflict));
+
+        let upserted = db
+            .record::<PersonId>()
+            .upsert(PersonId(7), person("Cid", None));
+        assert_eq!(upserted.execute().await.unwrap(), Ok(Upserted::Created));
+
+        let upserted = db
+            .record::<PersonId>()
+            .upsert(PersonId(7), person("Cid", Some(40)));
+        assert_eq!(upserted.execute().await.unwrap(), Ok(Upserted::Updated));
+
+        assert!(
+            db.record::<PersonId>()
+                .delete(bob)
+                .execute()
+                .await
+                .unwrap()
+                .is_ok()
+        );
+        assert!(
+            db.record::<PersonId>()
+                .delete(bob)
+                .execute()
+                .await
+                .unwrap()
+                .is_err()
+        );
+
+        let left: Vec<RecordEntry<PersonId, Person>> =
+            db.record::<PersonId>().list().execute().await.unwrap();
+        let ids: Vec<_> = left.iter().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![ana, PersonId(7)]);
+        assert_eq!(left[0].data.age, Some(32));
+    }
+}
@@ -0,0 +1,64 @@
+use sqlx::{Executor, Row};
+use wok::prelude::WokUnknownError;
+
+use crate::migrate::{AppliedMigration, DbMigrate, MIGRATIONS_TABLE, Migration, MigrationError};
+
+use super::SqlDb;
+
+impl DbMigrate for SqlDb {
+    async fn applied(&self) -> Result<Vec<AppliedMigration>, WokUnknownError> {
+        let create = format!(
+            "CREATE TABLE IF NOT EXISTS {MIGRATIONS_TABLE} \
+             (version BIGINT PRIMARY KEY, name TEXT NOT NULL, checksum TEXT NOT NULL)"
+        );
+        sqlx::query(&create).execute(self.pool()).await?;
+
+        let select = format!("SELECT version, name, checksum FROM {MIGRATIONS_TABLE}");
+        let rows = sqlx::query(&select).fetch_all(self.pool()).await?;
+
+        let applied = rows
+            .iter()
+            .map(|row| {
+                Ok(AppliedMigration {
+                    version: row.try_get("version")?,
+                    name: row.try_get("name")?,
+                    checksum: row.try_get("checksum")?,
+                })
+            })
+            .collect::<Result<_, sqlx::Error>>()?;
+
+        Ok(applied)
+    }
+
+    async fn apply(&self, migration: &Migration) -> Result<(), WokUnknownError> {
+        let mut tx = self.pool().begin().await?;
+        tx.execute(sqlx::raw_sql(&migration.up)).await?;
+
+        let insert =
+            format!("INSERT INTO {MIGRATIONS_TABLE} (version, name, checksum) VALUES ($1, $2, $3)");
+        let insert = sqlx::query(&insert)
+            .bind(migration.version)
+            .bind(migration.name.to_string())
+            .bind(migration.checksum());
+        tx.execute(insert).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn revert(&self, migration: &Migration) -> Result<(), WokUnknownError> {
+        let Some(down) = &migration.down else {
+            return Err(MigrationError::Irreversible(migration.version).into());
+        };
+
+        let mut tx = self.pool().begin().await?;
+        tx.execute(sqlx::raw_sql(down)).await?;
+
+        let delete = format!("DELETE FROM {MIGRATIONS_TABLE} WHERE version = $1");
+        tx.execute(sqlx::query(&delete).bind(migration.version))
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
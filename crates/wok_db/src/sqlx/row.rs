@@ -0,0 +1,148 @@
+use sqlx::{Row, ValueRef, any::AnyRow};
+
+pub use wok_db_derive::{AsSqlRow, FromSqlRow, SqlColumn};
+
+/// Value bound to a query param
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// Type stored in a single column, derive it on newtypes
+pub trait SqlColumn: Sized + Send + 'static {
+    fn to_sql(&self) -> SqlValue;
+    fn from_sql(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error>;
+}
+
+/// Columns written on create and update
+pub trait AsSqlRow: Send + 'static {
+    /// Skipped fields are left out, such as absent fields of a patch
+    fn sql_columns(&self) -> Vec<(&'static str, SqlValue)>;
+}
+
+pub trait FromSqlRow: Sized + Send + 'static {
+    fn from_row(row: &AnyRow) -> Result<Self, sqlx::Error>;
+}
+
+fn decode_error(column: &str, err: impl std::error::Error + Send + Sync + 'static) -> sqlx::Error {
+    sqlx::Error::ColumnDecode {
+        index: column.to_owned(),
+        source: Box::new(err),
+    }
+}
+
+macro_rules! sql_column_copy {
+    ($type:ty, $variant:ident) => {
+        impl SqlColumn for $type {
+            fn to_sql(&self) -> SqlValue {
+                SqlValue::$variant(*self)
+            }
+
+            fn from_sql(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error> {
+                row.try_get(column)
+            }
+        }
+    };
+}
+
+sql_column_copy!(bool, Bool);
+sql_column_copy!(i64, Int);
+sql_column_copy!(f64, Float);
+
+macro_rules! sql_column_clone {
+    ($type:ty, $variant:ident) => {
+        impl SqlColumn for $type {
+            fn to_sql(&self) -> SqlValue {
+                SqlValue::$variant(self.clone())
+            }
+
+            fn from_sql(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error> {
+                row.try_get(column)
+            }
+        }
+    };
+}
+
+sql_column_clone!(String, Text);
+sql_column_clone!(Vec<u8>, Bytes);
+
+/// Stored as `BIGINT`, wider integers do not fit every backend
+macro_rules! sql_column_int {
+    ($type:ty) => {
+        impl SqlColumn for $type {
+            fn to_sql(&self) -> SqlValue {
+                SqlValue::Int(i64::from(*self))
+            }
+
+            fn from_sql(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error> {
+                let value: i64 = row.try_get(column)?;
+                <$type>::try_from(value).map_err(|err| decode_error(column, err))
+            }
+        }
+    };
+}
+
+sql_column_int!(i8);
+sql_column_int!(i16);
+sql_column_int!(i32);
+sql_column_int!(u8);
+sql_column_int!(u16);
+sql_column_int!(u32);
+
+impl SqlColumn for f32 {
+    fn to_sql(&self) -> SqlValue {
+        SqlValue::Float(f64::from(*self))
+    }
+
+    fn from_sql(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error> {
+        let value: f64 = row.try_get(column)?;
+        Ok(value as f32)
+    }
+}
+
+/// Stored as RFC 3339 text
+#[cfg(feature = "chrono")]
+impl SqlColumn for chrono::DateTime<chrono::Utc> {
+    fn to_sql(&self) -> SqlValue {
+        SqlValue::Text(self.to_rfc3339())
+    }
+
+    fn from_sql(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error> {
+        let value: String = row.try_get(column)?;
+        chrono::DateTime::parse_from_rfc3339(&value)
+            .map(|date| date.to_utc())
+            .map_err(|err| decode_error(column, err))
+    }
+}
+
+/// Stored as text, which every backend compares the same way
+#[cfg(feature = "uuid")]
+impl SqlColumn for uuid::Uuid {
+    fn to_sql(&self) -> SqlValue {
+        SqlValue::Text(self.to_string())
+    }
+
+    fn from_sql(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error> {
+        let value: String = row.try_get(column)?;
+        uuid::Uuid::parse_str(&value).map_err(|err| decode_error(column, err))
+    }
+}
+
+impl<T: SqlColumn> SqlColumn for Option<T> {
+    fn to_sql(&self) -> SqlValue {
+        self.as_ref().map_or(SqlValue::Null, SqlColumn::to_sql)
+    }
+
+    fn from_sql(row: &AnyRow, column: &str) -> Result<Self, sqlx::Error> {
+        if row.try_get_raw(column)?.is_null() {
+            return Ok(None);
+        }
+
+        T::from_sql(row, column).map(Some)
+    }
+}
@@ -3,17 +3,21 @@ use surrealdb::{Connection, Surreal};
 
 pub use as_surreal_bind::{AsSurrealBind, SurrealSerialize};
 pub use from_surreal_bind::FromSurrealBind;
+#[cfg(any(feature = "surrealdb-mem", feature = "surrealdb-rocksdb"))]
+pub use local::LocalSurrealDbPlugin;
 pub use record_serde::{IdFlavor, StringFlavor, SurrealRecord};
+pub use transaction::SurrealTransaction;
 
-use crate::{
-    RecordEntry, RecordGenerate,
-    id_strategy::{GenerateId, IdStrategy},
-};
+use crate::RecordEntry;
 
 mod as_surreal_bind;
 mod crud;
 mod from_surreal_bind;
+#[cfg(any(feature = "surrealdb-mem", feature = "surrealdb-rocksdb"))]
+mod local;
+mod migrate;
 mod record_serde;
+mod transaction;
 
 #[derive(wok::prelude::Resource)]
 pub struct SurrealDb<C: Connection>(pub Surreal<C>);
@@ -54,16 +58,6 @@ impl<R: SurrealRecord, B: AsSurrealBind> AsSurrealBind for KeyValue<R, B> {
     }
 }
 
-impl<R: SurrealRecord + RecordGenerate> IdStrategy<R> for GenerateId {
-    type Wrap<D> = KeyValue<R, D>;
-    fn wrap<D>(body: D) -> Self::Wrap<D> {
-        KeyValue {
-            id: R::generate(),
-            data: body,
-        }
-    }
-}
-
 impl<R: SurrealRecord, D: AsSurrealBind> AsSurrealBind for RecordEntry<R, D> {
     type Bind<'b> = SurrealKeyValueRef<'b, R, D>;
     fn as_bind(&self) -> Self::Bind<'_> {
@@ -85,7 +79,7 @@ impl<R: SurrealRecord, D: FromSurrealBind> FromSurrealBind for RecordEntry<R, D>
     }
 }
 
-#[derive(wok::prelude::Resource, serde::Deserialize, Debug, valigate::Valid)]
+#[derive(wok::prelude::Resource, serde::Deserialize, Debug, Clone, valigate::Valid)]
 #[gate(serde = true)]
 pub struct SurrealCredentials {
     pub host: String,
@@ -93,7 +87,7 @@ pub struct SurrealCredentials {
     pub signin: Option<SurrealSignIn>,
 }
 
-#[derive(Debug, serde::Deserialize, valigate::Valid)]
+#[derive(Debug, Clone, serde::Deserialize, valigate::Valid)]
 #[gate(serde = true)]
 pub struct SurrealSignIn {
     pub username: String,
@@ -103,18 +97,53 @@ pub struct SurrealSignIn {
 #[derive(wok::prelude::Resource)]
 pub struct SurrealConfig(pub surrealdb::opt::Config);
 
-#[derive(Debug, valigate::Valid, wok::prelude::Resource)]
+#[derive(Debug, Clone, valigate::Valid, wok::prelude::Resource)]
 #[gate(serde = true)]
 pub struct SurrealUseDb {
     pub database: String,
     pub namespace: String,
 }
 
-pub struct RemoteSurrealDbPlugin<P: 'static>(std::marker::PhantomData<P>);
+/// Selects the namespace and database of `SurrealUseDb`, if any
+async fn use_surreal_db<C: Connection>(
+    db: &Surreal<C>,
+    use_db: Option<&SurrealUseDb>,
+) -> Result<(), surrealdb::Error> {
+    let Some(use_db) = use_db else {
+        tracing::warn!("Using no database for SurrealDB");
+        return Ok(());
+    };
+
+    tracing::info!(
+        "Using namespace '{}' and database '{}'",
+        &use_db.namespace,
+        &use_db.database
+    );
+
+    db.use_ns(use_db.namespace.clone())
+        .use_db(use_db.database.clone())
+        .await
+}
+
+pub struct RemoteSurrealDbPlugin<P: 'static> {
+    retry: wok::prelude::RetryPolicy,
+    _marker: std::marker::PhantomData<P>,
+}
 
 impl<P: 'static> Default for RemoteSurrealDbPlugin<P> {
     fn default() -> Self {
-        RemoteSurrealDbPlugin(std::marker::PhantomData)
+        RemoteSurrealDbPlugin {
+            retry: wok::prelude::RetryPolicy::default(),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<P: 'static> RemoteSurrealDbPlugin<P> {
+    /// Policy used to retry the connection on startup
+    pub fn retry(mut self, policy: wok::prelude::RetryPolicy) -> Self {
+        self.retry = policy;
+        self
     }
 }
 
@@ -124,19 +153,41 @@ where
         surrealdb::opt::IntoEndpoint<P, Client = surrealdb::engine::remote::ws::Client>,
 {
     fn setup(self, app: &mut wok::prelude::App) {
-        use wok::prelude::{ConfigureWorld, ResInit, ResTake};
-        async fn configure_surrealdb_connection<P>(
+        use wok::prelude::{ConfigureWorld, In, IntoBlockingSystem, IntoSystem, ResInit, ResTake};
+
+        /// Taken once from the world, each attempt gets a clone
+        #[derive(Clone)]
+        struct RemoteConnection {
+            creds: SurrealCredentials,
+            use_db: Option<SurrealUseDb>,
+            config: surrealdb::opt::Config,
+        }
+
+        fn take_connection(
             creds: ResTake<SurrealCredentials>,
             use_db: Option<ResTake<SurrealUseDb>>,
             config: Option<ResTake<SurrealConfig>>,
+        ) -> RemoteConnection {
+            RemoteConnection {
+                creds: creds.into_inner(),
+                use_db: use_db.map(ResTake::into_inner),
+                config: config.map(|c| c.into_inner().0).unwrap_or_default(),
+            }
+        }
+
+        async fn configure_surrealdb_connection<P>(
+            In(connection): In<RemoteConnection>,
             mut surreal: ResInit<'_, SurrealDb<surrealdb::engine::remote::ws::Client>>,
         ) -> Result<(), wok::prelude::WokUnknownError>
         where
             (String, surrealdb::opt::Config):
                 surrealdb::opt::IntoEndpoint<P, Client = surrealdb::engine::remote::ws::Client>,
         {
-            let creds = creds.into_inner();
-            let config = config.map(|c| c.into_inner().0).unwrap_or_default();
+            let RemoteConnection {
+                creds,
+                use_db,
+                config,
+            } = connection;
 
             tracing::info!("Connected to remote SurrealDB at '{}'", &creds.host);
             let db = surrealdb::Surreal::<surrealdb::engine::remote::ws::Client>::new::<P>((
@@ -156,24 +207,15 @@ where
                 tracing::warn!("Credentials missing for remote SurrealDB");
             }
 
-            if let Some(use_db) = use_db {
-                let use_db = use_db.into_inner();
-                tracing::info!(
-                    "Using namespace '{}' and database '{}'",
-                    &use_db.namespace,
-                    &use_db.database
-                );
-
-                db.use_ns(use_db.namespace).use_db(use_db.database).await?;
-            } else {
-                tracing::warn!("Using no database for remote SurrealDB");
-            }
-
+            use_surreal_db(&db, use_db.as_ref()).await?;
             surreal.init(SurrealDb::new(db));
 
             Ok(())
         }
 
-        app.add_systems(wok::prelude::Startup, configure_surrealdb_connection);
+        app.add_systems(
+            wok::prelude::Startup,
+            take_connection.pipe_then(configure_surrealdb_connection::<P>.retry(self.retry)),
+        );
     }
 }
@@ -1,7 +1,14 @@
 use surrealdb::{Connection, Surreal};
 use wok::prelude::WokUnknownError;
 
-use crate::{db::{RecordDb, DbCreate, DbDelete, DbDeleteError, DbList, DbSelectSingle}, Record};
+use crate::{
+    Record,
+    db::{
+        DbCreate, DbDelete, DbDeleteError, DbList, DbListPage, DbMerge, DbSelectSingle, DbUpdate,
+        DbUpdateError, DbUpsert, DbUpsertError, FilterValue, ListParams, RecordDb, Upserted,
+    },
+    query::{CompareOp, Condition, Direction, Order, is_field_path},
+};
 
 use super::{
     AsSurrealBind, FromSurrealBind, SurrealDb, SurrealRecord, SurrealSerialize,
@@ -83,7 +90,11 @@ where
 {
     type ListQuery<'q> = SurrealList<'q, C, D>;
     fn list<'q>(&'q self, table: &'static str) -> Self::ListQuery<'q> {
-        SurrealList { db: &self.0, table, _marker: std::marker::PhantomData }
+        SurrealList {
+            db: &self.0,
+            table,
+            _marker: std::marker::PhantomData,
+        }
     }
 }
 
@@ -164,3 +175,649 @@ where
         }
     }
 }
+
+pub struct SurrealListPage<'db, C: Connection, R, Out> {
+    db: &'db Surreal<C>,
+    table: &'static str,
+    params: ListParams<R>,
+    _marker: std::marker::PhantomData<Out>,
+}
+
+/// Param bound by a compiled query
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+enum SurrealParam {
+    Field(String),
+    Value(FilterValue),
+    Values(Vec<FilterValue>),
+}
+
+/// SurrealQL of conditions, fields and values are bound so they never end up in the query text
+#[derive(Default)]
+struct ConditionSql {
+    params: Vec<(String, SurrealParam)>,
+}
+
+impl ConditionSql {
+    fn bind(&mut self, param: SurrealParam) -> String {
+        let name = format!("p{}", self.params.len());
+        let placeholder = format!("${name}");
+        self.params.push((name, param));
+
+        placeholder
+    }
+
+    fn field(&mut self, field: &str) -> String {
+        let field = self.bind(SurrealParam::Field(field.to_owned()));
+        format!("type::field({field})")
+    }
+
+    fn compile(&mut self, condition: Condition) -> String {
+        match condition {
+            Condition::Compare { field, op, value } => {
+                let op = match op {
+                    CompareOp::Eq => "=",
+                    CompareOp::Ne => "!=",
+                    CompareOp::Lt => "<",
+                    CompareOp::Le => "<=",
+                    CompareOp::Gt => ">",
+                    CompareOp::Ge => ">=",
+                };
+                let field = self.field(&field);
+                let value = self.bind(SurrealParam::Value(value));
+
+                format!("{field} {op} {value}")
+            }
+            Condition::Contains { field, value } => {
+                let field = self.field(&field);
+                let value = self.bind(SurrealParam::Value(value));
+
+                format!("{field} CONTAINS {value}")
+            }
+            Condition::In { field, values } => {
+                let field = self.field(&field);
+                let values = self.bind(SurrealParam::Values(values));
+
+                format!("{field} IN {values}")
+            }
+            Condition::And(conditions) => self.join(conditions, " AND ", "true"),
+            Condition::Or(conditions) => self.join(conditions, " OR ", "false"),
+            Condition::Not(condition) => format!("!({})", self.compile(*condition)),
+        }
+    }
+
+    fn join(&mut self, conditions: Vec<Condition>, separator: &str, empty: &str) -> String {
+        if conditions.is_empty() {
+            return empty.to_owned();
+        }
+
+        let compiled: Vec<_> = conditions
+            .into_iter()
+            .map(|condition| format!("({})", self.compile(condition)))
+            .collect();
+
+        compiled.join(separator)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("cannot order by `{0}`, only field paths such as `address.city` are allowed")]
+struct InvalidOrderField(String);
+
+/// `ORDER BY` does not take params, so field names are checked instead
+fn order_sql(order: &[Order]) -> Result<String, InvalidOrderField> {
+    let mut fields = Vec::with_capacity(order.len() + 1);
+    for order in order {
+        if !is_field_path(&order.field) {
+            return Err(InvalidOrderField(order.field.to_string()));
+        }
+
+        let direction = match order.direction {
+            Direction::Asc => "ASC",
+            Direction::Desc => "DESC",
+        };
+        fields.push(format!("{} {direction}", order.field));
+    }
+
+    if !order.iter().any(|order| order.field == "id") {
+        fields.push("id ASC".to_owned());
+    }
+
+    Ok(format!(" ORDER BY {}", fields.join(", ")))
+}
+
+impl<'db, C: Connection, R, D> crate::db::DbQuery<Vec<D>> for SurrealListPage<'db, C, R, D>
+where
+    R: SurrealRecord,
+    D: FromSurrealBind,
+{
+    async fn execute(self) -> Result<Vec<D>, WokUnknownError> {
+        self.params.check_cursor("id")?;
+        let ListParams {
+            limit,
+            start,
+            after,
+            filters,
+            order,
+        } = self.params;
+
+        let mut compiled = ConditionSql::default();
+        let mut conditions = Vec::new();
+        if after.is_some() {
+            conditions.push("id > type::thing($table, $after)".to_owned());
+        }
+
+        for condition in filters {
+            conditions.push(format!("({})", compiled.compile(condition)));
+        }
+
+        let mut sql = String::from("SELECT * FROM type::table($table)");
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+
+        sql.push_str(&order_sql(&order)?);
+        if limit.is_some() {
+            sql.push_str(" LIMIT $limit");
+        }
+        if start.is_some() {
+            sql.push_str(" START $start");
+        }
+
+        let mut query = self.db.query(sql).bind(("table", self.table));
+        if let Some(after) = after {
+            query = query.bind(("after", after));
+        }
+        if let Some(limit) = limit {
+            query = query.bind(("limit", limit));
+        }
+        if let Some(start) = start {
+            query = query.bind(("start", start));
+        }
+        for param in compiled.params {
+            query = query.bind(param);
+        }
+
+        let mut response = query.await?;
+        let result: Vec<D::Bind> = response.take(0)?;
+
+        Ok(result.into_iter().map(D::from_bind).collect())
+    }
+}
+
+impl<C, R, D> DbListPage<R, D> for SurrealDb<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: FromSurrealBind,
+{
+    type ListPageQuery<'q> = SurrealListPage<'q, C, R, D>;
+
+    fn list_page<'q>(
+        &'q self,
+        table: &'static str,
+        params: ListParams<R>,
+    ) -> Self::ListPageQuery<'q> {
+        SurrealListPage {
+            db: &self.0,
+            table,
+            params,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Whether the statement broke a unique index or an existing record id
+/// Best effort for remote engines, which only send the rendered message of the db error
+pub(super) fn is_conflict(err: &surrealdb::Error) -> bool {
+    use surrealdb::error::{Api, Db};
+
+    match err {
+        surrealdb::Error::Db(Db::IndexExists { .. } | Db::RecordExists { .. }) => true,
+        // Remote engines only send the message of the db error, rendered from the kinds above
+        surrealdb::Error::Api(Api::Query(message)) => {
+            (message.starts_with("Database index") && message.contains("already contains"))
+                || (message.starts_with("Database record") && message.ends_with("already exists"))
+        }
+        _ => false,
+    }
+}
+
+/// Errors of the statements of a transaction, every statement fails along with the transaction
+/// so a conflict is looked for first
+fn check_statements(
+    response: &mut surrealdb::Response,
+) -> Result<Result<(), surrealdb::Error>, WokUnknownError> {
+    let mut errors: Vec<_> = response.take_errors().into_iter().collect();
+    errors.sort_by_key(|(statement, _)| *statement);
+
+    if let Some(conflict) = errors.iter().position(|(_, err)| is_conflict(err)) {
+        return Ok(Err(errors.swap_remove(conflict).1));
+    }
+
+    match errors.into_iter().next() {
+        Some((_, err)) => Err(WokUnknownError::new(err)),
+        None => Ok(Ok(())),
+    }
+}
+
+/// Result of the `RETURN` ending a transaction, the statements before it may not be listed
+fn take_returned<T: serde::de::DeserializeOwned>(
+    response: &mut surrealdb::Response,
+) -> Result<Option<T>, WokUnknownError> {
+    let last = response.num_statements().saturating_sub(1);
+    Ok(response.take(last)?)
+}
+
+/// Shared by `UPDATE ... CONTENT` and `UPDATE ... MERGE`
+pub struct SurrealUpdate<'db, C: Connection, R: SurrealRecord, D> {
+    db: &'db Surreal<C>,
+    table: &'static str,
+    id: R,
+    data: D,
+    mode: &'static str,
+}
+
+impl<'db, C, R, D> crate::db::DbQuery<Result<(), DbUpdateError>> for SurrealUpdate<'db, C, R, D>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: AsSurrealBind + Send,
+{
+    async fn execute(self) -> Result<Result<(), DbUpdateError>, WokUnknownError> {
+        let data = SurrealSerialize(self.data);
+        let sql = format!(
+            "LET $updated = UPDATE type::thing($table, $id) {} $data RETURN NONE",
+            self.mode
+        );
+
+        // `UPDATE` does not create missing records, count them to tell if it existed. The
+        // transaction keeps the record from being created or deleted in between
+        let mut response = self
+            .db
+            .query("BEGIN TRANSACTION")
+            .query("LET $found = (SELECT count() FROM type::thing($table, $id))")
+            .query(sql)
+            .query("RETURN array::len($found)")
+            .query("COMMIT TRANSACTION")
+            .bind(("table", self.table))
+            .bind(("id", self.id))
+            .bind(("data", data))
+            .await?;
+
+        if check_statements(&mut response)?.is_err() {
+            return Ok(Err(DbUpdateError::Conflict));
+        }
+
+        let found: Option<usize> = take_returned(&mut response)?;
+        match found {
+            Some(0) | None => Ok(Err(DbUpdateError::NotFound)),
+            Some(_) => Ok(Ok(())),
+        }
+    }
+}
+
+impl<C, R, D> DbUpdate<R, D> for SurrealDb<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: AsSurrealBind,
+{
+    type UpdateQuery<'q> = SurrealUpdate<'q, C, R, D>;
+
+    fn update<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpdateQuery<'q> {
+        SurrealUpdate {
+            db: &self.0,
+            table,
+            id,
+            data,
+            mode: "CONTENT",
+        }
+    }
+}
+
+impl<C, R, D> DbMerge<R, D> for SurrealDb<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: AsSurrealBind,
+{
+    type MergeQuery<'q> = SurrealUpdate<'q, C, R, D>;
+
+    fn merge<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::MergeQuery<'q> {
+        SurrealUpdate {
+            db: &self.0,
+            table,
+            id,
+            data,
+            mode: "MERGE",
+        }
+    }
+}
+
+pub struct SurrealUpsert<'db, C: Connection, R: SurrealRecord, D> {
+    db: &'db Surreal<C>,
+    table: &'static str,
+    id: R,
+    data: D,
+}
+
+impl<'db, C, R, D> crate::db::DbQuery<Result<Upserted, DbUpsertError>>
+    for SurrealUpsert<'db, C, R, D>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: AsSurrealBind + Send,
+{
+    async fn execute(self) -> Result<Result<Upserted, DbUpsertError>, WokUnknownError> {
+        let data = SurrealSerialize(self.data);
+
+        // Counted in the same transaction, so the outcome matches what the upsert did
+        let mut response = self
+            .db
+            .query("BEGIN TRANSACTION")
+            .query("LET $found = (SELECT count() FROM type::thing($table, $id))")
+            .query("LET $upserted = UPSERT type::thing($table, $id) CONTENT $data RETURN NONE")
+            .query("RETURN array::len($found)")
+            .query("COMMIT TRANSACTION")
+            .bind(("table", self.table))
+            .bind(("id", self.id))
+            .bind(("data", data))
+            .await?;
+
+        if check_statements(&mut response)?.is_err() {
+            return Ok(Err(DbUpsertError::Conflict));
+        }
+
+        let found: Option<usize> = take_returned(&mut response)?;
+        match found {
+            Some(0) | None => Ok(Ok(Upserted::Created)),
+            Some(_) => Ok(Ok(Upserted::Updated)),
+        }
+    }
+}
+
+impl<C, R, D> DbUpsert<R, D> for SurrealDb<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: AsSurrealBind,
+{
+    type UpsertQuery<'q> = SurrealUpsert<'q, C, R, D>;
+
+    fn upsert<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpsertQuery<'q> {
+        SurrealUpsert {
+            db: &self.0,
+            table,
+            id,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod sql_tests {
+    use std::borrow::Cow;
+
+    use crate::{
+        db::FilterValue,
+        query::{Condition, Direction, Field, Order},
+    };
+
+    use super::{ConditionSql, SurrealParam, is_conflict, order_sql};
+
+    fn field(name: &'static str) -> Field<(), i64> {
+        Field::new(name)
+    }
+
+    fn compile(condition: Condition) -> (String, Vec<String>) {
+        let mut compiled = ConditionSql::default();
+        let sql = compiled.compile(condition);
+        let params = compiled
+            .params
+            .into_iter()
+            .map(|(name, param)| match param {
+                SurrealParam::Field(field) => format!("{name}: field {field}"),
+                SurrealParam::Value(value) => format!("{name}: {value:?}"),
+                SurrealParam::Values(values) => format!("{name}: {values:?}"),
+            })
+            .collect();
+
+        (sql, params)
+    }
+
+    #[test]
+    fn binds_fields_and_values() {
+        assert_eq!(
+            compile(field("age").ge(18)),
+            (
+                "type::field($p0) >= $p1".to_owned(),
+                vec!["p0: field age".to_owned(), "p1: Int(18)".to_owned()]
+            )
+        );
+
+        let (sql, params) = compile(Condition::Contains {
+            field: Cow::Borrowed("name; DELETE person"),
+            value: FilterValue::String("' OR true".to_owned()),
+        });
+        assert_eq!(sql, "type::field($p0) CONTAINS $p1");
+        assert_eq!(
+            params,
+            ["p0: field name; DELETE person", "p1: String(\"' OR true\")"]
+        );
+    }
+
+    #[test]
+    fn groups_and_negates_conditions() {
+        let condition = !(field("age").eq(1).or(field("age").lt(2)));
+        let (sql, params) = compile(condition.and(field("rank").ne(3)));
+
+        assert_eq!(
+            sql,
+            "(!((type::field($p0) = $p1) OR (type::field($p2) < $p3))) \
+             AND (type::field($p4) != $p5)"
+        );
+        assert_eq!(params.len(), 6);
+    }
+
+    #[test]
+    fn compiles_empty_groups_and_lists() {
+        assert_eq!(
+            compile(Condition::And(Vec::new())),
+            ("true".to_owned(), Vec::new())
+        );
+        assert_eq!(
+            compile(Condition::Or(Vec::new())),
+            ("false".to_owned(), Vec::new())
+        );
+        assert_eq!(
+            compile(field("age").is_in(Vec::new())),
+            (
+                "type::field($p0) IN $p1".to_owned(),
+                vec!["p0: field age".to_owned(), "p1: []".to_owned()]
+            )
+        );
+    }
+
+    #[test]
+    fn orders_by_field_paths_then_id() {
+        let order = |field: &'static str, direction| Order {
+            field: Cow::Borrowed(field),
+            direction,
+        };
+
+        assert_eq!(
+            order_sql(&[order("address.city", Direction::Desc)]).unwrap(),
+            " ORDER BY address.city DESC, id ASC"
+        );
+        assert_eq!(
+            order_sql(&[order("id", Direction::Desc)]).unwrap(),
+            " ORDER BY id DESC"
+        );
+        assert!(order_sql(&[order("name; DELETE person", Direction::Asc)]).is_err());
+    }
+
+    #[test]
+    fn detects_conflicts_in_remote_messages() {
+        use surrealdb::{
+            error::{Api, Db},
+            sql::Thing,
+        };
+
+        let remote = |err: Db| surrealdb::Error::Api(Api::Query(err.to_string()));
+        let thing = Thing::from(("person", "ana"));
+
+        assert!(is_conflict(&remote(Db::RecordExists {
+            thing: thing.clone()
+        })));
+        assert!(is_conflict(&remote(Db::IndexExists {
+            thing,
+            index: "person_name".to_owned(),
+            value: "['Ana']".to_owned(),
+        })));
+        assert!(!is_conflict(&remote(Db::QueryNotExecuted)));
+    }
+}
+
+#[cfg(all(test, feature = "surrealdb-mem"))]
+pub(super) mod tests {
+    use serde::{Deserialize, Serialize, de::Error};
+    use surrealdb::{Surreal, engine::local::Db};
+
+    use crate::{
+        Record,
+        db::{DbQuery, DbUpdateError, DbUpsertError, RecordDb, Upserted},
+        surrealdb::{AsSurrealBind, FromSurrealBind, StringFlavor, SurrealDb, SurrealRecord},
+    };
+
+    /// Stored as a string id, as `StringFlavor` expects
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub(crate) struct PersonId(pub u32);
+
+    impl Serialize for PersonId {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(&self.0)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for PersonId {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let id = String::deserialize(deserializer)?;
+            id.parse().map(PersonId).map_err(D::Error::custom)
+        }
+    }
+
+    impl Record for PersonId {
+        const TABLE: &'static str = "person";
+    }
+
+    impl SurrealRecord for PersonId {
+        type Flavor = StringFlavor;
+    }
+
+    #[derive(Debug, PartialEq, Serialize, AsSurrealBind, FromSurrealBind)]
+    pub(crate) struct Person {
+        pub name: String,
+        pub age: i64,
+    }
+
+    #[derive(Serialize, AsSurrealBind)]
+    struct PersonAge {
+        age: i64,
+    }
+
+    pub(crate) fn person(name: &str, age: i64) -> Person {
+        Person {
+            name: name.to_owned(),
+            age,
+        }
+    }
+
+    /// In memory db where person names are unique
+    pub(crate) async fn memory_db() -> SurrealDb<Db> {
+        let db = Surreal::new::<surrealdb::engine::local::Mem>(())
+            .await
+            .unwrap();
+        db.use_ns("test").use_db("test").await.unwrap();
+        db.query("DEFINE INDEX person_name ON person FIELDS name UNIQUE")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        SurrealDb::new(db)
+    }
+
+    #[tokio::test]
+    async fn upserts_report_whether_they_created_the_record() {
+        let db = memory_db().await;
+        let ana = PersonId(1);
+
+        let created = db.record().upsert(ana, person("Ana", 30)).execute().await;
+        assert_eq!(created.unwrap(), Ok(Upserted::Created));
+
+        let updated = db.record().upsert(ana, person("Ana", 31)).execute().await;
+        assert_eq!(updated.unwrap(), Ok(Upserted::Updated));
+
+        let found: Option<Person> = db.record().select(ana).execute().await.unwrap();
+        assert_eq!(found, Some(person("Ana", 31)));
+    }
+
+    #[tokio::test]
+    async fn merges_only_the_given_fields() {
+        let db = memory_db().await;
+        let ana = PersonId(1);
+        db.record()
+            .upsert(ana, person("Ana", 30))
+            .execute()
+            .await
+            .unwrap()
+            .unwrap();
+
+        let merged = db
+            .record()
+            .merge(ana, PersonAge { age: 31 })
+            .execute()
+            .await;
+        assert_eq!(merged.unwrap(), Ok(()));
+
+        let found: Option<Person> = db.record().select(ana).execute().await.unwrap();
+        assert_eq!(found, Some(person("Ana", 31)));
+
+        let missing = db
+            .record()
+            .merge(PersonId(2), PersonAge { age: 31 })
+            .execute()
+            .await;
+        assert_eq!(missing.unwrap(), Err(DbUpdateError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn reports_unique_index_conflicts() {
+        let db = memory_db().await;
+        let (ana, bob) = (PersonId(1), PersonId(2));
+        for (id, name) in [(ana, "Ana"), (bob, "Bob")] {
+            db.record()
+                .upsert(id, person(name, 30))
+                .execute()
+                .await
+                .unwrap()
+                .unwrap();
+        }
+
+        let updated = db.record().update(bob, person("Ana", 30)).execute().await;
+        assert_eq!(updated.unwrap(), Err(DbUpdateError::Conflict));
+
+        let upserted = db
+            .record()
+            .upsert(PersonId(3), person("Bob", 30))
+            .execute()
+            .await;
+        assert_eq!(upserted.unwrap(), Err(DbUpsertError::Conflict));
+
+        // Conflicting statements do not write anything
+        let found: Option<Person> = db.record().select(bob).execute().await.unwrap();
+        assert_eq!(found, Some(person("Bob", 30)));
+    }
+}
@@ -0,0 +1,174 @@
+use surrealdb::{Surreal, engine::local::Db};
+use wok::prelude::{ConfigureWorld, IntoSystem, Res, ResInit, RetryPolicy, WokUnknownError};
+
+use super::{SurrealConfig, SurrealDb, SurrealUseDb, use_surreal_db};
+
+#[derive(wok::prelude::Resource, Debug, Clone)]
+enum LocalEngine {
+    #[cfg(feature = "surrealdb-mem")]
+    Memory,
+    #[cfg(feature = "surrealdb-rocksdb")]
+    RocksDb(std::path::PathBuf),
+}
+
+/// Runs SurrealDB within the app as a `SurrealDb<Db>`, no server is needed
+/// Uses the `SurrealUseDb` and `SurrealConfig` resources, as `RemoteSurrealDbPlugin` does
+/// ```rust,ignore
+/// App::default()
+///     .add_plugin(AssetsOrigin(wok_assets::Env).load::<Env>()) // Loads `SurrealUseDb`
+///     .add_plugin(LocalSurrealDbPlugin::rocksdb("data/db"));
+/// ```
+pub struct LocalSurrealDbPlugin {
+    engine: LocalEngine,
+    retry: RetryPolicy,
+}
+
+impl LocalSurrealDbPlugin {
+    fn new(engine: LocalEngine) -> Self {
+        LocalSurrealDbPlugin {
+            engine,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    /// Records are lost once the app stops, such as for tests
+    #[cfg(feature = "surrealdb-mem")]
+    pub fn memory() -> Self {
+        Self::new(LocalEngine::Memory)
+    }
+
+    /// Records are kept in the `path` directory
+    #[cfg(feature = "surrealdb-rocksdb")]
+    pub fn rocksdb(path: impl Into<std::path::PathBuf>) -> Self {
+        Self::new(LocalEngine::RocksDb(path.into()))
+    }
+
+    /// Policy used to retry opening the db on startup, such as while another process holds
+    /// the files
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+}
+
+impl wok::prelude::Plugin for LocalSurrealDbPlugin {
+    fn setup(self, app: &mut wok::prelude::App) {
+        async fn start_local_surrealdb(
+            engine: Res<'_, LocalEngine>,
+            use_db: Option<Res<'_, SurrealUseDb>>,
+            config: Option<Res<'_, SurrealConfig>>,
+            mut surreal: ResInit<'_, SurrealDb<Db>>,
+        ) -> Result<(), WokUnknownError> {
+            let config = config.map(|c| c.0.clone()).unwrap_or_default();
+
+            let db = match &*engine {
+                #[cfg(feature = "surrealdb-mem")]
+                LocalEngine::Memory => {
+                    let db = Surreal::new::<surrealdb::engine::local::Mem>(config).await?;
+                    tracing::info!("Started in memory SurrealDB");
+                    db
+                }
+                #[cfg(feature = "surrealdb-rocksdb")]
+                LocalEngine::RocksDb(path) => {
+                    let db =
+                        Surreal::new::<surrealdb::engine::local::RocksDb>((path.clone(), config))
+                            .await?;
+                    tracing::info!("Opened SurrealDB at '{}'", path.display());
+                    db
+                }
+            };
+
+            use_surreal_db(&db, use_db.as_deref()).await?;
+            surreal.init(SurrealDb::new(db));
+
+            Ok(())
+        }
+
+        app.insert_resource(self.engine).add_systems(
+            wok::prelude::Startup,
+            start_local_surrealdb.retry(self.retry),
+        );
+    }
+}
+
+#[cfg(all(test, feature = "surrealdb-mem"))]
+mod tests {
+    use surrealdb::engine::local::Db;
+    use wok::{
+        prelude::{App, ConfigureApp, ConfigureWorld, Res, WokUnknownError},
+        setup::{RuntimeCfg, TokioRt},
+    };
+
+    use crate::{
+        RecordEntry,
+        db::{DbQuery, DbUpdateError, RecordDb, Upserted},
+        surrealdb::{
+            SurrealDb, SurrealUseDb,
+            crud::tests::{Person, PersonFields, PersonId, person},
+        },
+    };
+
+    use super::LocalSurrealDbPlugin;
+
+    async fn use_records(db: Res<'_, SurrealDb<Db>>) -> Result<(), WokUnknownError> {
+        let (ana, bob) = (PersonId(1), PersonId(2));
+
+        let upserted = db.record().upsert(ana, person("Ana", 30)).execute().await?;
+        assert_eq!(upserted, Ok(Upserted::Created));
+        let merged = db.record().merge(ana, person("Ana", 31)).execute().await?;
+        assert_eq!(merged, Ok(()));
+
+        db.transaction(|tx| async move {
+            let entry = RecordEntry {
+                id: bob,
+                data: person("Bob", 20),
+            };
+            tx.record().create(entry).execute().await?;
+            Ok(())
+        })
+        .await?;
+
+        let missing = db
+            .transaction(|tx| async move {
+                tx.record()
+                    .merge(PersonId(3), person("Cid", 40))
+                    .execute()
+                    .await??;
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(missing.downcast_ref(), Some(&DbUpdateError::NotFound));
+
+        let page: Vec<RecordEntry<PersonId, Person>> = db
+            .record()
+            .query()
+            .filter(
+                PersonFields::age()
+                    .gt(25)
+                    .or(PersonFields::name().eq("Bob".to_owned())),
+            )
+            .order_by(PersonFields::age().desc())
+            .limit(1)
+            .start(1)
+            .execute()
+            .await?;
+        assert_eq!(page.len(), 1);
+        assert_eq!((page[0].id, &page[0].data), (bob, &person("Bob", 20)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn serves_records_from_a_memory_db() {
+        App::default()
+            .insert_resource(SurrealUseDb {
+                database: "test".to_owned(),
+                namespace: "test".to_owned(),
+            })
+            .add_plugin(LocalSurrealDbPlugin::memory())
+            .run(RuntimeCfg::default().with_async(TokioRt), use_records)
+            .await
+            .unwrap();
+    }
+}
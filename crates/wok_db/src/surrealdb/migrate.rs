@@ -0,0 +1,89 @@
+use surrealdb::{Connection, Response};
+use wok::prelude::WokUnknownError;
+
+use crate::migrate::{AppliedMigration, DbMigrate, MIGRATIONS_TABLE, Migration, MigrationError};
+
+use super::SurrealDb;
+
+#[derive(serde::Deserialize)]
+struct AppliedRow {
+    version: i64,
+    name: String,
+    checksum: String,
+}
+
+/// Every statement of a failed transaction fails, the cause is the one not saying so
+fn transaction_result(mut response: Response) -> Result<(), WokUnknownError> {
+    let mut errors: Vec<_> = response.take_errors().into_iter().collect();
+    errors.sort_by_key(|(statement, _)| *statement);
+
+    let cause = errors
+        .iter()
+        .position(|(_, err)| !err.to_string().contains("failed transaction"))
+        .unwrap_or_default();
+
+    match errors.into_iter().nth(cause) {
+        Some((_, err)) => Err(WokUnknownError::new(err)),
+        None => Ok(()),
+    }
+}
+
+/// Migrations are records of `MIGRATIONS_TABLE` keyed by their version
+impl<C: Connection> DbMigrate for SurrealDb<C> {
+    async fn applied(&self) -> Result<Vec<AppliedMigration>, WokUnknownError> {
+        let rows: Vec<AppliedRow> = self
+            .0
+            .query("SELECT record::id(id) AS version, name, checksum FROM type::table($table)")
+            .bind(("table", MIGRATIONS_TABLE))
+            .await?
+            .take(0)?;
+
+        let applied = rows
+            .into_iter()
+            .map(|row| AppliedMigration {
+                version: row.version,
+                name: row.name,
+                checksum: row.checksum,
+            })
+            .collect();
+
+        Ok(applied)
+    }
+
+    async fn apply(&self, migration: &Migration) -> Result<(), WokUnknownError> {
+        let response = self
+            .0
+            .query("BEGIN TRANSACTION")
+            .query(migration.up.to_string())
+            .query(
+                "CREATE type::thing($table, $version) \
+                 CONTENT { name: $name, checksum: $checksum } RETURN NONE",
+            )
+            .query("COMMIT TRANSACTION")
+            .bind(("table", MIGRATIONS_TABLE))
+            .bind(("version", migration.version))
+            .bind(("name", migration.name.to_string()))
+            .bind(("checksum", migration.checksum()))
+            .await?;
+
+        transaction_result(response)
+    }
+
+    async fn revert(&self, migration: &Migration) -> Result<(), WokUnknownError> {
+        let Some(down) = &migration.down else {
+            return Err(MigrationError::Irreversible(migration.version).into());
+        };
+
+        let response = self
+            .0
+            .query("BEGIN TRANSACTION")
+            .query(down.to_string())
+            .query("DELETE type::thing($table, $version) RETURN NONE")
+            .query("COMMIT TRANSACTION")
+            .bind(("table", MIGRATIONS_TABLE))
+            .bind(("version", migration.version))
+            .await?;
+
+        transaction_result(response)
+    }
+}
@@ -56,4 +56,3 @@ pub struct ThingOwned<R> {
 pub enum IdOwned<R> {
     String(R),
 }
-
@@ -0,0 +1,409 @@
+use std::sync::{Arc, Mutex};
+
+use surrealdb::{Connection, Surreal, method::Query};
+use wok::prelude::WokUnknownError;
+
+use crate::{
+    RecordEntry,
+    db::{
+        DbCreate, DbDelete, DbDeleteError, DbMerge, DbQuery, DbTransaction, DbUpdate,
+        DbUpdateError, RecordDb,
+    },
+};
+
+use super::{AsSurrealBind, KeyValue, SurrealDb, SurrealRecord, SurrealSerialize, crud};
+
+/// Thrown by the statements checking the record exists, rolls back the transaction
+const NOT_FOUND: &str = "wok_db: record not found";
+
+/// Adds the statements of a query, its params are named after the given prefix so they do
+/// not clash with the other queries of the transaction
+type Step<C> = Box<dyn for<'q, 'p> FnOnce(Query<'q, C>, &'p str) -> Query<'q, C> + Send>;
+
+struct TxState<C: Connection> {
+    steps: Vec<Step<C>>,
+    committed: bool,
+}
+
+/// Queues queries to send them as a single `BEGIN TRANSACTION ... COMMIT TRANSACTION` request
+/// Only writes are supported: creates with an id, updates, merges and deletes
+pub struct SurrealTransaction<C: Connection>(Arc<Mutex<TxState<C>>>);
+
+impl<C: Connection> Clone for SurrealTransaction<C> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<C: Connection> SurrealTransaction<C> {
+    fn push(&self, step: Step<C>) -> Result<(), WokUnknownError> {
+        let mut state = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        if state.committed {
+            return Err(WokUnknownError::from_message(
+                "the transaction was already committed",
+            ));
+        }
+
+        state.steps.push(step);
+        Ok(())
+    }
+}
+
+impl<C: Connection> RecordDb for SurrealTransaction<C> {}
+
+impl<C: Connection> DbTransaction for SurrealDb<C> {
+    type Transaction = SurrealTransaction<C>;
+    type CommitQuery<'q> = SurrealCommit<'q, C>;
+
+    fn begin(&self) -> Self::Transaction {
+        SurrealTransaction(Arc::new(Mutex::new(TxState {
+            steps: Vec::new(),
+            committed: false,
+        })))
+    }
+
+    fn commit<'q>(&'q self, tx: Self::Transaction) -> Self::CommitQuery<'q> {
+        SurrealCommit { db: &self.0, tx }
+    }
+}
+
+pub struct SurrealCommit<'db, C: Connection> {
+    db: &'db Surreal<C>,
+    tx: SurrealTransaction<C>,
+}
+
+impl<'db, C: Connection> DbQuery<()> for SurrealCommit<'db, C> {
+    async fn execute(self) -> Result<(), WokUnknownError> {
+        let steps = {
+            let mut state = self.tx.0.lock().unwrap_or_else(|err| err.into_inner());
+            state.committed = true;
+            std::mem::take(&mut state.steps)
+        };
+
+        if steps.is_empty() {
+            return Ok(());
+        }
+
+        let mut query = self.db.query("BEGIN TRANSACTION");
+        for (i, step) in steps.into_iter().enumerate() {
+            query = step(query, &format!("tx{i}_"));
+        }
+
+        let mut response = query.query("COMMIT TRANSACTION").await?;
+
+        // Every statement fails along with the transaction, look for the cause first
+        let mut errors: Vec<_> = response.take_errors().into_iter().collect();
+        errors.sort_by_key(|(statement, _)| *statement);
+
+        if errors.iter().any(|(_, err)| is_not_found(err)) {
+            return Err(WokUnknownError::new(DbUpdateError::NotFound).with_status(404));
+        }
+
+        if errors.iter().any(|(_, err)| crud::is_conflict(err)) {
+            return Err(WokUnknownError::new(DbUpdateError::Conflict).with_status(409));
+        }
+
+        match errors.into_iter().next() {
+            Some((_, err)) => Err(WokUnknownError::new(err)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Whether the statement is the `THROW` of `check_exists`
+fn is_not_found(err: &surrealdb::Error) -> bool {
+    use surrealdb::error::{Api, Db};
+
+    match err {
+        surrealdb::Error::Db(Db::Thrown(message)) => message == NOT_FOUND,
+        // Remote engines only send the message of the db error
+        surrealdb::Error::Api(Api::Query(message)) => {
+            *message == Db::Thrown(NOT_FOUND.to_owned()).to_string()
+        }
+        _ => false,
+    }
+}
+
+/// Query queued by `execute`, its output is known before the transaction runs
+/// Updates, merges and deletes always return `Ok(())`, their errors only come out of the commit
+pub struct SurrealQueued<C: Connection, O> {
+    tx: SurrealTransaction<C>,
+    step: Step<C>,
+    output: O,
+}
+
+impl<C: Connection, O: Send> DbQuery<O> for SurrealQueued<C, O> {
+    async fn execute(self) -> Result<O, WokUnknownError> {
+        self.tx.push(self.step)?;
+        Ok(self.output)
+    }
+}
+
+/// Boxes a step, the bound lets closures be generic over the query lifetime
+fn step<C, F>(step: F) -> Step<C>
+where
+    C: Connection,
+    F: for<'q, 'p> FnOnce(Query<'q, C>, &'p str) -> Query<'q, C> + Send + 'static,
+{
+    Box::new(step)
+}
+
+/// Checks the record of the `table` and `id` params exists
+fn check_exists(p: &str) -> String {
+    format!(
+        "IF array::len((SELECT count() FROM type::thing(${p}table, ${p}id))) == 0 \
+         {{ THROW \"{NOT_FOUND}\" }}"
+    )
+}
+
+fn create<C, D>(table: &'static str, data: D) -> Step<C>
+where
+    C: Connection,
+    D: AsSurrealBind + Send + 'static,
+{
+    step(move |query, p| {
+        query
+            .query(format!(
+                "CREATE type::table(${p}table) CONTENT ${p}data RETURN NONE"
+            ))
+            .bind((format!("{p}table"), table))
+            .bind((format!("{p}data"), SurrealSerialize(data)))
+    })
+}
+
+/// `mode` is either `CONTENT` or `MERGE`
+fn update<C, R, D>(table: &'static str, id: R, data: D, mode: &'static str) -> Step<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: AsSurrealBind + Send + 'static,
+{
+    step(move |query, p| {
+        query
+            .query(check_exists(p))
+            .query(format!(
+                "UPDATE type::thing(${p}table, ${p}id) {mode} ${p}data RETURN NONE"
+            ))
+            .bind((format!("{p}table"), table))
+            .bind((format!("{p}id"), id))
+            .bind((format!("{p}data"), SurrealSerialize(data)))
+    })
+}
+
+/// The id is generated before the transaction, records without one are not supported
+impl<C, R, B> DbCreate<R, KeyValue<R, B>> for SurrealTransaction<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    B: AsSurrealBind + Send + 'static,
+{
+    type CreateQuery<'q> = SurrealQueued<C, R>;
+
+    fn create<'q>(&'q self, table: &'static str, data: KeyValue<R, B>) -> Self::CreateQuery<'q> {
+        SurrealQueued {
+            tx: self.clone(),
+            output: data.id,
+            step: create(table, data),
+        }
+    }
+}
+
+impl<C, R, B> DbCreate<R, RecordEntry<R, B>> for SurrealTransaction<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    B: AsSurrealBind + Send + 'static,
+{
+    type CreateQuery<'q> = SurrealQueued<C, R>;
+
+    fn create<'q>(&'q self, table: &'static str, data: RecordEntry<R, B>) -> Self::CreateQuery<'q> {
+        SurrealQueued {
+            tx: self.clone(),
+            output: data.id,
+            step: create(table, data),
+        }
+    }
+}
+
+/// Missing records roll back the transaction instead of returning `DbUpdateError::NotFound`
+impl<C, R, D> DbUpdate<R, D> for SurrealTransaction<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: AsSurrealBind + Send + 'static,
+{
+    type UpdateQuery<'q> = SurrealQueued<C, Result<(), DbUpdateError>>;
+
+    fn update<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::UpdateQuery<'q> {
+        SurrealQueued {
+            tx: self.clone(),
+            step: update(table, id, data, "CONTENT"),
+            output: Ok(()),
+        }
+    }
+}
+
+/// Missing records roll back the transaction instead of returning `DbUpdateError::NotFound`
+impl<C, R, D> DbMerge<R, D> for SurrealTransaction<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+    D: AsSurrealBind + Send + 'static,
+{
+    type MergeQuery<'q> = SurrealQueued<C, Result<(), DbUpdateError>>;
+
+    fn merge<'q>(&'q self, table: &'static str, id: R, data: D) -> Self::MergeQuery<'q> {
+        SurrealQueued {
+            tx: self.clone(),
+            step: update(table, id, data, "MERGE"),
+            output: Ok(()),
+        }
+    }
+}
+
+/// Missing records roll back the transaction instead of returning `DbDeleteError::None`
+impl<C, R> DbDelete<R> for SurrealTransaction<C>
+where
+    C: Connection,
+    R: SurrealRecord,
+{
+    type DeleteQuery<'q> = SurrealQueued<C, Result<(), DbDeleteError>>;
+
+    fn delete<'q>(&'q self, table: &'static str, id: R) -> Self::DeleteQuery<'q> {
+        let step = step(move |query, p| {
+            query
+                .query(check_exists(p))
+                .query(format!("DELETE type::thing(${p}table, ${p}id) RETURN NONE"))
+                .bind((format!("{p}table"), table))
+                .bind((format!("{p}id"), id))
+        });
+
+        SurrealQueued {
+            tx: self.clone(),
+            step,
+            output: Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "surrealdb-mem"))]
+mod tests {
+    use wok::prelude::WokUnknownError;
+
+    use crate::{
+        RecordEntry,
+        db::{DbQuery, DbUpdateError, RecordDb},
+        surrealdb::crud::tests::{Person, PersonId, memory_db, person},
+    };
+
+    #[tokio::test]
+    async fn commits_queued_queries_together() {
+        let db = memory_db().await;
+        let (ana, bob) = (PersonId(1), PersonId(2));
+        db.record()
+            .upsert(bob, person("Bob", 30))
+            .execute()
+            .await
+            .unwrap()
+            .unwrap();
+
+        db.transaction(|tx| async move {
+            let entry = RecordEntry {
+                id: ana,
+                data: person("Ana", 30),
+            };
+            tx.record().create(entry).execute().await?;
+            tx.record()
+                .update(bob, person("Bob", 31))
+                .execute()
+                .await??;
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        let found: Option<Person> = db.record().select(ana).execute().await.unwrap();
+        assert_eq!(found, Some(person("Ana", 30)));
+        let found: Option<Person> = db.record().select(bob).execute().await.unwrap();
+        assert_eq!(found, Some(person("Bob", 31)));
+    }
+
+    #[tokio::test]
+    async fn rolls_back_when_a_record_is_missing() {
+        let db = memory_db().await;
+        let ana = PersonId(1);
+
+        let err = db
+            .transaction(|tx| async move {
+                let entry = RecordEntry {
+                    id: ana,
+                    data: person("Ana", 30),
+                };
+                tx.record().create(entry).execute().await?;
+                // Queued, so only the commit reports the missing record
+                let queued = tx.record().delete(PersonId(2)).execute().await?;
+                assert!(queued.is_ok());
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.downcast_ref(), Some(&DbUpdateError::NotFound));
+        let found: Option<Person> = db.record().select(ana).execute().await.unwrap();
+        assert_eq!(found, None);
+    }
+
+    #[tokio::test]
+    async fn rolls_back_on_conflicts() {
+        let db = memory_db().await;
+        let (ana, bob) = (PersonId(1), PersonId(2));
+        db.record()
+            .upsert(ana, person("Ana", 30))
+            .execute()
+            .await
+            .unwrap()
+            .unwrap();
+
+        let err = db
+            .transaction(|tx| async move {
+                tx.record()
+                    .merge(ana, person("Ana", 31))
+                    .execute()
+                    .await??;
+                let entry = RecordEntry {
+                    id: bob,
+                    data: person("Ana", 30),
+                };
+                tx.record().create(entry).execute().await?;
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.downcast_ref(), Some(&DbUpdateError::Conflict));
+        let found: Option<Person> = db.record().select(ana).execute().await.unwrap();
+        assert_eq!(found, Some(person("Ana", 30)));
+    }
+
+    #[tokio::test]
+    async fn writes_nothing_when_the_steps_fail() {
+        let db = memory_db().await;
+        let ana = PersonId(1);
+
+        let err = db
+            .transaction(|tx| async move {
+                let entry = RecordEntry {
+                    id: ana,
+                    data: person("Ana", 30),
+                };
+                tx.record().create(entry).execute().await?;
+                Err::<(), _>(WokUnknownError::from_message("steps failed"))
+            })
+            .await
+            .unwrap_err();
+
+        assert!(err.to_string().ends_with("steps failed"));
+        let found: Option<Person> = db.record().select(ana).execute().await.unwrap();
+        assert_eq!(found, None);
+    }
+}
@@ -1,4 +1,4 @@
-use derime::{span_compile_error, CompileError};
+use derime::{CompileError, span_compile_error};
 use proc_macro2::Ident;
 use quote::quote;
 use syn::{Index, spanned::Spanned};
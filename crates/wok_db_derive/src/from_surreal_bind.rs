@@ -1,4 +1,4 @@
-use derime::{CompileError, span_compile_error};
+use derime::{CompileError, SerdeField, span_compile_error};
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
 use syn::{DeriveInput, Index, spanned::Spanned};
@@ -29,7 +29,7 @@ pub fn from_surreal_db_derive(ast: DeriveInput) -> Result<TokenStream, CompileEr
 
     let (og_impl_generics, og_ty_generics, og_where_clause) = ast.generics.split_for_impl();
 
-    let (mapping_impl, struct_body_mapped) = match &struct_data.fields {
+    let (mapping_impl, struct_body_mapped, field_descriptors) = match &struct_data.fields {
         syn::Fields::Unit => {
             return Err(span_compile_error!(span => "Unit structs are not supported"));
         }
@@ -52,6 +52,29 @@ pub fn from_surreal_db_derive(ast: DeriveInput) -> Result<TokenStream, CompileEr
             quote! { #(#serde_attrs)* #name: <#ty as #trait_path>::Bind }
         });
 
+            let descriptors = fields
+                .named
+                .iter()
+                .map(|field| {
+                    let name = field.ident.as_ref().expect("named field");
+                    let ty = &field.ty;
+                    let field_vis = &field.vis;
+
+                    let serde = SerdeField::parse(field)?;
+                    if serde.flatten || serde.skip {
+                        return Ok(None);
+                    }
+
+                    let db_name = serde.name(name);
+
+                    Ok(Some(quote! {
+                        #field_vis const fn #name() -> ::wok_db::query::Field<#thing_name #og_ty_generics, #ty> {
+                            ::wok_db::query::Field::new(#db_name)
+                        }
+                    }))
+                })
+                .collect::<Result<Vec<_>, CompileError>>()?;
+
             let mapping = quote! {
                 #thing_name {
                     #(#fields_map,)*
@@ -64,7 +87,24 @@ pub fn from_surreal_db_derive(ast: DeriveInput) -> Result<TokenStream, CompileEr
                 }
             };
 
-            (mapping, struct_body_mapped)
+            // On their own type, so they cannot clash with methods of the record data
+            let fields_name = format_ident!("{}Fields", thing_name);
+            let descriptors = match descriptors.iter().any(Option::is_some) {
+                true => quote! {
+                    #[allow(dead_code)]
+                    #vis struct #fields_name #og_impl_generics (
+                        ::core::marker::PhantomData<fn() -> #thing_name #og_ty_generics>
+                    ) #og_where_clause;
+
+                    #[allow(dead_code)]
+                    impl #og_impl_generics #fields_name #og_ty_generics #og_where_clause {
+                        #(#descriptors)*
+                    }
+                },
+                false => TokenStream::new(),
+            };
+
+            (mapping, struct_body_mapped, descriptors)
         }
 
         syn::Fields::Unnamed(fields) => {
@@ -95,7 +135,7 @@ pub fn from_surreal_db_derive(ast: DeriveInput) -> Result<TokenStream, CompileEr
                 );
             };
 
-            (mapping, struct_body_mapped)
+            (mapping, struct_body_mapped, TokenStream::new())
         }
     };
 
@@ -109,6 +149,8 @@ pub fn from_surreal_db_derive(ast: DeriveInput) -> Result<TokenStream, CompileEr
                 #mapping_impl
             }
         }
+
+        #field_descriptors
     };
 
     Ok(result)
@@ -127,9 +169,9 @@ mod tests {
         let output = from_surreal_db_derive(input);
         let expected = quote! {
             #[derive(serde::Deserialize)]
-            pub struct SurrealFoo(<u32 as FromSurrealBind>::Bind,);
+            pub struct FooFromSurrealBind(<u32 as FromSurrealBind>::Bind,);
             impl FromSurrealBind for Foo {
-                type Bind = SurrealFoo;
+                type Bind = FooFromSurrealBind;
                 fn from_bind(bind: Self::Bind) -> Self {
                     Foo(<u32 as FromSurrealBind>::from_bind(bind.0),)
                 }
@@ -151,17 +193,26 @@ mod tests {
         let output = from_surreal_db_derive(input);
         let expected = quote! {
             #[derive(serde::Deserialize)]
-            pub struct SurrealFoo {
+            pub struct FooFromSurrealBind {
                 bar: <u32 as FromSurrealBind>::Bind,
             }
             impl FromSurrealBind for Foo {
-                type Bind = SurrealFoo;
+                type Bind = FooFromSurrealBind;
                 fn from_bind(bind: Self::Bind) -> Self {
                     Foo {
                         bar: <u32 as FromSurrealBind>::from_bind(bind.bar),
                     }
                 }
             }
+            #[allow(dead_code)]
+            pub struct FooFields(::core::marker::PhantomData<fn() -> Foo>);
+
+            #[allow(dead_code)]
+            impl FooFields {
+                const fn bar() -> ::wok_db::query::Field<Foo, u32> {
+                    ::wok_db::query::Field::new("bar")
+                }
+            }
         };
 
         assert!(output.is_ok(), "{:?}", output.unwrap_err());
@@ -180,12 +231,12 @@ mod tests {
         let output = from_surreal_db_derive(input);
         let expected = quote! {
             #[derive(serde::Deserialize)]
-            pub struct SurrealFoo {
+            pub struct FooFromSurrealBind {
                 #[serde(flatten)]
                 inner: <FooInner as FromSurrealBind>::Bind,
             }
             impl FromSurrealBind for Foo {
-                type Bind = SurrealFoo;
+                type Bind = FooFromSurrealBind;
                 fn from_bind(bind: Self::Bind) -> Self {
                     Foo {
                         inner: <FooInner as FromSurrealBind>::from_bind(bind.inner),
@@ -197,4 +248,54 @@ mod tests {
         assert!(output.is_ok(), "{:?}", output.unwrap_err());
         assert_eq!(output.unwrap().to_string(), expected.to_string());
     }
+
+    #[test]
+    fn generates_renamed_field_descriptors() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            pub struct Foo {
+                #[serde(rename = "full_name", default)]
+                pub name: String,
+            }
+        };
+
+        let output = from_surreal_db_derive(input);
+        let expected = quote! {
+            impl FooFields {
+                pub const fn name() -> ::wok_db::query::Field<Foo, String> {
+                    ::wok_db::query::Field::new("full_name")
+                }
+            }
+        };
+
+        assert!(output.is_ok(), "{:?}", output.unwrap_err());
+        assert!(
+            output.unwrap().to_string().ends_with(&expected.to_string()),
+            "descriptor named after the serde rename"
+        );
+    }
+
+    #[test]
+    fn generates_generic_field_descriptors() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            pub struct Foo<T: Clone> {
+                pub bar: T,
+            }
+        };
+
+        let output = from_surreal_db_derive(input);
+        let expected = quote! {
+            #[allow(dead_code)]
+            pub struct FooFields<T: Clone>(::core::marker::PhantomData<fn() -> Foo<T> >);
+
+            #[allow(dead_code)]
+            impl<T: Clone> FooFields<T> {
+                pub const fn bar() -> ::wok_db::query::Field<Foo<T>, T> {
+                    ::wok_db::query::Field::new("bar")
+                }
+            }
+        };
+
+        assert!(output.is_ok(), "{:?}", output.unwrap_err());
+        assert!(output.unwrap().to_string().ends_with(&expected.to_string()));
+    }
 }
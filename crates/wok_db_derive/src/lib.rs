@@ -18,5 +18,33 @@ pub fn from_surreal_bind_derive(input: proc_macro::TokenStream) -> proc_macro::T
     }
 }
 
+#[proc_macro_derive(AsSqlRow, attributes(serde))]
+pub fn as_sql_row_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    match sql_row::as_sql_row_derive(ast) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => proc_macro2::TokenStream::from(err).into(),
+    }
+}
+
+#[proc_macro_derive(FromSqlRow, attributes(serde))]
+pub fn from_sql_row_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    match sql_row::from_sql_row_derive(ast) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => proc_macro2::TokenStream::from(err).into(),
+    }
+}
+
+#[proc_macro_derive(SqlColumn)]
+pub fn sql_column_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+    match sql_row::sql_column_derive(ast) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => proc_macro2::TokenStream::from(err).into(),
+    }
+}
+
 mod as_surreal_bind;
 mod from_surreal_bind;
+mod sql_row;
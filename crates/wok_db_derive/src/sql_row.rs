@@ -0,0 +1,209 @@
+use derime::{CompileError, SerdeField, span_compile_error};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, FieldsNamed, spanned::Spanned};
+
+fn named_fields<'a>(ast: &'a DeriveInput, derive: &str) -> Result<&'a FieldsNamed, CompileError> {
+    let span = ast.span();
+    match &ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => Ok(fields),
+        _ => Err(span_compile_error!(
+            span => "{} can only be derived on structs with named fields, derive SqlColumn on newtypes",
+            derive
+        )),
+    }
+}
+
+pub fn as_sql_row_derive(ast: DeriveInput) -> Result<TokenStream, CompileError> {
+    let fields = named_fields(&ast, "AsSqlRow")?;
+    let thing_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let pushes = fields
+        .named
+        .iter()
+        .map(|field| {
+            let name = field.ident.as_ref().expect("named field");
+            let serde = SerdeField::parse(field)?;
+
+            if serde.skip {
+                return Ok(TokenStream::new());
+            }
+
+            if serde.flatten {
+                return Ok(quote! {
+                    columns.extend(::wok_db::sqlx::AsSqlRow::sql_columns(&self.#name));
+                });
+            }
+
+            let column = serde.name(name);
+            let push = quote! {
+                columns.push((#column, ::wok_db::sqlx::SqlColumn::to_sql(&self.#name)));
+            };
+
+            Ok(match serde.skip_serializing_if {
+                Some(skip_if) => quote! { if !#skip_if(&self.#name) { #push } },
+                None => push,
+            })
+        })
+        .collect::<Result<Vec<_>, CompileError>>()?;
+
+    Ok(quote! {
+        impl #impl_generics ::wok_db::sqlx::AsSqlRow for #thing_name #ty_generics #where_clause {
+            fn sql_columns(&self) -> ::std::vec::Vec<(&'static str, ::wok_db::sqlx::SqlValue)> {
+                let mut columns = ::std::vec::Vec::new();
+                #(#pushes)*
+                columns
+            }
+        }
+    })
+}
+
+pub fn from_sql_row_derive(ast: DeriveInput) -> Result<TokenStream, CompileError> {
+    let fields = named_fields(&ast, "FromSqlRow")?;
+    let thing_name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let fields_map = fields
+        .named
+        .iter()
+        .map(|field| {
+            let name = field.ident.as_ref().expect("named field");
+            let serde = SerdeField::parse(field)?;
+
+            if serde.skip {
+                return Ok(quote! { #name: ::std::default::Default::default() });
+            }
+
+            if serde.flatten {
+                return Ok(quote! { #name: ::wok_db::sqlx::FromSqlRow::from_row(row)? });
+            }
+
+            let column = serde.name(name);
+            Ok(quote! { #name: ::wok_db::sqlx::SqlColumn::from_sql(row, #column)? })
+        })
+        .collect::<Result<Vec<_>, CompileError>>()?;
+
+    Ok(quote! {
+        impl #impl_generics ::wok_db::sqlx::FromSqlRow for #thing_name #ty_generics #where_clause {
+            fn from_row(
+                row: &::wok_db::sqlx::AnyRow,
+            ) -> ::std::result::Result<Self, ::wok_db::sqlx::SqlxError> {
+                ::std::result::Result::Ok(#thing_name {
+                    #(#fields_map,)*
+                })
+            }
+        }
+    })
+}
+
+pub fn sql_column_derive(ast: DeriveInput) -> Result<TokenStream, CompileError> {
+    let span = ast.span();
+    let field = match &ast.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Unnamed(fields),
+            ..
+        }) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+        _ => {
+            return Err(span_compile_error!(span => "SqlColumn can only be derived on newtypes"));
+        }
+    };
+
+    let thing_name = &ast.ident;
+    let ty = &field.ty;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::wok_db::sqlx::SqlColumn for #thing_name #ty_generics #where_clause {
+            fn to_sql(&self) -> ::wok_db::sqlx::SqlValue {
+                <#ty as ::wok_db::sqlx::SqlColumn>::to_sql(&self.0)
+            }
+
+            fn from_sql(
+                row: &::wok_db::sqlx::AnyRow,
+                column: &str,
+            ) -> ::std::result::Result<Self, ::wok_db::sqlx::SqlxError> {
+                <#ty as ::wok_db::sqlx::SqlColumn>::from_sql(row, column).map(#thing_name)
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_as_sql_row() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            pub struct Foo {
+                #[serde(rename = "full_name")]
+                name: String,
+                #[serde(skip_serializing_if = "Option::is_none")]
+                age: Option<u8>,
+                #[serde(flatten)]
+                inner: FooInner,
+            }
+        };
+
+        let output = as_sql_row_derive(input);
+        let expected = quote! {
+            impl ::wok_db::sqlx::AsSqlRow for Foo {
+                fn sql_columns(&self) -> ::std::vec::Vec<(&'static str, ::wok_db::sqlx::SqlValue)> {
+                    let mut columns = ::std::vec::Vec::new();
+                    columns.push(("full_name", ::wok_db::sqlx::SqlColumn::to_sql(&self.name)));
+                    if !Option::is_none(&self.age) {
+                        columns.push(("age", ::wok_db::sqlx::SqlColumn::to_sql(&self.age)));
+                    }
+                    columns.extend(::wok_db::sqlx::AsSqlRow::sql_columns(&self.inner));
+                    columns
+                }
+            }
+        };
+
+        assert!(output.is_ok(), "{:?}", output.unwrap_err());
+        assert_eq!(output.unwrap().to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn generates_from_sql_row() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            pub struct Foo {
+                name: String,
+                #[serde(skip)]
+                cache: Vec<u8>,
+            }
+        };
+
+        let output = from_sql_row_derive(input);
+        let expected = quote! {
+            impl ::wok_db::sqlx::FromSqlRow for Foo {
+                fn from_row(
+                    row: &::wok_db::sqlx::AnyRow,
+                ) -> ::std::result::Result<Self, ::wok_db::sqlx::SqlxError> {
+                    ::std::result::Result::Ok(Foo {
+                        name: ::wok_db::sqlx::SqlColumn::from_sql(row, "name")?,
+                        cache: ::std::default::Default::default(),
+                    })
+                }
+            }
+        };
+
+        assert!(output.is_ok(), "{:?}", output.unwrap_err());
+        assert_eq!(output.unwrap().to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn rejects_sql_column_on_named_structs() {
+        let input: syn::DeriveInput = syn::parse_quote! {
+            pub struct Foo {
+                name: String,
+            }
+        };
+
+        assert!(sql_column_derive(input).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Tonic integration, gRPC methods are systems
+//! ```rust,ignore
+//! use wok::prelude::*;
+//! use wok_tonic::{GrpcAddr, GrpcMethod, TonicPlugin, TonicServicePlugin};
+//!
+//! struct GreeterPlugin;
+//! impl Plugin for GreeterPlugin {
+//!     fn setup(self, app: &mut App) {
+//!         let greeter = Greeter {
+//!             say_hello: GrpcMethod::new(app, say_hello),
+//!         };
+//!
+//!         app.add_plugin(TonicServicePlugin(GreeterServer::new(greeter)));
+//!     }
+//! }
+//!
+//! async fn say_hello(
+//!     In(request): In<Request<HelloRequest>>,
+//!     greetings: Res<'_, Greetings>,
+//! ) -> Result<Response<HelloReply>, Status> {
+//!     // ...
+//! }
+//!
+//! App::default()
+//!     .add_plugin(TonicPlugin)
+//!     .add_plugin(GreeterPlugin)
+//!     .insert_resource(GrpcAddr("[::1]:50051".parse().unwrap()))
+//!     .run(RuntimeCfg::default().with_async(TokioRt), wok_tonic::serve)
+//!     .await;
+//! ```
+
+mod method;
+
+use std::{
+    convert::Infallible,
+    task::{Context, Poll},
+};
+
+use tonic::{server::NamedService, service::RoutesBuilder};
+use tower_service::Service;
+use wok::{
+    plugin::Plugin,
+    prelude::{ConfigureWorld, Res, ResMut, Resource, WokUnknownError},
+    remote_gateway::{RemoteWorldPorts, RemoteWorldRef},
+};
+
+pub use method::*;
+
+/// Services of the gRPC server
+#[derive(Resource, Default)]
+#[resource(mutable = true)]
+pub struct GrpcRoutes(pub RoutesBuilder);
+
+/// Address of the gRPC server
+#[derive(Resource, serde::Deserialize, Debug, Clone, Copy)]
+#[serde(transparent)]
+pub struct GrpcAddr(pub std::net::SocketAddr);
+
+/// Setups the resources of the gRPC server, use before any `TonicServicePlugin`
+pub struct TonicPlugin;
+
+impl Plugin for TonicPlugin {
+    fn setup(self, app: &mut wok::prelude::App) {
+        app.init_resource::<GrpcRoutes>();
+    }
+}
+
+/// Adds a tonic service, usually a generated `*Server` of `GrpcMethod`s
+pub struct TonicServicePlugin<S>(pub S);
+
+impl<S> Plugin for TonicServicePlugin<S>
+where
+    S: Service<
+            http::Request<tonic::body::Body>,
+            Response = http::Response<tonic::body::Body>,
+            Error = Infallible,
+        > + NamedService
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    S::Future: Send + 'static,
+{
+    fn setup(self, app: &mut wok::prelude::App) {
+        app.world_mut()
+            .get::<ResMut<'_, GrpcRoutes>>()
+            .0
+            .add_service(self.0);
+    }
+}
+
+/// Main runtime for wok_tonic
+/// Requires the `TonicPlugin` & a `GrpcAddr` resource
+pub async fn serve(
+    world: RemoteWorldRef<'_>,
+    addr: Res<'_, GrpcAddr>,
+    mut routes: ResMut<'_, GrpcRoutes>,
+) -> Result<(), WokUnknownError> {
+    let world = world.upgrade().expect("the app to be active");
+    let routes = std::mem::take(&mut routes.0).routes();
+
+    tracing::info!("gRPC listening on: {}", addr.0);
+
+    tonic::transport::Server::builder()
+        .layer(WorldLayer(world))
+        .add_routes(routes)
+        .serve(addr.0)
+        .await
+        .map_err(WokUnknownError::new)
+}
+
+/// Inserts the world ports in the request extensions, `GrpcMethod`s read them from there
+#[derive(Clone)]
+struct WorldLayer(RemoteWorldPorts);
+
+impl<S> tower_layer::Layer<S> for WorldLayer {
+    type Service = WorldService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        WorldService {
+            inner,
+            world: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct WorldService<S> {
+    inner: S,
+    world: RemoteWorldPorts,
+}
+
+impl<S, B> Service<http::Request<B>> for WorldService<S>
+where
+    S: Service<http::Request<B>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<B>) -> Self::Future {
+        req.extensions_mut().insert(self.world.clone());
+        self.inner.call(req)
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use tonic::{Code, Request, Response, Status};
+use wok::{
+    prelude::{
+        App, BorrowMutParam, ConfigureWorld, In, IntoSystem, ProtoTaskSystem, System,
+        WokUnknownError,
+    },
+    remote_gateway::RemoteWorldPorts,
+};
+
+type RpcFuture<Res> = BoxFuture<'static, Result<Response<Res>, Status>>;
+type CallRpc<Req, Res> = dyn Fn(RemoteWorldPorts, Request<Req>) -> RpcFuture<Res> + Send + Sync;
+
+/// RPC method backed by a system, call it from the tonic service trait
+/// The system gets the request as `In<Request<Req>>`, its params are reserved for each call
+/// ```rust,ignore
+/// struct Greeter {
+///     say_hello: GrpcMethod<HelloRequest, HelloReply>,
+/// }
+///
+/// #[tonic::async_trait]
+/// impl greeter_server::Greeter for Greeter {
+///     async fn say_hello(
+///         &self,
+///         request: Request<HelloRequest>,
+///     ) -> Result<Response<HelloReply>, Status> {
+///         self.say_hello.call(request).await
+///     }
+/// }
+/// ```
+pub struct GrpcMethod<Req, Res> {
+    call: Arc<CallRpc<Req, Res>>,
+}
+
+impl<Req, Res> Clone for GrpcMethod<Req, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            call: self.call.clone(),
+        }
+    }
+}
+
+impl<Req: Send + 'static, Res: 'static> GrpcMethod<Req, Res> {
+    pub fn new<S, Marker>(app: &mut App, system: S) -> Self
+    where
+        S: IntoSystem<Marker>,
+        S::System: ProtoTaskSystem<Param: BorrowMutParam>
+            + System<In = In<Request<Req>>, Out: IntoGrpcResponse<Res>>,
+    {
+        let system = app.world_mut().center.register_system(system.into_system());
+
+        let call = move |world: RemoteWorldPorts, request: Request<Req>| {
+            let system = system.clone();
+            let fut: RpcFuture<Res> = Box::pin(async move {
+                world
+                    .reserver()
+                    .reserve(system.entry_ref())
+                    .await
+                    .task()
+                    .run(request)
+                    .await
+                    .into_grpc_response()
+            });
+
+            fut
+        };
+
+        Self {
+            call: Arc::new(call),
+        }
+    }
+
+    /// Runs the system, the request must come from a service of the `TonicPlugin` server
+    pub async fn call(&self, request: Request<Req>) -> Result<Response<Res>, Status> {
+        let Some(world) = request.extensions().get::<RemoteWorldPorts>().cloned() else {
+            return Err(Status::internal("the service is not served by wok_tonic"));
+        };
+
+        (self.call)(world, request).await
+    }
+}
+
+pub trait IntoGrpcResponse<Res>: Send + 'static {
+    #[allow(
+        clippy::result_large_err,
+        reason = "tonic services return `Status` by value"
+    )]
+    fn into_grpc_response(self) -> Result<Response<Res>, Status>;
+}
+
+impl<Res: Send + 'static> IntoGrpcResponse<Res> for Result<Response<Res>, Status> {
+    fn into_grpc_response(self) -> Result<Response<Res>, Status> {
+        self
+    }
+}
+
+/// Responds with the code matching the status hint of the error, defaults to `Internal`
+impl<Res: Send + 'static> IntoGrpcResponse<Res> for Result<Response<Res>, WokUnknownError> {
+    fn into_grpc_response(self) -> Result<Response<Res>, Status> {
+        self.map_err(status_from_error)
+    }
+}
+
+fn status_from_error(err: WokUnknownError) -> Status {
+    let code = match err.status() {
+        Some(400) => Code::InvalidArgument,
+        Some(401) => Code::Unauthenticated,
+        Some(403) => Code::PermissionDenied,
+        Some(404) => Code::NotFound,
+        Some(409) => Code::AlreadyExists,
+        Some(412) => Code::FailedPrecondition,
+        Some(429) => Code::ResourceExhausted,
+        Some(501) => Code::Unimplemented,
+        Some(503) => Code::Unavailable,
+        Some(504) => Code::DeadlineExceeded,
+        _ => Code::Internal,
+    };
+
+    // Only client errors are meant to be read by the client
+    if code == Code::Internal {
+        tracing::error!(%err);
+        Status::new(code, "internal error")
+    } else {
+        tracing::debug!(%err);
+        Status::new(code, err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_status_hints_to_codes() {
+        let not_found = WokUnknownError::from_message("no such user").with_status(404);
+        let status = status_from_error(not_found);
+
+        assert_eq!(status.code(), Code::NotFound);
+        assert!(status.message().contains("no such user"));
+
+        let status = status_from_error(WokUnknownError::from_message("db is down"));
+        assert_eq!(status.code(), Code::Internal);
+        assert_eq!(status.message(), "internal error");
+    }
+}
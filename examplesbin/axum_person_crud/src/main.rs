@@ -1,6 +1,6 @@
 #![allow(dead_code)] // for demo
 use wok::{prelude::*, setup::*};
-use wok_axum::AxumPlugin;
+use wok_axum::{AxumPlugin, openapi::OpenApiPlugin};
 
 // a resource system for demo purposes
 #[derive(Resource)]
@@ -15,6 +15,8 @@ async fn main() -> Result<(), MainError> {
         // a resource system for demo purposes
         .insert_resource(EnvMode::Dev)
         .add_plugin(AxumPlugin)
+        // Documents every route below at GET /openapi.json
+        .add_plugin(OpenApiPlugin::new("/openapi.json").title("Persons & colors"))
         .add_plugin(config::ConfigPlugin)
         .add_plugin(persons::PersonsPlugin)
         .add_plugin(colors::ColorsPlugin)
@@ -42,6 +44,11 @@ fn validate_dev_localhost(
     Ok(())
 }
 
+// Conditions are read-only blocking systems, this one gates systems with `.run_if(is_dev)`
+fn is_dev(env_mode: Res<'_, EnvMode>) -> bool {
+    matches!(env_mode.as_ref(), EnvMode::Dev)
+}
+
 mod config {
     use surrealdb::engine::remote::ws::{Client, Ws};
     use wok::prelude::*;
@@ -111,7 +118,7 @@ mod persons {
                 .add_plugin(factory.get_one::<Person>())
                 // .add_plugin(factory.create_one::<Person>()) // We are implementing this one ourselves for
                 // demo purposes
-                .add_systems(Route("/persons"), post(create_one));
+                .add_systems(Route("/persons"), post(create_one).documented());
         }
     }
 
@@ -183,13 +190,12 @@ mod persons {
 }
 
 mod colors {
-    use axum::{Json, extract::Query};
     use valigate::gates::MinLen;
     use wok::prelude::*;
-    use wok_axum::{Route, crud::CrudConfig, get};
+    use wok_axum::crud::CrudConfig;
     use wok_db::{
-        RecordGenerate,
-        db::{DbQuery, RecordDb},
+        RecordEntry, RecordGenerate,
+        db::{DbQuery, FilterType, RecordDb},
         surrealdb::{AsSurrealBind, FromSurrealBind},
     };
 
@@ -200,10 +206,19 @@ mod colors {
         fn setup(self, app: &mut App) {
             let factory = db_config_factory().for_record::<ColorId>();
 
-            app.add_systems(Route("/colors"), get(list))
-                .add_plugin(factory.delete_one())
-                .add_plugin(factory.get_one::<Color>())
-                .add_plugin(factory.create_one::<Color>());
+            // GET /colors?name=Red&limit=10&after=<id>
+            app.add_plugin(
+                factory
+                    .list_all::<Color>()
+                    .filterable(&[("name", FilterType::String)]),
+            )
+            .add_plugin(factory.update_one::<Color>())
+            .add_plugin(factory.patch_one::<ColorPatch>())
+            .add_plugin(factory.delete_one())
+            .add_plugin(factory.get_one::<Color>())
+            .add_plugin(factory.create_one::<Color>())
+            // Demo data is only seeded in dev mode
+            .add_systems(Startup, seed_demo_data.run_if(crate::is_dev));
         }
     }
 
@@ -232,33 +247,56 @@ mod colors {
         value: String,
     }
 
+    // Absent fields are left untouched on PATCH
+    #[derive(valigate::Valid, AsSurrealBind, serde::Serialize)]
+    #[gate(serde = true)]
+    struct ColorPatch {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<ColorName>,
+        #[gate(skip = true)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        value: Option<String>,
+    }
+
     #[derive(valigate::Valid, AsSurrealBind, FromSurrealBind, serde::Serialize)]
     #[gate(gate = MinLen(1))]
     pub struct ColorName(String);
 
-    #[derive(serde::Deserialize)]
-    struct QueryFilter {
-        name: Option<String>,
-    }
+    async fn seed_demo_data(db: Res<'_, Db>) -> Result<(), WokUnknownError> {
+        // Field descriptors are generated on `ColorFields` by the `FromSurrealBind` derive
+        let seeded: Vec<Color> = db
+            .record::<ColorId>()
+            .query()
+            .filter(ColorFields::name().contains("Red"))
+            .limit(1)
+            .execute()
+            .await?;
+
+        if !seeded.is_empty() {
+            return Ok(());
+        }
 
-    // Can manually implement listing if needed
-    async fn list(
-        In(Query(query)): In<Query<QueryFilter>>,
-        db: Res<'_, Db>,
-    ) -> Result<Json<Vec<Color>>, WokUnknownError> {
-        let colors = if let Some(name) = query.name {
-            // Can always use underline surrealdb API to create complex queries
-            let mut response =
-                db.0.query("SELECT * FROM color WHERE name CONTAINS $name")
-                    .bind(("name", name))
-                    .await?;
-
-            let colors: Vec<<Color as FromSurrealBind>::Bind> = response.take(0)?;
-            colors.into_iter().map(Color::from_bind).collect()
-        } else {
-            db.record::<ColorId>().list::<Color>().execute().await?
-        };
-
-        Ok(Json(colors))
+        // Either every color is seeded or none
+        db.transaction(|tx| async move {
+            for (name, value) in [
+                ("Red", "#ff0000"),
+                ("Green", "#00ff00"),
+                ("Blue", "#0000ff"),
+            ] {
+                let color = Color {
+                    name: ColorName(name.to_string()),
+                    value: value.to_string(),
+                };
+
+                let entry = RecordEntry {
+                    id: ColorId::generate(),
+                    data: color,
+                };
+                tx.record::<ColorId>().create(entry).execute().await?;
+            }
+
+            Ok(())
+        })
+        .await
     }
 }
@@ -1,13 +1,16 @@
+use std::panic::Location;
+
 use futures::FutureExt;
 use wok_core::{
     async_executor::AsyncExecutor,
-    error::WokUnknownError,
-    prelude::{IntoBlockingSystem, IntoSystem, System, TaskSystem},
+    error::{WokUnknownError, into_unknown},
+    prelude::{In, IntoBlockingSystem, IntoSystem, ResMut, System, TaskSystem},
     runtime::RuntimeAddon,
     world::{ConfigureWorld, UnsafeMutState, World, WorldCenter, gateway::SystemEntry},
 };
 
 use crate::{
+    plugin::{Plugin, PluginTrail},
     runtime::{RuntimeBuilder, RuntimeCfg},
     startup::Startup,
 };
@@ -21,18 +24,31 @@ impl Default for App {
         let mut world = World::default();
         Startup::init(&mut world);
 
-        Self { world }
+        Self {
+            world: world.init_resource::<PluginTrail>(),
+        }
     }
 }
 
 impl App {
+    fn setup_plugin<P: Plugin>(&mut self, plugin: P) {
+        let name = std::any::type_name::<P>();
+        self.world.get::<ResMut<PluginTrail>>().push(name);
+
+        plugin.setup(self);
+
+        self.world.get::<ResMut<PluginTrail>>().pop();
+    }
+
     pub async fn run<Marker, AsyncRt: AsyncExecutor, RtAddon: RuntimeAddon>(
         self,
         cfg: RuntimeCfg<AsyncRt, RtAddon>,
         system: impl AppSystem<Marker>,
     ) -> Result<(), WokUnknownError> {
-        let mut state = self.world.state;
-        let mut center = self.world.center;
+        // Timers go through the executor so systems stay runtime agnostic
+        let world = self.world.insert_resource(cfg.async_runtime.timer());
+        let mut state = world.state;
+        let mut center = world.center;
 
         // Run addon build before startup to allow the use of ParamsClient
         let (addon, rests) = RtAddon::create(&mut state);
@@ -84,17 +100,21 @@ pub trait AppSystem<Marker> {
 #[doc(hidden)]
 pub struct TaskAppSystem;
 
-impl<Marker, S> AppSystem<(Marker, TaskAppSystem)> for S
+impl<Marker, S, E> AppSystem<(Marker, fn() -> E, TaskAppSystem)> for S
 where
     S: IntoSystem<Marker>,
-    S::System: System<In = (), Out = Result<(), WokUnknownError>>,
+    S::System: System<In = (), Out = Result<(), E>>,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
 {
+    #[track_caller]
     unsafe fn app_run(
         self,
         state: &UnsafeMutState,
         center: &mut WorldCenter,
     ) -> impl Future<Output = Result<(), WokUnknownError>> + 'static {
-        let system = center.register_system(self.into_system());
+        let location = Location::caller();
+        let system = self.map(move |In(result): In<Result<(), E>>| into_unknown(result, location));
+        let system = center.register_system(system.into_system());
         let mut world = unsafe { state.borrow_world_mut(&mut center.system_locks) };
 
         let fut = world
@@ -164,7 +184,7 @@ pub trait ConfigureApp: ConfigureWorld {
 
 impl ConfigureApp for App {
     fn add_plugin(mut self, plugin: impl crate::plugin::Plugin) -> Self {
-        plugin.setup(&mut self);
+        self.setup_plugin(plugin);
         self
     }
 }
@@ -181,7 +201,7 @@ impl ConfigureWorld for &mut App {
 
 impl ConfigureApp for &mut App {
     fn add_plugin(self, plugin: impl crate::plugin::Plugin) -> Self {
-        plugin.setup(&mut *self);
+        self.setup_plugin(plugin);
         self
     }
 }
@@ -7,7 +7,9 @@ pub mod tokio {
 
     use futures::FutureExt;
 
-    use wok_core::async_executor::{AsyncExecutor, AsyncExecutorabel, FutSpawnError, JoinHandle};
+    use wok_core::async_executor::{
+        AsyncExecutor, AsyncExecutorabel, FutSpawnError, JoinHandle, Timer,
+    };
 
     pub struct TokioRt;
 
@@ -41,6 +43,10 @@ pub mod tokio {
         {
             TokioJoinHandle(self.0.spawn_blocking(func))
         }
+
+        fn timer(&self) -> Timer {
+            Timer::new(tokio::time::sleep)
+        }
     }
 
     pub struct TokioJoinHandle<Out: Send + 'static>(tokio::task::JoinHandle<Out>);
@@ -16,7 +16,7 @@ pub mod setup {
     pub use crate::run::{DefaultPlugins, runtime};
     pub use crate::runtime::RuntimeCfg;
     pub use wok_core::async_executor::AsyncExecutorabel;
-    pub use wok_core::error::MainError;
+    pub use wok_core::error::{MainError, MainExit};
 }
 
 pub mod app;
@@ -1,3 +1,5 @@
+use wok_core::prelude::Resource;
+
 use crate::app::App;
 
 pub trait Plugin {
@@ -7,3 +9,23 @@ pub trait Plugin {
 impl Plugin for () {
     fn setup(self, _: &mut App) {}
 }
+
+/// Plugins being set up, outermost first. Used to tell where a startup system comes from
+#[derive(Default, Resource)]
+#[resource(usage = lib, mutable = true)]
+pub(crate) struct PluginTrail(Vec<&'static str>);
+
+impl PluginTrail {
+    pub(crate) fn push(&mut self, plugin: &'static str) {
+        self.0.push(plugin);
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.0.pop();
+    }
+
+    /// `None` outside of any plugin
+    pub(crate) fn describe(&self) -> Option<String> {
+        (!self.0.is_empty()).then(|| self.0.join(" > "))
+    }
+}
@@ -1,15 +1,16 @@
 use crate::prelude::Param;
-use std::{collections::VecDeque, sync::Arc};
+use std::{collections::VecDeque, marker::PhantomData, sync::Arc};
 
 use futures::{FutureExt, channel::oneshot};
 use wok_core::{
     prelude::{
-        BorrowMutParam, BorrowTaskSystem, DynTaskSystem, ProtoTaskSystem, Res, Resource, SystemIn,
-        SystemInput,
+        BorrowMutParam, BorrowTaskSystem, DynTaskSystem, ParamGetError, ProtoTaskSystem, Res,
+        Resource, SystemIn, SystemInput,
     },
+    resources::Immutable,
     runtime::RuntimeAddon,
     world::{
-        SystemId, UnsafeWorldState, WeakState,
+        SystemId, UnsafeWorldState, WeakState, World,
         gateway::{
             ReleaseSystem, SystemEntryRef, SystemReleaseRx, SystemReleaser, WeakSystemReleaser,
         },
@@ -70,6 +71,82 @@ impl RemoteWorldPorts {
             gateway: &self.locking,
         }
     }
+
+    /// Reserves the params registered in `params` until the permit is dropped
+    pub async fn reserve_params<P: BorrowMutParam>(
+        &self,
+        params: RemoteParams<P>,
+    ) -> ParamsPermit<'_, P> {
+        let releaser = self.reserver().lock(params.id).await;
+
+        ParamsPermit {
+            state: &self.state,
+            _releaser: releaser,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Locks of `P` registered ahead of time, to get short lived world access outside of a system,
+/// such as in a response stream
+/// Init it in the world and take it with `Res<'_, RemoteParams<P>>`
+pub struct RemoteParams<P> {
+    id: SystemId,
+    _marker: PhantomData<fn() -> P>,
+}
+
+impl<P> Clone for RemoteParams<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for RemoteParams<P> {}
+
+impl<P: Param + 'static> Resource for RemoteParams<P> {
+    type Mutability = Immutable;
+}
+
+impl<P: BorrowMutParam + 'static> RemoteParams<P> {
+    pub fn init(world: &mut World) {
+        if world.state.resources.handle_ref::<Self>().is_some() {
+            return;
+        }
+
+        let id = world.center.register_params::<P>();
+        world.state.resources.insert(Self {
+            id,
+            _marker: PhantomData,
+        });
+    }
+}
+
+pub struct ParamsPermit<'w, P> {
+    state: &'w UnsafeWorldState,
+    _releaser: ReleaseSystem,
+    _marker: PhantomData<fn() -> P>,
+}
+
+impl<'w, P: BorrowMutParam> ParamsPermit<'w, P> {
+    /// Borrows the reserved params, fails when one of their resources is not in the world
+    pub fn try_get(&mut self) -> Result<P::AsRef<'_>, ParamGetError> {
+        // Safety: Already checked with locks
+        unsafe { P::borrow(self.state) }
+    }
+
+    /// Borrows the reserved params
+    ///
+    /// # Panics
+    /// When one of their resources is not in the world, use `try_get` to handle it
+    pub fn get(&mut self) -> P::AsRef<'_> {
+        match self.try_get() {
+            Ok(params) => params,
+            Err(err) => panic!(
+                "Could not get param `{}`: {err}",
+                std::any::type_name::<P>()
+            ),
+        }
+    }
 }
 
 #[derive(Param)]
@@ -98,11 +175,21 @@ pub struct RemoteSystemReserver<'w> {
 
 impl<'w> RemoteSystemReserver<'w> {
     pub async fn reserve<S>(&self, system: SystemEntryRef<'w, S>) -> SystemPermit<'w, S> {
+        let releaser = self.lock(system.id).await;
+
+        SystemPermit {
+            state: self.state,
+            system: system.system,
+            releaser,
+        }
+    }
+
+    async fn lock(&self, system_id: SystemId) -> ReleaseSystem {
         let (tx, rx) = oneshot::channel();
 
         let request = LockRequest {
             respond_to: tx,
-            system_id: system.id,
+            system_id,
         };
         self.gateway
             .locker
@@ -112,11 +199,7 @@ impl<'w> RemoteSystemReserver<'w> {
 
         rx.await.expect("to receive confirmation");
 
-        SystemPermit {
-            state: self.state,
-            system: system.system,
-            releaser: ReleaseSystem::new(system.id, self.gateway.releaser.clone()),
-        }
+        ReleaseSystem::new(system_id, self.gateway.releaser.clone())
     }
 }
 
@@ -206,20 +289,77 @@ impl RuntimeAddon for RemoteGatewayRuntime {
         _async_executor: &impl wok_core::async_executor::AsyncExecutor,
         state: &mut wok_core::world::gateway::RemoteWorldMut<'_>,
     ) {
-        while let Some(req) = self.buf.iter().next() {
-            let result = state.world_mut().locks.try_lock(req.system_id);
-            if result.is_err() {
-                let req = self.buf.pop_front().unwrap();
+        // One pass, conflicting requests wait for the next release
+        for _ in 0..self.buf.len() {
+            let req = self.buf.pop_front().unwrap();
+            if state.world_mut().locks.try_lock(req.system_id).is_err() {
                 self.buf.push_back(req);
-
                 continue;
             }
-            drop(result);
 
-            let req = self.buf.pop_front().unwrap();
             if req.respond_to.send(()).is_err() {
                 state.world_mut().release(req.system_id);
             }
         }
     }
 }
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    use super::*;
+    use crate::{
+        prelude::*,
+        setup::{RuntimeCfg, TokioRt},
+    };
+
+    #[derive(Resource)]
+    #[resource(usage = lib)]
+    struct Present(u32);
+
+    #[derive(Resource)]
+    #[resource(usage = lib)]
+    struct Missing;
+
+    async fn read_params(
+        world: RemoteWorldRef<'_>,
+        present: Res<'_, RemoteParams<Res<'static, Present>>>,
+        missing: Res<'_, RemoteParams<Res<'static, Missing>>>,
+    ) -> Result<(), WokUnknownError> {
+        let world = world.upgrade().expect("to have a world");
+
+        let mut permit = world.reserve_params(*present).await;
+        assert_eq!(permit.try_get().map(|present| present.0).ok(), Some(1));
+        assert_eq!(permit.get().0, 1);
+        drop(permit);
+
+        let mut permit = world.reserve_params(*missing).await;
+        assert!(permit.try_get().is_err());
+        assert!(
+            catch_unwind(AssertUnwindSafe(|| {
+                permit.get();
+            }))
+            .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn params_permit_reports_missing_resources() {
+        let mut app = App::default().insert_resource(Present(1));
+        RemoteParams::<Res<'static, Present>>::init(app.world_mut());
+        RemoteParams::<Res<'static, Missing>>::init(app.world_mut());
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            app.run(RuntimeCfg::default().with_async(TokioRt), read_params)
+                .await
+        })
+        .unwrap();
+    }
+}
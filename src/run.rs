@@ -1,6 +1,8 @@
+use std::panic::Location;
+
 use wok_core::{
-    error::WokUnknownError,
-    prelude::{BorrowMutParam, IntoSystem, ProtoTaskSystem, Res, ResMut, Resource, System},
+    error::{WokUnknownError, into_unknown},
+    prelude::{BorrowMutParam, In, IntoSystem, ProtoTaskSystem, Res, ResMut, Resource, System},
     schedule::{ScheduleConfigure, ScheduleLabel, Systems},
     world::ConfigureWorld,
 };
@@ -18,14 +20,18 @@ pub struct RunSystems(Systems<(), Result<(), WokUnknownError>>);
 #[doc(hidden)]
 pub struct FallibleRun;
 
-impl<Marker, S> ScheduleConfigure<S, (FallibleRun, Marker)> for Run
+impl<Marker, S, E> ScheduleConfigure<S, (FallibleRun, fn() -> E, Marker)> for Run
 where
     S: IntoSystem<Marker>,
-    S::System:
-        System<In = (), Out = Result<(), WokUnknownError>> + ProtoTaskSystem<Param: BorrowMutParam>,
+    S::System: System<In = (), Out = Result<(), E>> + ProtoTaskSystem<Param: BorrowMutParam>,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
 {
+    #[track_caller]
     fn add(self, world: &mut wok_core::world::World, system: S) {
-        let system = system.into_system();
+        let location = Location::caller();
+        let system = system
+            .map(move |In(result): In<Result<(), E>>| into_unknown(result, location))
+            .into_system();
         let entry = world.register_system(system);
 
         let mut systems = world.get::<ResMut<RunSystems>>();
@@ -41,7 +47,7 @@ where
     S::System: System<In = (), Out = ()> + ProtoTaskSystem<Param: BorrowMutParam>,
 {
     fn add(self, world: &mut wok_core::world::World, system: S) {
-        self.add(world, system.map(|| Ok(())));
+        self.add(world, system.map(|| Ok::<_, WokUnknownError>(())));
     }
 }
 
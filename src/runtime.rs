@@ -173,6 +173,12 @@ impl<'w, Addon: RuntimeAddon> Runtime<'w, Addon> {
                 system_id = release_fut.fuse() => {
                     if let Some(system_id) = system_id {
                         self.locks.release(system_id);
+
+                        // Retry the lock requests waiting on it
+                        if let Some(releaser) = self.releaser.as_ref() {
+                            let mut remote = WorldBorrowMut::new(self.state, self.locks).with_remote(releaser);
+                            self.foreign_rt.act(async_executor, &mut remote);
+                        }
                     }
                     else {
                         release_recv_open = false;
@@ -1,9 +1,13 @@
+use std::panic::Location;
+
 use futures::{StreamExt, stream::FuturesUnordered};
 use wok_core::{
-    error::WokUnknownError,
+    error::{WokUnknownError, into_unknown},
     prelude::{
-        DynBlockingSystem, IntoBlockingSystem, IntoSystem, ResMut, Resource, System, TaskSystem,
+        DynBlockingSystem, In, IntoBlockingSystem, IntoSystem, Res, ResMut, Resource, System,
+        TaskSystem,
     },
+    resources::ResourceId,
     schedule::{
         ScheduleConfigure, ScheduleLabel, dependency_graph::SystemsMutationDependencyGraph,
     },
@@ -15,6 +19,8 @@ use wok_core::{
 
 use wok_core::async_executor::AsyncExecutor;
 
+use crate::plugin::PluginTrail;
+
 #[derive(Default, Resource)]
 #[resource(usage = lib, mutable = true)]
 struct StartupSystems {
@@ -37,15 +43,44 @@ impl ScheduleLabel for Startup {}
 pub struct InlineStartup;
 impl ScheduleLabel for InlineStartup {}
 
+/// Describes a startup system and the plugins that added it, so failures point back to their origin
+fn startup_frame<S>(world: &mut World) -> String {
+    let mut frame = format!("startup system `{}`", std::any::type_name::<S>());
+
+    let trail = world.get::<Option<Res<PluginTrail>>>();
+    if let Some(trail) = trail.as_deref().and_then(PluginTrail::describe) {
+        frame.push_str(" (plugin ");
+        frame.push_str(&trail);
+        frame.push(')');
+    }
+
+    frame
+}
+
+/// Typed errors are located where the system was added
+fn with_frame<E: Into<WokUnknownError> + 'static>(
+    result: Result<(), E>,
+    frame: &str,
+    location: &'static Location<'static>,
+) -> Result<(), WokUnknownError> {
+    into_unknown(result, location).map_err(|err| err.frame(frame.to_owned()))
+}
+
 #[doc(hidden)]
 pub struct FallibleStartup;
-impl<Marker, S> ScheduleConfigure<S, (FallibleStartup, Marker)> for Startup
+impl<Marker, S, E> ScheduleConfigure<S, (FallibleStartup, fn() -> E, Marker)> for Startup
 where
     S: IntoSystem<Marker> + 'static,
-    S::System: System<In = (), Out = Result<(), WokUnknownError>>,
+    S::System: System<In = (), Out = Result<(), E>>,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
 {
+    #[track_caller]
     fn add(self, world: &mut wok_core::world::World, system: S) {
-        let system = system.into_system();
+        let frame = startup_frame::<S>(world);
+        let location = Location::caller();
+        let system = system
+            .map(move |In(result): In<Result<(), E>>| with_frame(result, &frame, location))
+            .into_system();
         let systemid = world.register_system_ref(&system);
 
         let mut systems = world.get::<ResMut<StartupSystems>>();
@@ -65,7 +100,7 @@ where
     S::System: System<In = (), Out = ()>,
 {
     fn add(self, world: &mut wok_core::world::World, system: S) {
-        let system = system.map(|| Ok(()));
+        let system = system.map(|| Ok::<_, WokUnknownError>(()));
         self.add(world, system);
     }
 }
@@ -73,13 +108,20 @@ where
 #[doc(hidden)]
 pub struct BlockingStartup;
 
-impl<Marker, S> ScheduleConfigure<S, (FallibleStartup, BlockingStartup, Marker)> for Startup
+impl<Marker, S, E> ScheduleConfigure<S, (FallibleStartup, BlockingStartup, fn() -> E, Marker)>
+    for Startup
 where
     S: IntoBlockingSystem<Marker>,
-    S::System: System<In = (), Out = Result<(), WokUnknownError>>,
+    S::System: System<In = (), Out = Result<(), E>>,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
 {
+    #[track_caller]
     fn add(self, world: &mut wok_core::world::World, system: S) {
-        let system = system.into_system();
+        let frame = startup_frame::<S>(world);
+        let location = Location::caller();
+        let system = system
+            .pipe(move |In(result): In<Result<(), E>>| with_frame(result, &frame, location))
+            .into_system();
         let systemid = world.register_system_ref(&system);
 
         let mut systems = world.get::<ResMut<StartupSystems>>();
@@ -97,18 +139,24 @@ where
     S::System: System<In = (), Out = ()>,
 {
     fn add(self, world: &mut wok_core::world::World, system: S) {
-        let system = system.pipe(|| Ok(()));
+        let system = system.pipe(|| Ok::<_, WokUnknownError>(()));
         self.add(world, system);
     }
 }
 
-impl<Marker, S> ScheduleConfigure<S, (InlineStartup, Marker)> for InlineStartup
+impl<Marker, S, E> ScheduleConfigure<S, (InlineStartup, fn() -> E, Marker)> for InlineStartup
 where
     S: IntoBlockingSystem<Marker>,
-    S::System: System<In = (), Out = Result<(), WokUnknownError>>,
+    S::System: System<In = (), Out = Result<(), E>>,
+    E: Into<WokUnknownError> + Send + Sync + 'static,
 {
+    #[track_caller]
     fn add(self, world: &mut wok_core::world::World, system: S) {
-        let system = system.into_system();
+        let frame = startup_frame::<S>(world);
+        let location = Location::caller();
+        let system = system
+            .pipe(move |In(result): In<Result<(), E>>| with_frame(result, &frame, location))
+            .into_system();
         let systemid = world.register_system_ref(&system);
 
         let mut systems = world.get::<ResMut<StartupSystems>>();
@@ -126,7 +174,7 @@ where
     S::System: System<In = (), Out = ()>,
 {
     fn add(self, world: &mut wok_core::world::World, system: S) {
-        let system = system.pipe(|| Ok(()));
+        let system = system.pipe(|| Ok::<_, WokUnknownError>(()));
         self.add(world, system);
     }
 }
@@ -136,6 +184,30 @@ impl Startup {
         world.init_resource::<StartupSystems>();
     }
 
+    /// Whether a startup system added so far reads or writes `R`, systems only inserting it
+    /// such as with `ResInit` are not counted
+    /// Conflicting startup systems run in the order they were added, so these run before any
+    /// system writing `R` added later
+    pub fn has_access<R: Resource>(world: &mut World) -> bool {
+        let resource = ResourceId::new::<R>();
+        let pendings = match world.get::<Option<Res<StartupSystems>>>() {
+            Some(systems) => systems.pendings.clone(),
+            None => return false,
+        };
+
+        pendings.into_iter().any(|id| {
+            world
+                .center
+                .system_locks
+                .systems_rw
+                .get(id)
+                .is_some_and(|rw| {
+                    rw.has_resource_read(resource)
+                        || (rw.has_resource_write(resource) && !rw.has_resource_init(resource))
+                })
+        })
+    }
+
     pub fn create_invoker<'w, C: AsyncExecutor>(
         center: &'w mut WorldCenter,
         state: &'w mut WorldState,
@@ -273,3 +345,104 @@ impl<'w, C: AsyncExecutor> StartupInvoke<'w, C> {
         }
     }
 }
+
+#[cfg(all(test, feature = "tokio"))]
+mod tests {
+    use std::any::{type_name, type_name_of_val};
+
+    use crate::{
+        prelude::*,
+        setup::{RuntimeCfg, TokioRt},
+    };
+
+    struct DatabasePlugin;
+    impl Plugin for DatabasePlugin {
+        fn setup(self, app: &mut App) {
+            app.add_systems(Startup, connect);
+        }
+    }
+
+    struct ServerPlugin;
+    impl Plugin for ServerPlugin {
+        fn setup(self, app: &mut App) {
+            app.add_plugin(DatabasePlugin);
+        }
+    }
+
+    async fn connect() -> Result<(), WokUnknownError> {
+        Err(WokUnknownError::from_message("connection refused")).context("dialing the database")
+    }
+
+    async fn serve() -> Result<(), WokUnknownError> {
+        Ok(())
+    }
+
+    #[derive(Debug)]
+    struct MigrationFailed;
+
+    impl std::fmt::Display for MigrationFailed {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("migration failed")
+        }
+    }
+
+    impl std::error::Error for MigrationFailed {}
+
+    async fn migrate() -> Result<(), MigrationFailed> {
+        Err(MigrationFailed)
+    }
+
+    const SEED_LINE: u32 = line!() + 3;
+
+    async fn seed() -> Result<(), WokUnknownError> {
+        Err(MigrationFailed)?;
+        Ok(())
+    }
+
+    fn run_err(app: App) -> WokUnknownError {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            app.run(RuntimeCfg::default().with_async(TokioRt), serve)
+                .await
+        })
+        .unwrap_err()
+    }
+
+    #[test]
+    fn startup_errors_name_the_system_and_its_plugins() {
+        let err = run_err(App::default().add_plugin(ServerPlugin));
+
+        let frame = format!(
+            "startup system `{}` (plugin {} > {})",
+            type_name_of_val(&connect),
+            type_name::<ServerPlugin>(),
+            type_name::<DatabasePlugin>()
+        );
+        let contexts: Vec<_> = err.contexts().map(|(message, _)| message).collect();
+        assert_eq!(contexts, [frame.as_str(), "dialing the database"]);
+
+        let report = format!("{err:#}");
+        assert!(report.starts_with(&format!(
+            "{frame}\n\nCaused by:\n    0: dialing the database\n"
+        )));
+        assert!(report.contains("\n    1: connection refused\n"));
+    }
+
+    #[test]
+    fn startup_errors_point_to_the_failing_system() {
+        let line = line!() + 1;
+        let err = run_err(App::default().add_systems(Startup, migrate));
+
+        assert!(err.is::<MigrationFailed>());
+        assert_eq!(err.location().file(), file!());
+        assert_eq!(err.location().line(), line);
+
+        let err = run_err(App::default().add_systems(Startup, seed));
+
+        assert_eq!(err.location().file(), file!());
+        assert_eq!(err.location().line(), SEED_LINE);
+    }
+}